@@ -0,0 +1,8 @@
+fn main() {
+    // the target triple selects the release asset to download when upgrading
+    println!(
+        "cargo:rustc-env=TMGR_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+    println!("cargo:rerun-if-env-changed=TMGR_RELEASE_PUBLIC_KEY");
+}
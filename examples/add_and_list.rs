@@ -0,0 +1,40 @@
+//! Adds a few tasks to an in-memory task store, completes one and lists the open ones.
+//!
+//! Run with `cargo run --example add_and_list`.
+
+use tmgr::{DB, ListFilter, ListSort, OutputFormat, TaskPriority, commands};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::in_memory().await?;
+
+    let release = commands::add::run(
+        &db,
+        "Write the release notes".to_string(),
+        Some(TaskPriority::High),
+        None,
+    )
+    .await?
+    .into_result();
+    commands::add::run(&db, "Tag the release".to_string(), None, None).await?;
+    commands::add::run(
+        &db,
+        "Fix the flaky test".to_string(),
+        Some(TaskPriority::Critical),
+        Some("It fails once in a while on CI".to_string()),
+    )
+    .await?;
+
+    let completed = commands::complete::run(&db, release.id()?).await?;
+    println!("{}", completed.message());
+
+    let filter = ListFilter {
+        sort: Some(ListSort::Priority),
+        ..ListFilter::default()
+    };
+    let open = commands::list::run(&db, filter, None, OutputFormat::Json).await?;
+    for task in open.result() {
+        println!("{} [{}] {}", task.id()?, task.priority(), task.name());
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! Moves a task of the tmgr task store through its states, using the settings of the
+//! config files like the `tmgr` executable does.
+//!
+//! Run with `cargo run --example move_task -- <DB_PATH> <ID> <STATE>`, where the id can be
+//! a prefix of the task id and the state is one of todo, in-progress, in-review,
+//! blocked, done or cancelled.
+
+use clap::ValueEnum;
+use std::{env::args, process::exit};
+use tmgr::{Config, DB, TaskState, commands};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let [_, path, id, state] = args().collect::<Vec<_>>().try_into().unwrap_or_else(|_| {
+        eprintln!("Usage: move_task <DB_PATH> <ID> <STATE>");
+        exit(2);
+    });
+    let state = TaskState::from_str(&state, true)?;
+
+    let config = Config::load()?;
+    config.register();
+    let db = DB::open(path).await?;
+
+    let task = db.select_task_by_partial_id(&id).await?;
+    println!("{} is {}", task.name(), task.state());
+    let moved = commands::move_task::run(&db, id, state, &config.transitions()).await?;
+    println!("{}", moved.message());
+    for transition in moved.result().state_history() {
+        println!("  {transition}");
+    }
+    Ok(())
+}
@@ -1,3 +1,6 @@
+import hashlib
+import os
+import shutil
 import subprocess
 import sys
 
@@ -63,9 +66,38 @@ def create_binary_release():
     )
     return result.returncode == 0
 
-def create_gh_release(version):
+def get_target_triple():
     result = subprocess.run(
-        ['gh', 'release', 'create', version, './target/release/tmgr', '--latest', '--generate-notes'],
+        ["rustc", "-vV"],
+        stdout=subprocess.PIPE,
+        text=True
+    )
+    for line in result.stdout.splitlines():
+        if line.startswith("host: "):
+            return line.split(" ", 1)[1].strip()
+    return None
+
+def create_release_assets(target):
+    # `tmgr upgrade` downloads the asset named after its target triple, and checks it against the .sha256 asset
+    asset = f'./target/release/tmgr-{target}'
+    shutil.copy('./target/release/tmgr', asset)
+    with open(asset, 'rb') as f:
+        digest = hashlib.sha256(f.read()).hexdigest()
+    with open(f'{asset}.sha256', 'w') as f:
+        f.write(f'{digest}  tmgr-{target}\n')
+    assets = [asset, f'{asset}.sha256']
+
+    # signing is optional, the .minisig asset is only required by builds with TMGR_RELEASE_PUBLIC_KEY set
+    secret_key = os.environ.get('TMGR_RELEASE_SECRET_KEY')
+    if secret_key and shutil.which('minisign'):
+        result = subprocess.run(['minisign', '-S', '-s', secret_key, '-m', asset])
+        if result.returncode == 0:
+            assets.append(f'{asset}.minisig')
+    return assets
+
+def create_gh_release(version, assets):
+    result = subprocess.run(
+        ['gh', 'release', 'create', version, *assets, '--latest', '--generate-notes'],
         stdout=subprocess.PIPE,
         text=True
     )
@@ -98,5 +130,10 @@ if __name__ == "__main__":
         print(f"Tag {binary_version} already exist. Skipping release.")
         sys.exit(0)
 
+    target = get_target_triple()
+    if not target:
+        print("Unable to determine the target triple from rustc.")
+        sys.exit(1)
+
     create_binary_release()
-    create_gh_release(binary_version)
\ No newline at end of file
+    create_gh_release(binary_version, create_release_assets(target))
\ No newline at end of file
@@ -0,0 +1,153 @@
+use super::super::model::{ErrorCategory, ErrorCode, TmgrError, TmgrErrorKind};
+use super::model::LogArgs;
+use std::{
+    fmt, fs,
+    io::{self, IsTerminal},
+    path::Path,
+};
+use tracing_appender::rolling::{Builder, RollingFileAppender, Rotation};
+use tracing_subscriber::EnvFilter;
+
+/// The environment variable holding log directives, like `tmgr=debug`, overriding the flags.
+pub(super) const LOG_ENV: &str = "TMGR_LOG";
+
+/// How many rotated log files are kept.
+const MAX_LOG_FILES: usize = 7;
+
+/// Starts logging to stderr, or to the log file, when the flags or `TMGR_LOG` ask for it.
+pub(super) fn init(args: &LogArgs) -> Result<(), LoggingError> {
+    let env = std::env::var(LOG_ENV).ok();
+    let Some(directives) = directives(args, env) else {
+        return Ok(());
+    };
+    let filter = EnvFilter::try_new(&directives).map_err(|e| LoggingError {
+        kind: LoggingErrorKind::InvalidDirectives,
+        message: format!("Invalid log directives '{directives}': {e}"),
+    })?;
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    let result = match &args.log_file {
+        Some(file) => builder
+            .with_ansi(false)
+            .with_writer(rolling_file(file)?)
+            .try_init(),
+        None => builder
+            .with_ansi(io::stderr().is_terminal())
+            .with_writer(io::stderr)
+            .try_init(),
+    };
+    result.map_err(|e| LoggingError {
+        kind: LoggingErrorKind::AlreadyStarted,
+        message: e.to_string(),
+    })
+}
+
+/// The log directives asked for: `TMGR_LOG` when set, otherwise a level from the flags.
+///
+/// Nothing is logged without any of them, unless a log file is given, which gets info logs.
+pub(super) fn directives(args: &LogArgs, env: Option<String>) -> Option<String> {
+    if let Some(env) = env.filter(|e| !e.trim().is_empty()) {
+        return Some(env);
+    }
+    match (args.debug, args.verbose, &args.log_file) {
+        (true, _, _) => Some("debug".to_string()),
+        (false, 0, None) => None,
+        (false, 0 | 1, _) => Some("tmgr=info".to_string()),
+        (false, _, _) => Some("tmgr=debug".to_string()),
+    }
+}
+
+/// A writer to `file`, suffixed with the date and rotated daily.
+fn rolling_file(file: &Path) -> Result<RollingFileAppender, LoggingError> {
+    let invalid = || LoggingError {
+        kind: LoggingErrorKind::InvalidLogFile,
+        message: format!("'{}' is not a file path", file.display()),
+    };
+    let name = file.file_name().ok_or_else(invalid)?;
+    let dir = file
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).map_err(|e| LoggingError {
+        kind: LoggingErrorKind::InvalidLogFile,
+        message: format!("Unable to create '{}': {e}", dir.display()),
+    })?;
+    Builder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(name.to_string_lossy())
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| LoggingError {
+            kind: LoggingErrorKind::InvalidLogFile,
+            message: format!("Unable to log to '{}': {e}", file.display()),
+        })
+}
+
+// --- Logging Errors ---
+#[derive(Debug)]
+pub enum LoggingErrorKind {
+    AlreadyStarted,
+    InvalidDirectives,
+    InvalidLogFile,
+}
+
+#[derive(Debug)]
+pub struct LoggingError {
+    kind: LoggingErrorKind,
+    message: String,
+}
+
+impl fmt::Display for LoggingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (logging error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for LoggingError {}
+
+impl fmt::Display for LoggingErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoggingErrorKind::AlreadyStarted => write!(f, "Already started"),
+            LoggingErrorKind::InvalidDirectives => write!(f, "Invalid directives"),
+            LoggingErrorKind::InvalidLogFile => write!(f, "Invalid log file"),
+        }
+    }
+}
+
+impl ErrorCode for LoggingErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            LoggingErrorKind::AlreadyStarted => "E-LOG-001",
+            LoggingErrorKind::InvalidDirectives => "E-LOG-002",
+            LoggingErrorKind::InvalidLogFile => "E-LOG-003",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            LoggingErrorKind::AlreadyStarted => ErrorCategory::Internal,
+            LoggingErrorKind::InvalidDirectives => ErrorCategory::Validation,
+            LoggingErrorKind::InvalidLogFile => ErrorCategory::IO,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            LoggingErrorKind::InvalidDirectives => {
+                Some("Set TMGR_LOG to a level like 'debug' or to directives like 'tmgr=info'")
+            }
+            LoggingErrorKind::InvalidLogFile => {
+                Some("Give --log-file, or TMGR_LOG_FILE, a file in a writable directory")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<LoggingError> for TmgrError {
+    fn from(err: LoggingError) -> Self {
+        TmgrError::new(TmgrErrorKind::Tmgr, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -1,4 +1,6 @@
+mod logging;
 pub mod model;
 pub mod parser;
 pub mod result_handler;
+mod shell;
 mod tests;
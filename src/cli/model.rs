@@ -1,5 +1,7 @@
-use super::super::model::TaskPriority;
-use clap::{Parser, Subcommand, ValueEnum};
+use super::super::model::{TaskPriority, TaskState};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -8,45 +10,137 @@ use clap::{Parser, Subcommand, ValueEnum};
     about = "Store todo tasks",
     propagate_version = true
 )]
-pub(super) struct Cli {
+pub(crate) struct Cli {
     #[command(subcommand)]
     pub(super) command: Command,
+    #[command(flatten)]
+    pub(super) log: LogArgs,
+}
+
+/// Where and how much tmgr logs, on top of the `TMGR_LOG` environment variable.
+#[derive(Args, Debug, Default)]
+pub(super) struct LogArgs {
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    /// Log to stderr: -v for file paths and timings, -vv for database queries and errors
+    pub(super) verbose: u8,
+    #[arg(long, global = true)]
+    /// Log everything, including the internals of the database
+    pub(super) debug: bool,
+    #[arg(long, env = "TMGR_LOG_FILE", global = true)]
+    /// Log to this file, rotated daily, instead of stderr
+    pub(super) log_file: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
 pub(super) enum Command {
     /// Add a new task
     Add {
+        #[arg(required_unless_present = "editor")]
         /// A short description of the task
-        name: String,
+        name: Option<String>,
         #[arg(short, long)]
-        /// An optional priority of the task (will use low priority by default)
+        /// An optional priority of the task (will use low priority by default). Accepts a level name, p0-p4 or !-!!!!
         priority: Option<TaskPriority>,
         #[arg(short, long)]
         /// An optional long description of the task
         description: Option<String>,
+        #[arg(short, long)]
+        /// Fill in the task in $EDITOR, pre-filled with the other values given
+        editor: bool,
+    },
+    /// Attach a file or URL to a task
+    Attach {
+        /// The id of the task (can be partial)
+        id: String,
+        /// The path of the file, or the URL, to attach
+        target: String,
+        #[arg(short, long)]
+        /// Reference the file where it is instead of copying it into the attachments folder
+        link: bool,
+    },
+    /// Show tasks in columns by state
+    Board {
+        #[arg(short, long)]
+        /// Include the done and cancelled columns
+        all: bool,
     },
     /// Mark a task as complete
     Complete {
         /// The id of the task to update (can be partial)
         id: String,
     },
+    /// Print the in-progress task ids with their names, for shell completion
+    #[command(name = "__complete-ids", hide = true)]
+    CompleteIds {
+        /// Only print the ids starting with this prefix
+        prefix: Option<String>,
+    },
+    /// Print a completion script for a shell, completing task ids from the database
+    Completions {
+        #[arg(value_enum)]
+        /// The shell to print the completion script for
+        shell: CompletionShell,
+    },
+    /// Show or change the settings of the config files
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
     /// Delete a task
     Delete {
         /// The id of the task to delete (can be partial)
         id: String,
     },
+    /// Check the database and notes directory for inconsistencies
+    Doctor {
+        #[arg(short, long)]
+        /// Repair the issues found, quarantining records and notes that cannot be repaired
+        fix: bool,
+    },
+    /// Manage the git hook completing tasks from commit messages
+    GitHook {
+        #[command(subcommand)]
+        action: GitHookAction,
+    },
+    /// Link a task to lines of a source file in a git repository
+    Link {
+        /// The id of the task (can be partial)
+        id: String,
+        /// The location to link, as path:line or path:start-end
+        location: String,
+    },
     /// List all tasks. By default, this will only list in-progress tasks.
     List {
+        #[command(flatten)]
+        filter: ListFilter,
+        #[arg(short, long, value_enum)]
+        /// The format to print tasks in (will use the output of the config file, or table, by default)
+        output: Option<OutputFormat>,
+    },
+    /// Run a language server on stdin and stdout, for editors to create tasks from TODO comments and show the tasks referenced as tmgr:<ID>
+    Lsp,
+    /// Print the man page, write the man pages of every command, or print the markdown command reference
+    Manpage {
+        #[arg(short, long, value_name = "DIR", conflicts_with = "markdown")]
+        /// Write tmgr.1 and a tmgr-<command>.1 page for every command into this directory instead of printing tmgr.1
+        out_dir: Option<String>,
         #[arg(short, long)]
-        /// List all tasks, including completed ones
-        all: bool,
+        /// Print the command reference in markdown instead
+        markdown: bool,
     },
     /// Migrate will migrate the database from an older version of tmgr to be compatible with the latest version
     Migrate {
         /// The major version of the database to migrate from
         from: TmgrVersion,
     },
+    /// Move a task to another state
+    Move {
+        /// The id of the task to move (can be partial)
+        id: String,
+        #[arg(value_enum)]
+        /// The state to move the task to
+        state: TaskState,
+    },
     /// Creates and/or opens a markdown file to store notes associated with a particular task
     Note {
         /// The id of the task (can be partial)
@@ -54,9 +148,54 @@ pub(super) enum Command {
         #[arg(short, long)]
         /// Opens up file in vi editor
         open: bool,
+        #[arg(short, long)]
+        /// The template to create the note from (a file named <TEMPLATE>.md in the templates directory)
+        template: Option<String>,
+        #[arg(short, long, conflicts_with = "open")]
+        /// Appends a timestamped entry under the Notes section (use - to read it from stdin)
+        append: Option<String>,
+        #[arg(short, long, conflicts_with_all = ["open", "append"])]
+        /// Prints the note, rendered for the terminal
+        show: bool,
+    },
+    /// Open an attachment of a task with the default application
+    Open {
+        /// The id of the task (can be partial)
+        id: String,
+        /// The number of the attachment, as listed by view (can be omitted if the task has a single attachment)
+        number: Option<usize>,
+    },
+    /// Create tasks from the TODO, FIXME and HACK comments of a source tree, and complete those whose comment is gone
+    Scan {
+        #[arg(default_value = ".")]
+        /// The directory or file to scan (files ignored by .gitignore are skipped)
+        path: String,
+    },
+    /// Serve a REST API with JSON bodies over the database, for editor plugins and dashboards
+    Serve {
+        #[arg(short, long, default_value = "127.0.0.1:7878")]
+        /// The address to listen on, as IP:PORT
+        bind: String,
+        #[arg(long, env = "TMGR_API_TOKEN", hide_env_values = true)]
+        /// Require requests to send this token as "Authorization: Bearer <TOKEN>"
+        token: Option<String>,
+    },
+    /// Run commands in an interactive shell, keeping the database open, with a current task for commands taking an id
+    Shell,
+    /// Create and switch to a git branch named after a task, moving the task to in progress
+    StartBranch {
+        /// The id of the task (can be partial)
+        id: String,
+    },
+    /// Save list filters under a name in the database, and list tasks with them
+    Query {
+        #[command(subcommand)]
+        action: QueryAction,
     },
     /// Info regarding file locations, current database, general statistics
     Status,
+    /// Open an interactive full-screen board of tasks
+    Tui,
     /// Update a task
     Update {
         /// The id of the task to update (can be partial)
@@ -65,21 +204,189 @@ pub(super) enum Command {
         /// A short description of the task
         name: Option<String>,
         #[arg(short, long)]
-        /// The priority of the task
+        /// The priority of the task. Accepts a level name, p0-p4 or !-!!!!
         priority: Option<TaskPriority>,
         #[arg(short, long)]
         /// An optional long description of the task
         description: Option<String>,
+        #[arg(short, long)]
+        /// Edit the task in $EDITOR, pre-filled with its current values
+        editor: bool,
     },
     /// Upgrade to the latest version
-    Upgrade,
+    #[command(disable_version_flag = true)]
+    Upgrade {
+        #[arg(long, conflicts_with_all = ["check", "version", "channel"])]
+        /// Go back to the version replaced by the last upgrade
+        rollback: bool,
+        #[arg(long)]
+        /// Only report the available update and its release notes, without installing it
+        check: bool,
+        #[arg(long, value_name = "X.Y.Z")]
+        /// Install this version instead of the newest one (can be older than the current version)
+        version: Option<String>,
+        #[arg(long)]
+        /// The releases to upgrade to (will use the channel of the config file, or stable, by default)
+        channel: Option<ReleaseChannel>,
+    },
     /// View a specific task
     View {
         /// The id of the task to view (can be partial)
         id: String,
+        #[arg(short = 'n', long)]
+        /// Also prints the note of the task, rendered for the terminal
+        with_note: bool,
+        #[arg(short, long, value_enum)]
+        /// The format to print the task in (will use the output of the config file, or table, by default)
+        output: Option<OutputFormat>,
+    },
+}
+
+/// The filters of the `list` command, which can be saved as a named query.
+#[derive(Args, Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ListFilter {
+    #[arg(short, long)]
+    /// List all tasks, including completed ones
+    pub all: bool,
+    #[arg(short, long, value_enum)]
+    /// The order to list tasks in (will use the sort of the config file, or priority, by default)
+    pub sort: Option<ListSort>,
+    #[arg(long, value_enum)]
+    /// Only list tasks in the given state (can be repeated)
+    pub state: Vec<TaskState>,
+    #[arg(short, long)]
+    /// Only list tasks with the given priority (can be repeated). Accepts a level name, p0-p4 or !-!!!!
+    pub priority: Vec<TaskPriority>,
+    #[arg(long)]
+    /// Only list tasks linked to the given file
+    pub file: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub(super) enum QueryAction {
+    /// Save list filters under a name, shared with everyone using the same database
+    Save {
+        /// The name of the query
+        name: String,
+        #[command(flatten)]
+        filter: ListFilter,
+        #[arg(short, long)]
+        /// Replace an existing query with the same name
+        force: bool,
+    },
+    /// List tasks with the filters of a saved query
+    Run {
+        /// The name of the query
+        name: String,
+        #[arg(short, long, value_enum)]
+        /// The format to print tasks in (will use the output of the config file, or table, by default)
+        output: Option<OutputFormat>,
+    },
+    /// List the saved queries
+    List,
+    /// Delete a saved query
+    Delete {
+        /// The name of the query
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub(super) enum ConfigAction {
+    /// Print the value of a setting, as a dotted key like list.sort
+    Get {
+        /// The key of the setting
+        key: String,
+    },
+    /// Change a setting in the global config file, or in the project config file
+    Set {
+        /// The key of the setting, as a dotted key like list.sort
+        key: String,
+        /// The new value, in TOML (plain words are read as strings)
+        value: String,
+        #[arg(short, long)]
+        /// Change the .tmgr.toml project config file of the current directory instead
+        project: bool,
+    },
+    /// Print the config files in use and the effective settings
+    List,
+    /// Open the global config file, or the project config file, in the editor
+    Edit {
+        #[arg(short, long)]
+        /// Edit the .tmgr.toml project config file of the current directory instead
+        project: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub(super) enum GitHookAction {
+    /// Install a commit-msg hook in the current git repository that completes the tasks referenced as "closes <ID>"
+    Install {
+        #[arg(short, long)]
+        /// Replace an existing commit-msg hook that was not installed by tmgr
+        force: bool,
+    },
+    /// Complete the tasks closed by a commit message (run by the installed hook)
+    #[command(hide = true)]
+    CommitMsg {
+        /// The file holding the commit message
+        file: String,
     },
 }
 
+/// The order in which the `list` command shows tasks.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ListSort {
+    /// Highest priority first, oldest first within the same priority
+    #[default]
+    Priority,
+    /// Oldest first
+    Created,
+    /// Alphabetically by name
+    Name,
+}
+
+/// The shells the `completions` command prints a script for.
+#[derive(Clone, Debug, PartialEq, ValueEnum)]
+pub(crate) enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// The format the `list` and `view` commands print tasks in.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// A table, for reading
+    #[default]
+    Table,
+    /// JSON, for scripts
+    Json,
+}
+
+/// The releases the `upgrade` command picks from.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ReleaseChannel {
+    /// Stable releases only
+    #[default]
+    Stable,
+    /// Pre-releases as well as stable releases
+    Prerelease,
+}
+
+impl fmt::Display for ReleaseChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReleaseChannel::Stable => write!(f, "stable"),
+            ReleaseChannel::Prerelease => write!(f, "prerelease"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, ValueEnum)]
 pub(crate) enum TmgrVersion {
     V2,
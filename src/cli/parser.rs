@@ -1,17 +1,70 @@
 use super::super::{
     cli::{
-        model::{Cli, Command},
+        logging,
+        model::{Cli, Command, ConfigAction, GitHookAction, QueryAction},
         result_handler::handle_result,
+        shell,
     },
     commands,
+    config::Config,
     db::DB,
-    model::TmgrError,
+    model::{TmgrError, TmgrErrorKind},
 };
 use clap::Parser;
+use std::time::Instant;
+use tracing::{debug, info};
 
 #[tokio::main]
 pub async fn run() -> i32 {
-    let input = Cli::parse();
+    // config must be loaded before parsing, as custom priority levels are valid argument values
+    let config = if cfg!(test) {
+        Ok(Config::default())
+    } else {
+        Config::load()
+    };
+    // an invalid config is only reported once the command is known, so it can still be fixed
+    let (config, config_error) = match config {
+        Ok(config) => {
+            config.register();
+            (config, None)
+        }
+        Err(e) => (Config::default(), Some(e)),
+    };
+
+    // aliases are expanded before parsing, as they stand for a command and its arguments
+    let parsed = config
+        .expand_alias(std::env::args().collect())
+        .map_err(TmgrError::from)
+        .map(Cli::try_parse_from);
+    let input = match (parsed, config_error) {
+        (Ok(Ok(input)), None) => input,
+        (Ok(Ok(input)), Some(_)) if starts_despite_invalid_config(&input.command) => input,
+        (Ok(Err(e)), None) => e.exit(),
+        (Err(e), _) => {
+            let result = handle_result(Err(e)).await;
+            println!("{}", result.result_string());
+            return result.exit_code();
+        }
+        (Ok(_), Some(e)) => {
+            let result = handle_result(Err(TmgrError::from(e))).await;
+            println!("{}", result.result_string());
+            return result.exit_code();
+        }
+    };
+    if let Err(e) = logging::init(&input.log) {
+        let result = handle_result(Err(TmgrError::from(e))).await;
+        println!("{}", result.result_string());
+        return result.exit_code();
+    }
+    if let Ok((global, project)) = Config::config_files() {
+        info!("Using the config file '{}'", global.display());
+        if let Some(project) = project {
+            info!("Using the project config file '{}'", project.display());
+        }
+    }
+    debug!("Running {:?}", input.command);
+    let start = Instant::now();
+
     let db = if cfg!(test) {
         DB::new_test().await
     } else {
@@ -21,59 +74,260 @@ pub async fn run() -> i32 {
     let res: Result<String, TmgrError> = match db {
         Err(e) => Err(TmgrError::from(e)),
         Ok(db) => match input.command {
-            Command::Add {
-                name,
-                priority,
-                description,
-            } => commands::add::run(&db, name, priority, description)
-                .await
-                .map_err(TmgrError::from)
-                .map(|f| f.message().to_string()),
-            Command::Complete { id } => commands::complete::run(&db, id)
-                .await
-                .map_err(TmgrError::from)
-                .map(|f| f.message().to_string()),
-            Command::Delete { id } => commands::delete::run(&db, id)
-                .await
+            Command::Shell => shell::run(&db, &config).await,
+            command => execute(&db, &config, command).await,
+        },
+    };
+
+    info!("Finished in {:.2?}", start.elapsed());
+
+    let result = handle_result(res).await;
+    // commands owning the terminal or stdout, as lsp does, have nothing left to print
+    if !result.result_string().is_empty() {
+        println!("{}", result.result_string());
+    }
+    result.exit_code()
+}
+
+/// Whether a command runs when the config files are invalid, as it is the way to fix them.
+fn starts_despite_invalid_config(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Config {
+            action: ConfigAction::Edit { .. }
+        }
+    )
+}
+
+/// Runs a command, returning the message to print.
+pub(super) async fn execute(
+    db: &DB,
+    config: &Config,
+    command: Command,
+) -> Result<String, TmgrError> {
+    match command {
+        Command::Add {
+            name,
+            priority,
+            description,
+            editor,
+        } => match (editor, name, priority.or(config.default_priority())) {
+            (false, Some(name), priority) => {
+                commands::add::run(db, name, priority, description).await
+            }
+            (_, name, priority) => {
+                commands::add::run_in_editor(db, name, priority, description).await
+            }
+        }
+        .map_err(TmgrError::from)
+        .map(|f| f.message().to_string()),
+        Command::Attach { id, target, link } => commands::attach::run(db, id, target, link)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Board { all } => commands::board::run(db, all, &config.transitions())
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Complete { id } => commands::complete::run(db, id)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::CompleteIds { prefix } => commands::completions::ids(db, prefix)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Completions { shell } => commands::completions::run(shell)
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Config { action } => match action {
+            ConfigAction::Get { key } => commands::config::get(config, key)
                 .map_err(TmgrError::from)
                 .map(|f| f.message().to_string()),
-            Command::List { all } => commands::list::run(&db, all)
-                .await
+            ConfigAction::Set {
+                key,
+                value,
+                project,
+            } => commands::config::set(key, value, project)
                 .map_err(TmgrError::from)
                 .map(|f| f.message().to_string()),
-            Command::Migrate { from } => commands::migrate::run(&db, from)
-                .await
+            ConfigAction::List => commands::config::list(config)
                 .map_err(TmgrError::from)
                 .map(|f| f.message().to_string()),
-            Command::Note { id, open } => commands::note::run(&db, id, open)
-                .await
+            ConfigAction::Edit { project } => commands::config::edit(project)
                 .map_err(TmgrError::from)
                 .map(|f| f.message().to_string()),
-            Command::Status => commands::status::run(&db)
+        },
+        Command::Delete { id } => commands::delete::run(db, id)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Doctor { fix } => commands::doctor::run(db, fix)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::GitHook {
+            action: GitHookAction::Install { force },
+        } => commands::git_hook::install(force)
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::GitHook {
+            action: GitHookAction::CommitMsg { file },
+        } => commands::git_hook::commit_msg(db, file)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Link { id, location } => commands::link::run(db, id, location)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::List { filter, output } => commands::list::run(
+            db,
+            config.list_filter(filter),
+            config.list_columns(),
+            output.unwrap_or(config.output_format()),
+        )
+        .await
+        .map_err(TmgrError::from)
+        .map(|f| f.message().to_string()),
+        Command::Lsp => commands::lsp::run(db)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Manpage {
+            out_dir: Some(dir), ..
+        } => commands::manpage::write_pages(dir)
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Manpage { markdown: true, .. } => commands::manpage::markdown()
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Manpage { .. } => commands::manpage::run()
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Migrate { from } => commands::migrate::run(db, from)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Move { id, state } => {
+            commands::move_task::run(db, id, state, &config.transitions())
                 .await
                 .map_err(TmgrError::from)
-                .map(|f| f.message().to_string()),
-            Command::Update {
-                id,
+                .map(|f| f.message().to_string())
+        }
+        Command::Note {
+            id,
+            template,
+            append: Some(text),
+            ..
+        } => commands::note::append(db, id, text, template.or(config.default_template()))
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Note { id, show: true, .. } => commands::note::show(db, id)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Note {
+            id,
+            open,
+            template,
+            append: None,
+            ..
+        } => commands::note::run(db, id, open, template.or(config.default_template()))
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Open { id, number } => commands::open::run(db, id, number)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Query { action } => match action {
+            QueryAction::Save {
                 name,
-                priority,
-                description,
-            } => commands::update::run(&db, id, name, priority, description)
+                filter,
+                force,
+            } => commands::query::save(db, name, filter, force)
                 .await
                 .map_err(TmgrError::from)
                 .map(|f| f.message().to_string()),
-            Command::Upgrade => commands::upgrade::run(&db)
+            QueryAction::Run { name, output } => {
+                commands::query::run(db, config, name, output.unwrap_or(config.output_format()))
+                    .await
+                    .map_err(TmgrError::from)
+                    .map(|f| f.message().to_string())
+            }
+            QueryAction::List => commands::query::list(db)
                 .await
                 .map_err(TmgrError::from)
                 .map(|f| f.message().to_string()),
-            Command::View { id } => commands::view::run(&db, id)
+            QueryAction::Delete { name } => commands::query::delete(db, name)
                 .await
                 .map_err(TmgrError::from)
                 .map(|f| f.message().to_string()),
         },
-    };
-
-    let result = handle_result(res).await;
-    println!("{}", result.result_string());
-    result.exit_code()
+        Command::Scan { path } => commands::scan::run(db, path)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Serve { bind, token } => commands::serve::run(db, config, bind, token)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Shell => Err(TmgrError::new(
+            TmgrErrorKind::ShellCommand,
+            "Already in the tmgr shell".to_string(),
+        )),
+        Command::StartBranch { id } => commands::start_branch::run(db, id)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Status => commands::status::run(db)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Tui => commands::tui::run(db, config)
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Update {
+            id,
+            name,
+            priority,
+            description,
+            editor,
+        } => if editor {
+            commands::update::run_in_editor(db, id, name, priority, description).await
+        } else {
+            commands::update::run(db, id, name, priority, description).await
+        }
+        .map_err(TmgrError::from)
+        .map(|f| f.message().to_string()),
+        Command::Upgrade { rollback: true, .. } => commands::upgrade::rollback()
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+        Command::Upgrade {
+            check,
+            version,
+            channel,
+            ..
+        } => commands::upgrade::run(
+            db,
+            check,
+            version,
+            channel.unwrap_or(config.release_channel()),
+            config.release_api_url(),
+        )
+        .await
+        .map_err(TmgrError::from)
+        .map(|f| f.message().to_string()),
+        Command::View {
+            id,
+            with_note,
+            output,
+        } => commands::view::run(db, id, with_note, output.unwrap_or(config.output_format()))
+            .await
+            .map_err(TmgrError::from)
+            .map(|f| f.message().to_string()),
+    }
 }
@@ -1,10 +1,33 @@
-use super::super::model::TmgrError;
+use super::super::model::{ErrorCategory, TmgrError};
 use colored::Colorize;
+use tracing::debug;
 
+/// The exit codes of tmgr, so scripts can branch on why a command failed.
+///
+/// Usage errors, like an unknown argument, exit with 2.
 enum ExitCode {
     Success = 0,
     Failure = 1,
+    NotFound = 3,
+    Ambiguous = 4,
+    Validation = 5,
+    IO = 6,
+    Network = 7,
 }
+
+impl From<ErrorCategory> for ExitCode {
+    fn from(category: ErrorCategory) -> Self {
+        match category {
+            ErrorCategory::Ambiguous => ExitCode::Ambiguous,
+            ErrorCategory::Internal => ExitCode::Failure,
+            ErrorCategory::IO => ExitCode::IO,
+            ErrorCategory::Network => ExitCode::Network,
+            ErrorCategory::NotFound => ExitCode::NotFound,
+            ErrorCategory::Validation => ExitCode::Validation,
+        }
+    }
+}
+
 pub(in crate::cli) struct ResultHandler {
     result_string: String,
     exit_code: i32,
@@ -27,10 +50,15 @@ pub(in crate::cli) async fn handle_result(result: Result<String, TmgrError>) ->
             exit_code: ExitCode::Success as i32,
         },
         Err(err) => {
-            let response = format!("{}: {err}", "error".red());
+            // the message only summarizes the errors, the log gets all of them
+            debug!("{err:?}");
+            let mut response = format!("{}: {err}", format!("error[{}]", err.code()).red());
+            if let Some(hint) = err.hint() {
+                response.push_str(&format!("\n{}: {hint}", "hint".cyan()));
+            }
             ResultHandler {
                 result_string: response,
-                exit_code: ExitCode::Failure as i32,
+                exit_code: ExitCode::from(err.category()) as i32,
             }
         }
     }
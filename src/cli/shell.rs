@@ -0,0 +1,346 @@
+use super::super::{
+    commands::completions::{self, id_commands},
+    config::Config,
+    db::{DB, Logged},
+    model::{ErrorCategory, ErrorCode, Task, TmgrError, TmgrErrorKind},
+};
+use super::{
+    model::{Cli, Command},
+    parser::execute,
+    result_handler::handle_result,
+};
+use clap::{CommandFactory, FromArgMatches, error::ErrorKind};
+use rustyline::{
+    Context, Editor, Helper,
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+};
+use std::{fmt, fs::create_dir_all, path::PathBuf};
+
+/// The commands of the shell itself, besides the tmgr commands.
+const BUILTINS: [&str; 3] = ["exit", "quit", "use"];
+
+/// How many characters of the current task id are shown in the prompt.
+const PROMPT_ID_LENGTH: usize = 6;
+
+/// Reads commands in a loop, running them against the open database until `exit`.
+pub(super) async fn run(db: &DB, config: &Config) -> Result<String, TmgrError> {
+    let mut editor: Editor<ShellHelper, DefaultHistory> =
+        Editor::new().map_err(|e| ShellError {
+            kind: ShellErrorKind::ReadlineError,
+            message: e.to_string(),
+        })?;
+    editor.set_helper(Some(ShellHelper::default()));
+    let history = history_path();
+    if let Some(history) = &history {
+        // there is no history on the first run
+        let _ = editor.load_history(history);
+    }
+
+    println!(
+        "tmgr shell: type commands without 'tmgr', 'use <ID>' to set the current task and 'exit' to quit"
+    );
+    let mut current: Option<String> = None;
+    loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.ids = task_ids(db).await;
+        }
+        let line = match editor.readline(&prompt(current.as_deref())) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                return Err(ShellError {
+                    kind: ShellErrorKind::ReadlineError,
+                    message: e.to_string(),
+                }
+                .into());
+            }
+        };
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+
+        match parse_line(&line, current.as_deref(), config) {
+            ShellInput::Empty => {}
+            ShellInput::Exit => break,
+            ShellInput::Use(None) => current = None,
+            ShellInput::Use(Some(id)) => match db.select_task_by_partial_id(&id).await {
+                Ok(task) => current = task.id().ok(),
+                Err(e) => println!("{}", handle_result(Err(e.into())).await.result_string()),
+            },
+            ShellInput::Invalid(message) => println!("{}", message.trim_end()),
+            ShellInput::Run { name, id, command } => {
+                let result = execute(db, config, command).await;
+                let succeeded = result.is_ok();
+                println!("{}", handle_result(result).await.result_string());
+                if succeeded {
+                    current = next_current(db, current, &name, id).await;
+                }
+            }
+        }
+    }
+
+    if let Some(history) = &history {
+        // history is a convenience, failing to keep it does not fail the shell
+        if let Some(dir) = history.parent() {
+            let _ = create_dir_all(dir);
+        }
+        let _ = editor.save_history(history);
+    }
+    Ok(String::new())
+}
+
+/// A line read by the shell.
+#[derive(Debug)]
+pub(super) enum ShellInput {
+    Empty,
+    Exit,
+    /// Sets the current task, or clears it
+    Use(Option<String>),
+    /// A tmgr command, with its name and the id it was given, if any
+    Run {
+        name: String,
+        id: Option<String>,
+        command: Command,
+    },
+    /// A line that is not a valid command, with the message explaining why
+    Invalid(String),
+}
+
+/// Parses a line as a tmgr command, expanding aliases. The current task is used as the id of
+/// commands that need one and are not given one.
+pub(super) fn parse_line(line: &str, current: Option<&str>, config: &Config) -> ShellInput {
+    let words = match shell_words::split(line) {
+        Ok(words) => words,
+        Err(e) => return ShellInput::Invalid(format!("Invalid command: {e}")),
+    };
+    let Some(first) = words.first() else {
+        return ShellInput::Empty;
+    };
+    match first.as_str() {
+        "exit" | "quit" => return ShellInput::Exit,
+        "use" => return ShellInput::Use(words.get(1).cloned()),
+        _ => {}
+    }
+
+    let mut args = vec!["tmgr".to_string()];
+    args.extend(words);
+    let mut args = match config.expand_alias(args) {
+        Ok(args) => args,
+        Err(e) => return ShellInput::Invalid(e.to_string()),
+    };
+    let mut matches = Cli::command().try_get_matches_from(&args);
+    if let (Err(e), Some(current)) = (&matches, current)
+        && e.kind() == ErrorKind::MissingRequiredArgument
+        && args.get(1).is_some_and(|name| id_commands().contains(name))
+    {
+        args.insert(2, current.to_string());
+        matches = Cli::command().try_get_matches_from(&args);
+    }
+    let matches = match matches {
+        Ok(matches) => matches,
+        Err(e) => return ShellInput::Invalid(e.render().to_string()),
+    };
+
+    let (name, id) = match matches.subcommand() {
+        Some((name, sub)) => (
+            name.to_string(),
+            sub.try_get_one::<String>("id").ok().flatten().cloned(),
+        ),
+        None => (String::new(), None),
+    };
+    match Cli::from_arg_matches(&matches) {
+        Ok(Cli {
+            command: Command::Shell,
+            ..
+        }) => ShellInput::Invalid("Already in the tmgr shell".to_string()),
+        Ok(cli) => ShellInput::Run {
+            name,
+            id,
+            command: cli.command,
+        },
+        Err(e) => ShellInput::Invalid(e.render().to_string()),
+    }
+}
+
+/// The current task after a command succeeded: the task it was run on, or the task it added.
+/// Deleting the current task clears it.
+async fn next_current(
+    db: &DB,
+    current: Option<String>,
+    name: &str,
+    id: Option<String>,
+) -> Option<String> {
+    match (name, id) {
+        ("delete", Some(id)) => current.filter(|c| !c.starts_with(&id)),
+        (_, Some(id)) => db
+            .select_task_by_partial_id(&id)
+            .await
+            .ok()
+            .and_then(|t| t.id().ok())
+            .or(current),
+        ("add", None) => newest_task_id(db).await.or(current),
+        _ => current,
+    }
+}
+
+async fn newest_task_id(db: &DB) -> Option<String> {
+    let query = "SELECT * FROM task ORDER BY created_at DESC LIMIT 1";
+    let tasks: Vec<Task> = db
+        .client
+        .query(query)
+        .logged(query)
+        .await
+        .ok()?
+        .take(0)
+        .ok()?;
+    tasks.first().and_then(|t| t.id().ok())
+}
+
+/// The ids and names of the in-progress tasks, for completion.
+async fn task_ids(db: &DB) -> Vec<(String, String)> {
+    completions::ids(db, None)
+        .await
+        .map(|res| {
+            res.result()
+                .iter()
+                .filter_map(|t| t.id().ok().map(|id| (id, t.name().to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(super) fn prompt(current: Option<&str>) -> String {
+    match current {
+        Some(id) => format!("tmgr [{}]> ", &id[..id.len().min(PROMPT_ID_LENGTH)]),
+        None => "tmgr> ".to_string(),
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    Config::get_config_dir()
+        .ok()
+        .map(|dir| dir.join("shell_history"))
+}
+
+/// Completes command names, and the task ids of the commands taking one.
+#[derive(Default)]
+struct ShellHelper {
+    ids: Vec<(String, String)>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(complete_line(&line[..pos], &self.ids))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// The candidates completing the last word of a line, and where that word starts.
+pub(super) fn complete_line(line: &str, ids: &[(String, String)]) -> (usize, Vec<Pair>) {
+    let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = &line[start..];
+    let previous: Vec<&str> = line[..start].split_whitespace().collect();
+
+    let candidates = match previous.as_slice() {
+        [] => {
+            let cli = Cli::command();
+            let mut names: Vec<String> = cli
+                .get_subcommands()
+                .filter(|c| !c.is_hide_set() && c.get_name() != "shell")
+                .map(|c| c.get_name().to_string())
+                .chain(BUILTINS.iter().map(|b| b.to_string()))
+                .filter(|name| name.starts_with(word))
+                .collect();
+            names.sort();
+            names
+                .into_iter()
+                .map(|name| Pair {
+                    display: name.clone(),
+                    replacement: name,
+                })
+                .collect()
+        }
+        [command] if *command == "use" || id_commands().iter().any(|c| c == command) => ids
+            .iter()
+            .filter(|(id, _)| id.to_lowercase().starts_with(&word.to_lowercase()))
+            .map(|(id, name)| Pair {
+                display: format!("{id}  {name}"),
+                replacement: id.clone(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    (start, candidates)
+}
+
+// --- Shell Errors ---
+#[derive(Debug)]
+pub enum ShellErrorKind {
+    ReadlineError,
+}
+
+#[derive(Debug)]
+pub struct ShellError {
+    kind: ShellErrorKind,
+    message: String,
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (shell error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for ShellError {}
+
+impl fmt::Display for ShellErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShellErrorKind::ReadlineError => write!(f, "Readline error"),
+        }
+    }
+}
+
+impl ErrorCode for ShellErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ShellErrorKind::ReadlineError => "E-SHELL-001",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ShellErrorKind::ReadlineError => ErrorCategory::IO,
+        }
+    }
+}
+
+impl From<ShellError> for TmgrError {
+    fn from(err: ShellError) -> Self {
+        TmgrError::new(TmgrErrorKind::ShellCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -0,0 +1,61 @@
+use super::super::{logging::directives, model::LogArgs};
+use std::path::PathBuf;
+
+#[test]
+fn given_no_flags_when_getting_directives_then_nothing_should_be_logged() {
+    assert_eq!(directives(&LogArgs::default(), None), None);
+}
+
+#[test]
+fn given_verbose_flag_when_getting_directives_then_info_logs_should_be_kept() {
+    let args = LogArgs {
+        verbose: 1,
+        ..Default::default()
+    };
+    assert_eq!(directives(&args, None), Some("tmgr=info".to_string()));
+}
+
+#[test]
+fn given_verbose_flag_twice_when_getting_directives_then_debug_logs_should_be_kept() {
+    let args = LogArgs {
+        verbose: 2,
+        ..Default::default()
+    };
+    assert_eq!(directives(&args, None), Some("tmgr=debug".to_string()));
+}
+
+#[test]
+fn given_debug_flag_when_getting_directives_then_all_debug_logs_should_be_kept() {
+    let args = LogArgs {
+        verbose: 1,
+        debug: true,
+        ..Default::default()
+    };
+    assert_eq!(directives(&args, None), Some("debug".to_string()));
+}
+
+#[test]
+fn given_log_file_without_flags_when_getting_directives_then_info_logs_should_be_kept() {
+    let args = LogArgs {
+        log_file: Some(PathBuf::from("tmgr.log")),
+        ..Default::default()
+    };
+    assert_eq!(directives(&args, None), Some("tmgr=info".to_string()));
+}
+
+#[test]
+fn given_tmgr_log_when_getting_directives_then_it_should_override_the_flags() {
+    let args = LogArgs {
+        verbose: 2,
+        ..Default::default()
+    };
+    assert_eq!(
+        directives(&args, Some("tmgr::db=debug".to_string())),
+        Some("tmgr::db=debug".to_string())
+    );
+}
+
+#[test]
+fn given_empty_tmgr_log_when_getting_directives_then_the_flags_should_be_used() {
+    assert_eq!(directives(&LogArgs::default(), Some(" ".to_string())), None);
+}
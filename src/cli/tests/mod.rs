@@ -1,2 +1,8 @@
 #[cfg(test)]
+mod logging_test;
+#[cfg(test)]
+mod model_test;
+#[cfg(test)]
 mod result_handler_test;
+#[cfg(test)]
+mod shell_test;
@@ -0,0 +1,7 @@
+use super::super::model::Cli;
+use clap::CommandFactory;
+
+#[test]
+fn given_cli_definition_when_building_commands_then_clap_should_accept_it() {
+    Cli::command().debug_assert();
+}
@@ -1,4 +1,8 @@
-use super::super::super::model::TmgrError;
+use super::super::super::{
+    commands::{delete, note, view},
+    db,
+    model::TmgrError,
+};
 use super::super::result_handler::handle_result;
 use crate::model::TmgrErrorKind;
 use colored::Colorize;
@@ -26,7 +30,7 @@ async fn given_error_result_when_handling_result_then_error_string_should_be_ret
     let handler = handle_result(result).await;
     let expected = format!(
         "{}: An error occurred: (tmgr error: Add command error)",
-        "error".red()
+        "error[E-TMGR-001]".red()
     );
     assert_eq!(handler.result_string(), expected);
 }
@@ -40,3 +44,63 @@ async fn given_error_result_when_handling_result_then_exit_code_1_should_be_retu
     let handler = handle_result(result).await;
     assert_eq!(handler.exit_code(), 1);
 }
+
+#[tokio::test]
+async fn given_unknown_task_id_when_handling_error_then_code_hint_and_not_found_exit_code_should_be_returned()
+ {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let result = view::run(&db, "randomID".to_string(), false, Default::default())
+        .await
+        .map(|res| res.message().to_string())
+        .map_err(TmgrError::from);
+    let handler = handle_result(result).await;
+    assert_eq!(handler.exit_code(), 3);
+    assert!(handler.result_string().contains("error[E-DB-001]"));
+    assert!(
+        handler
+            .result_string()
+            .ends_with("Run 'tmgr list --all' to see the ids of all tasks")
+    );
+}
+
+#[tokio::test]
+async fn given_ambiguous_task_id_when_handling_error_then_ambiguous_exit_code_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    db.client
+        .query("CREATE task:abc1 SET name = 'First', priority = 'Low', created_at = time::now()")
+        .query("CREATE task:abc2 SET name = 'Second', priority = 'Low', created_at = time::now()")
+        .await
+        .expect("Failed to create tasks");
+    let err = TmgrError::from(delete::run(&db, "abc".to_string()).await.unwrap_err());
+    assert_eq!(err.code(), "E-DB-002");
+    let handler = handle_result(Err(err)).await;
+    assert_eq!(handler.exit_code(), 4);
+}
+
+#[tokio::test]
+async fn given_validation_error_when_handling_error_then_validation_exit_code_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let err = note::append(&db, "abc".to_string(), "  ".to_string(), None)
+        .await
+        .unwrap_err();
+    let err = TmgrError::from(err);
+    assert_eq!(err.code(), "E-NOTE-003");
+    assert!(std::error::Error::source(&err).is_some());
+    assert_eq!(handle_result(Err(err)).await.exit_code(), 5);
+}
+
+#[tokio::test]
+async fn given_unknown_task_id_when_converting_error_then_db_error_should_be_in_source_chain() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let err = TmgrError::from(
+        view::run(&db, "randomID".to_string(), false, Default::default())
+            .await
+            .unwrap_err(),
+    );
+    let view_error = std::error::Error::source(&err).expect("Expected the view error");
+    let db_error = view_error
+        .source()
+        .and_then(|e| e.downcast_ref::<db::DBError>())
+        .expect("Expected the db error");
+    assert_eq!(*db_error.kind(), db::DBErrorKind::NoTasksFound);
+}
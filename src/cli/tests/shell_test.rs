@@ -0,0 +1,166 @@
+use super::super::{
+    model::Command,
+    shell::{ShellInput, complete_line, parse_line, prompt},
+};
+use crate::config::Config;
+
+fn ids() -> Vec<(String, String)> {
+    vec![
+        ("abc123def".to_string(), "Write docs".to_string()),
+        ("abd999xyz".to_string(), "Fix bug".to_string()),
+    ]
+}
+
+#[test]
+fn given_exit_or_quit_when_parsing_line_then_should_exit() {
+    let config = Config::default();
+    assert!(matches!(
+        parse_line("exit", None, &config),
+        ShellInput::Exit
+    ));
+    assert!(matches!(
+        parse_line("quit", None, &config),
+        ShellInput::Exit
+    ));
+}
+
+#[test]
+fn given_blank_line_when_parsing_line_then_should_be_empty() {
+    assert!(matches!(
+        parse_line("   ", None, &Config::default()),
+        ShellInput::Empty
+    ));
+}
+
+#[test]
+fn given_use_with_and_without_id_when_parsing_line_then_should_set_or_clear_current_task() {
+    let config = Config::default();
+    assert!(matches!(
+        parse_line("use abc", None, &config),
+        ShellInput::Use(Some(id)) if id == "abc"
+    ));
+    assert!(matches!(
+        parse_line("use", Some("abc123"), &config),
+        ShellInput::Use(None)
+    ));
+}
+
+#[test]
+fn given_current_task_when_parsing_command_without_id_then_should_use_current_task() {
+    let res = parse_line("note -o", Some("abc123"), &Config::default());
+    match res {
+        ShellInput::Run {
+            name,
+            id,
+            command: Command::Note {
+                id: note_id, open, ..
+            },
+        } => {
+            assert_eq!(name, "note");
+            assert_eq!(id.as_deref(), Some("abc123"));
+            assert_eq!(note_id, "abc123");
+            assert!(open);
+        }
+        other => panic!("Expected a note command, got {other:?}"),
+    }
+}
+
+#[test]
+fn given_current_task_when_parsing_move_without_id_then_should_use_current_task_before_state() {
+    let res = parse_line("move done", Some("abc123"), &Config::default());
+    assert!(matches!(
+        res,
+        ShellInput::Run { command: Command::Move { id, .. }, .. } if id == "abc123"
+    ));
+}
+
+#[test]
+fn given_explicit_id_when_parsing_command_then_should_not_use_current_task() {
+    let res = parse_line("complete xyz", Some("abc123"), &Config::default());
+    assert!(matches!(
+        res,
+        ShellInput::Run { id: Some(id), command: Command::Complete { .. }, .. } if id == "xyz"
+    ));
+}
+
+#[test]
+fn given_no_current_task_when_parsing_command_without_id_then_should_be_invalid() {
+    assert!(matches!(
+        parse_line("complete", None, &Config::default()),
+        ShellInput::Invalid(_)
+    ));
+}
+
+#[test]
+fn given_alias_when_parsing_line_then_should_expand_it() {
+    let config = Config::parse("[aliases]\nfin = \"complete\"\n").unwrap();
+    let res = parse_line("fin", Some("abc123"), &config);
+    assert!(matches!(
+        res,
+        ShellInput::Run { command: Command::Complete { id }, .. } if id == "abc123"
+    ));
+}
+
+#[test]
+fn given_shell_command_when_parsing_line_then_should_be_invalid() {
+    match parse_line("shell", None, &Config::default()) {
+        ShellInput::Invalid(message) => assert_eq!(message, "Already in the tmgr shell"),
+        other => panic!("Expected the shell command to be rejected, got {other:?}"),
+    }
+}
+
+#[test]
+fn given_unknown_command_or_unclosed_quote_when_parsing_line_then_should_be_invalid() {
+    let config = Config::default();
+    assert!(matches!(
+        parse_line("bogus", None, &config),
+        ShellInput::Invalid(_)
+    ));
+    assert!(matches!(
+        parse_line("add \"unclosed", None, &config),
+        ShellInput::Invalid(_)
+    ));
+}
+
+#[test]
+fn given_partial_command_when_completing_then_should_list_matching_commands() {
+    let (start, candidates) = complete_line("co", &ids());
+    let names: Vec<String> = candidates.into_iter().map(|c| c.replacement).collect();
+    assert_eq!(start, 0);
+    assert_eq!(names, vec!["complete", "completions", "config"]);
+}
+
+#[test]
+fn given_empty_line_when_completing_then_should_include_builtins_but_not_shell() {
+    let (_, candidates) = complete_line("", &ids());
+    let names: Vec<String> = candidates.into_iter().map(|c| c.replacement).collect();
+    assert!(names.contains(&"use".to_string()));
+    assert!(names.contains(&"exit".to_string()));
+    assert!(!names.contains(&"shell".to_string()));
+    assert!(!names.contains(&"__complete-ids".to_string()));
+}
+
+#[test]
+fn given_id_command_when_completing_second_word_then_should_list_matching_ids_with_names() {
+    let (start, candidates) = complete_line("view abc", &ids());
+    assert_eq!(start, 5);
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].replacement, "abc123def");
+    assert_eq!(candidates[0].display, "abc123def  Write docs");
+
+    let (_, candidates) = complete_line("use ab", &ids());
+    assert_eq!(candidates.len(), 2);
+}
+
+#[test]
+fn given_command_without_id_when_completing_second_word_then_should_list_nothing() {
+    let (_, candidates) = complete_line("list a", &ids());
+    assert!(candidates.is_empty());
+}
+
+#[test]
+fn given_current_task_when_building_prompt_then_should_show_start_of_its_id() {
+    assert_eq!(prompt(Some("abc123def")), "tmgr [abc123]> ");
+    assert_eq!(prompt(Some("ab")), "tmgr [ab]> ");
+    assert_eq!(prompt(None), "tmgr> ");
+}
@@ -1,10 +1,15 @@
 use super::super::{
-    db::DB,
-    model::{CommandResult, Task, TaskPriority, TmgrError, TmgrErrorKind},
+    db::{DB, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TaskPriority, TaskState,
+        TmgrError, TmgrErrorKind,
+    },
 };
+use super::task_editor::{TaskFields, edit_task};
 use std::fmt;
 
-pub(crate) async fn run(
+/// Adds a task in the `todo` state, with the default priority unless one is given.
+pub async fn run(
     db: &DB,
     name: String,
     priority: Option<TaskPriority>,
@@ -12,7 +17,8 @@ pub(crate) async fn run(
 ) -> Result<CommandResult<Task>, AddError> {
     let mut task_builder = Task::builder()
         .name(&name)
-        .priority(priority.unwrap_or_default());
+        .priority(priority.unwrap_or_default())
+        .state(TaskState::Todo);
     if let Some(description) = description {
         task_builder = task_builder.description(description);
     }
@@ -21,9 +27,10 @@ pub(crate) async fn run(
         .client
         .create("task")
         .content(task_builder.build())
+        .logged("CREATE task CONTENT $task")
         .await
-        .map_err(|_| AddError {
-            kind: AddErrorKind::DatabaseError,
+        .map_err(|e| AddError {
+            kind: AddErrorKind::DatabaseError(Some(e.into())),
             message: format!("Failed to create task: '{name}'."),
         })?;
 
@@ -45,11 +52,33 @@ pub(crate) async fn run(
     }
 }
 
+/// Adds a task after letting the user fill it in with `$EDITOR`.
+///
+/// The given values pre-fill the document opened in the editor.
+pub(crate) async fn run_in_editor(
+    db: &DB,
+    name: Option<String>,
+    priority: Option<TaskPriority>,
+    description: Option<String>,
+) -> Result<CommandResult<Task>, AddError> {
+    let fields = edit_task(&TaskFields {
+        name: name.unwrap_or_default(),
+        priority: priority.unwrap_or_default(),
+        description,
+    })
+    .map_err(|e| AddError {
+        kind: AddErrorKind::EditorError,
+        message: e.to_string(),
+    })?;
+    run(db, fields.name, Some(fields.priority), fields.description).await
+}
+
 // -- Add Errors ---
 #[derive(Debug)]
 pub enum AddErrorKind {
     BadTaskId,
-    DatabaseError,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    EditorError,
     FailedToCreateTask,
 }
 
@@ -59,24 +88,72 @@ pub struct AddError {
     message: String,
 }
 
+impl AddError {
+    pub fn kind(&self) -> &AddErrorKind {
+        &self.kind
+    }
+}
+
 impl fmt::Display for AddError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} (add error: {})", self.message, self.kind)
     }
 }
 
+impl std::error::Error for AddError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            AddErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for AddErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             AddErrorKind::BadTaskId => write!(f, "Bad task id"),
-            AddErrorKind::DatabaseError => write!(f, "Database error"),
+            AddErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            AddErrorKind::EditorError => write!(f, "Editor error"),
             AddErrorKind::FailedToCreateTask => write!(f, "Failed to create task"),
         }
     }
 }
 
+impl ErrorCode for AddErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            AddErrorKind::BadTaskId => "E-ADD-001",
+            AddErrorKind::DatabaseError(_) => "E-ADD-002",
+            AddErrorKind::EditorError => "E-ADD-003",
+            AddErrorKind::FailedToCreateTask => "E-ADD-004",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            AddErrorKind::BadTaskId
+            | AddErrorKind::DatabaseError(_)
+            | AddErrorKind::FailedToCreateTask => ErrorCategory::Internal,
+            AddErrorKind::EditorError => ErrorCategory::IO,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            AddErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            AddErrorKind::EditorError => Some(
+                "Set the editor setting of the config file, or the $EDITOR environment variable",
+            ),
+            _ => None,
+        }
+    }
+}
+
 impl From<AddError> for TmgrError {
     fn from(err: AddError) -> Self {
         TmgrError::new(TmgrErrorKind::AddCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
@@ -0,0 +1,244 @@
+use super::super::{
+    db::{DB, DBError, Logged},
+    model::{
+        Attachment, AttachmentKind, CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task,
+        TmgrError, TmgrErrorKind,
+    },
+};
+use super::note::notes_dir;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{copy, create_dir_all},
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+pub(crate) async fn run(
+    db: &DB,
+    id: String,
+    target: String,
+    link: bool,
+) -> Result<CommandResult<Task>, AttachError> {
+    let task = db
+        .select_task_by_partial_id(&id)
+        .await
+        .map_err(|e| AttachError {
+            message: e.to_string(),
+            kind: AttachErrorKind::TaskLookup(e),
+        })?;
+    let task_id = task.id().map_err(|e| AttachError {
+        kind: AttachErrorKind::BadTaskId,
+        message: e.to_string(),
+    })?;
+
+    let attachment = if is_url(&target) {
+        Attachment::new(AttachmentKind::Url, target.clone())
+    } else {
+        let path = Path::new(&target).canonicalize().map_err(|e| AttachError {
+            kind: AttachErrorKind::FileNotFound,
+            message: format!("Unable to attach '{target}': {e}"),
+        })?;
+        if !path.is_file() {
+            return Err(AttachError {
+                kind: AttachErrorKind::NotAFile,
+                message: format!("Unable to attach '{target}', only files can be attached"),
+            });
+        }
+        if link {
+            Attachment::new(AttachmentKind::Path, path.to_string_lossy().to_string())
+        } else {
+            let copied = copy_into(&task_attachments_dir(&task_id)?, &path)?;
+            Attachment::new(AttachmentKind::Copy, copied.to_string_lossy().to_string())
+        }
+    };
+    if attachment.kind() != &AttachmentKind::Copy
+        && task
+            .attachments()
+            .iter()
+            .any(|a| a.target() == attachment.target())
+    {
+        return Err(AttachError {
+            kind: AttachErrorKind::AlreadyAttached,
+            message: format!("'{target}' is already attached to task '{task_id}'"),
+        });
+    }
+
+    let mut attachments = task.attachments().clone();
+    attachments.push(attachment);
+    let number = attachments.len();
+    let update: BTreeMap<&str, Vec<Attachment>> = BTreeMap::from([("attachments", attachments)]);
+    let updated_task: Task = db
+        .client
+        .update(("task", &task_id))
+        .merge(update)
+        .logged(&format!("UPDATE task:{task_id} MERGE $data"))
+        .await
+        .map_err(|e| AttachError {
+            kind: AttachErrorKind::DatabaseError(Some(e.into())),
+            message: "Failed to update task".to_string(),
+        })?
+        .ok_or_else(|| AttachError {
+            kind: AttachErrorKind::DatabaseError(None),
+            message: "Failed to update task".to_string(),
+        })?;
+
+    Ok(CommandResult::new(
+        format!("Successfully attached '{target}' to task '{task_id}' as attachment {number}"),
+        updated_task,
+    ))
+}
+
+/// The directory where attached files are copied, next to the notes directory.
+pub(super) fn attachments_dir() -> Result<PathBuf, AttachError> {
+    let notes_dir = notes_dir().map_err(|e| AttachError {
+        kind: AttachErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    let dir_path = notes_dir.parent().ok_or(AttachError {
+        kind: AttachErrorKind::IOError,
+        message: "Could not get parent directory of the notes directory".to_string(),
+    })?;
+    Ok(dir_path.join("tmgr_attachments"))
+}
+
+/// The folder holding the files copied for one task.
+pub(super) fn task_attachments_dir(task_id: &str) -> Result<PathBuf, AttachError> {
+    Ok(attachments_dir()?.join(task_id))
+}
+
+/// Copies a file into `dir`, renaming it if a file with the same name was already copied.
+pub(super) fn copy_into(dir: &Path, file: &Path) -> Result<PathBuf, AttachError> {
+    create_dir_all(dir).map_err(|e| AttachError {
+        kind: AttachErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or("attachment".to_string());
+    let extension = file
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let mut destination = dir.join(format!("{stem}{extension}"));
+    let mut n = 1;
+    while destination.exists() {
+        destination = dir.join(format!("{stem}-{n}{extension}"));
+        n += 1;
+    }
+    info!(
+        "Copying '{}' to '{}'",
+        file.display(),
+        destination.display()
+    );
+    copy(file, &destination).map_err(|e| AttachError {
+        kind: AttachErrorKind::IOError,
+        message: format!("Failed to copy '{}': {e}", file.display()),
+    })?;
+    Ok(destination)
+}
+
+fn is_url(target: &str) -> bool {
+    target
+        .split_once("://")
+        .is_some_and(|(scheme, rest)| !rest.is_empty() && scheme.chars().all(char::is_alphanumeric))
+}
+
+// --- Attach Errors ---
+#[derive(Debug)]
+pub enum AttachErrorKind {
+    AlreadyAttached,
+    BadTaskId,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    FileNotFound,
+    IOError,
+    NotAFile,
+    TaskLookup(DBError),
+}
+
+#[derive(Debug)]
+pub struct AttachError {
+    kind: AttachErrorKind,
+    message: String,
+}
+
+impl fmt::Display for AttachError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (attach error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for AttachError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            AttachErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            AttachErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AttachErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttachErrorKind::AlreadyAttached => write!(f, "Already attached"),
+            AttachErrorKind::BadTaskId => write!(f, "Bad task id"),
+            AttachErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            AttachErrorKind::FileNotFound => write!(f, "File not found"),
+            AttachErrorKind::IOError => write!(f, "IO error"),
+            AttachErrorKind::NotAFile => write!(f, "Not a file"),
+            AttachErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+        }
+    }
+}
+
+impl ErrorCode for AttachErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            AttachErrorKind::AlreadyAttached => "E-ATTACH-001",
+            AttachErrorKind::BadTaskId => "E-ATTACH-002",
+            AttachErrorKind::DatabaseError(_) => "E-ATTACH-003",
+            AttachErrorKind::FileNotFound => "E-ATTACH-004",
+            AttachErrorKind::IOError => "E-ATTACH-005",
+            AttachErrorKind::NotAFile => "E-ATTACH-006",
+            AttachErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            AttachErrorKind::AlreadyAttached | AttachErrorKind::NotAFile => {
+                ErrorCategory::Validation
+            }
+            AttachErrorKind::BadTaskId | AttachErrorKind::DatabaseError(_) => {
+                ErrorCategory::Internal
+            }
+            AttachErrorKind::FileNotFound => ErrorCategory::NotFound,
+            AttachErrorKind::IOError => ErrorCategory::IO,
+            AttachErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            AttachErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            AttachErrorKind::FileNotFound => {
+                Some("Check the path, relative paths are resolved from the current directory")
+            }
+            AttachErrorKind::NotAFile => {
+                Some("Attach a file or a URL, directories cannot be attached")
+            }
+            AttachErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
+        }
+    }
+}
+
+impl From<AttachError> for TmgrError {
+    fn from(err: AttachError) -> Self {
+        TmgrError::new(TmgrErrorKind::AttachCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -0,0 +1,157 @@
+use super::super::{
+    cli::model::ListSort,
+    db::{DB, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TaskState, TmgrError,
+        TmgrErrorKind,
+    },
+};
+use super::list::sort_tasks;
+use clap::ValueEnum;
+use comfy_table::{Cell, ContentArrangement::Dynamic, Table};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// Shows the tasks in a column per state of the workflow set by `transitions`.
+pub(crate) async fn run(
+    db: &DB,
+    all: bool,
+    transitions: &BTreeMap<TaskState, Vec<TaskState>>,
+) -> Result<CommandResult<Vec<Task>>, BoardError> {
+    let mut tasks: Vec<Task> = db
+        .client
+        .select("task")
+        .logged("SELECT * FROM task")
+        .await
+        .map_err(|e| BoardError {
+            kind: BoardErrorKind::DatabaseError(Some(e.into())),
+            message: "Failed to get all tasks".to_string(),
+        })?;
+    sort_tasks(&mut tasks, &ListSort::Priority);
+
+    let workflow = workflow_states(transitions);
+    let columns: Vec<TaskState> = TaskState::value_variants()
+        .iter()
+        .filter(|state| workflow.contains(state) || tasks.iter().any(|t| t.state() == **state))
+        .filter(|state| all || !state.is_closed())
+        .cloned()
+        .collect();
+    let cards: Vec<Vec<String>> = columns
+        .iter()
+        .map(|state| {
+            tasks
+                .iter()
+                .filter(|t| t.state() == *state)
+                .map(card)
+                .collect()
+        })
+        .collect();
+
+    let mut table = Table::new();
+    table.set_content_arrangement(Dynamic).set_header(
+        columns
+            .iter()
+            .zip(&cards)
+            .map(|(state, cards)| format!("{state} ({})", cards.len())),
+    );
+    let rows = cards.iter().map(Vec::len).max().unwrap_or_default();
+    for row in 0..rows {
+        table.add_row(
+            cards
+                .iter()
+                .map(|column| Cell::new(column.get(row).map(String::as_str).unwrap_or_default())),
+        );
+    }
+
+    Ok(CommandResult::new(table.to_string(), tasks))
+}
+
+/// The states a task can reach from `todo` through the transitions, along with `done`, which
+/// `tmgr complete` moves tasks to whatever the transitions are.
+fn workflow_states(transitions: &BTreeMap<TaskState, Vec<TaskState>>) -> BTreeSet<TaskState> {
+    let mut states = BTreeSet::from([TaskState::Todo, TaskState::Done]);
+    let mut pending = vec![TaskState::Todo, TaskState::Done];
+    while let Some(state) = pending.pop() {
+        for next in transitions.get(&state).into_iter().flatten() {
+            if states.insert(next.clone()) {
+                pending.push(next.clone());
+            }
+        }
+    }
+    states
+}
+
+/// The text of a task on the board: its name, followed by its id and priority.
+fn card(task: &Task) -> String {
+    format!(
+        "{}\n{} [{}]",
+        task.name(),
+        task.id().unwrap_or("Error getting ID".to_string()),
+        task.priority()
+    )
+}
+
+// --- Board Errors ---
+#[derive(Debug)]
+pub enum BoardErrorKind {
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+}
+
+#[derive(Debug)]
+pub struct BoardError {
+    kind: BoardErrorKind,
+    message: String,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (board error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for BoardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            BoardErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BoardErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardErrorKind::DatabaseError(_) => write!(f, "Database error"),
+        }
+    }
+}
+
+impl ErrorCode for BoardErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            BoardErrorKind::DatabaseError(_) => "E-BOARD-001",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            BoardErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            BoardErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+        }
+    }
+}
+
+impl From<BoardError> for TmgrError {
+    fn from(err: BoardError) -> Self {
+        TmgrError::new(TmgrErrorKind::BoardCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -1,34 +1,29 @@
 use super::super::{
-    db::DB,
-    model::{CommandResult, Task, TmgrError, TmgrErrorKind},
+    db::{DB, DBError},
+    model::{CommandResult, ErrorCategory, ErrorCode, Task, TaskState, TmgrError, TmgrErrorKind},
 };
+use super::move_task::{MoveError, transition_task};
 use std::fmt;
-use surrealdb::{opt::PatchOp, sql::Datetime};
 
-pub(crate) async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, CompleteError> {
+/// Completes the task whose id starts with `id`.
+pub async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, CompleteError> {
     let task = db
         .select_task_by_partial_id(&id)
         .await
         .map_err(|e| CompleteError {
-            kind: CompleteErrorKind::DatabaseError,
             message: e.to_string(),
+            kind: CompleteErrorKind::TaskLookup(e),
         })?;
     let task_id = task.id().map_err(|e| CompleteError {
         kind: CompleteErrorKind::BadTaskId,
         message: e.to_string(),
     })?;
-    let t: Task = db
-        .client
-        .upsert(("task", &task_id))
-        .patch(PatchOp::replace("/completed_at", Datetime::default()))
+    // completing a task is always allowed, regardless of the configured transitions
+    let t: Task = transition_task(db, &task, TaskState::Done)
         .await
-        .map_err(|_| CompleteError {
-            kind: CompleteErrorKind::DatabaseError,
-            message: "Failed to set task to complete".to_string(),
-        })?
-        .ok_or_else(|| CompleteError {
-            kind: CompleteErrorKind::DatabaseError,
-            message: "Failed to set task to complete".to_string(),
+        .map_err(|e| CompleteError {
+            message: e.to_string(),
+            kind: CompleteErrorKind::MoveError(e),
         })?;
 
     Ok(CommandResult::new(
@@ -40,7 +35,8 @@ pub(crate) async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, Comp
 #[derive(Debug)]
 pub enum CompleteErrorKind {
     BadTaskId,
-    DatabaseError,
+    MoveError(MoveError),
+    TaskLookup(DBError),
 }
 
 #[derive(Debug)]
@@ -49,17 +45,60 @@ pub struct CompleteError {
     message: String,
 }
 
+impl CompleteError {
+    pub fn kind(&self) -> &CompleteErrorKind {
+        &self.kind
+    }
+}
+
 impl fmt::Display for CompleteError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} (complete error: {})", self.message, self.kind)
     }
 }
 
+impl std::error::Error for CompleteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            CompleteErrorKind::MoveError(e) => Some(e),
+            CompleteErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for CompleteErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             CompleteErrorKind::BadTaskId => write!(f, "Bad task id"),
-            CompleteErrorKind::DatabaseError => write!(f, "Database error"),
+            CompleteErrorKind::MoveError(e) => write!(f, "{}", e.kind()),
+            CompleteErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+        }
+    }
+}
+
+impl ErrorCode for CompleteErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            CompleteErrorKind::BadTaskId => "E-COMPLETE-001",
+            CompleteErrorKind::MoveError(e) => e.kind().code(),
+            CompleteErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            CompleteErrorKind::BadTaskId => ErrorCategory::Internal,
+            CompleteErrorKind::MoveError(e) => e.kind().category(),
+            CompleteErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            CompleteErrorKind::MoveError(e) => e.kind().hint(),
+            CompleteErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
         }
     }
 }
@@ -67,5 +106,7 @@ impl fmt::Display for CompleteErrorKind {
 impl From<CompleteError> for TmgrError {
     fn from(err: CompleteError) -> Self {
         TmgrError::new(TmgrErrorKind::CompleteCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
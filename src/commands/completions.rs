@@ -0,0 +1,240 @@
+use super::super::{
+    cli::model::{Cli, CompletionShell},
+    db::{DB, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TmgrError, TmgrErrorKind,
+    },
+};
+use clap::CommandFactory;
+use clap_complete::{Shell, generate};
+use std::fmt;
+
+/// Prints the completion script of a shell.
+///
+/// The script is generated from the command line definition, and completes the task id of
+/// the commands taking one by calling `tmgr __complete-ids`.
+pub(crate) fn run(shell: CompletionShell) -> Result<CommandResult<String>, CompletionsError> {
+    let script = script(&shell)?;
+    Ok(CommandResult::new(script.clone(), script))
+}
+
+/// The completion script of a shell.
+pub(super) fn script(shell: &CompletionShell) -> Result<String, CompletionsError> {
+    let target = match shell {
+        CompletionShell::Bash => Shell::Bash,
+        CompletionShell::Zsh => Shell::Zsh,
+        CompletionShell::Fish => Shell::Fish,
+        CompletionShell::Powershell => Shell::PowerShell,
+    };
+    let mut buffer = Vec::new();
+    generate(target, &mut Cli::command(), "tmgr", &mut buffer);
+    let script = String::from_utf8(buffer).map_err(|e| CompletionsError {
+        kind: CompletionsErrorKind::InvalidScript,
+        message: e.to_string(),
+    })?;
+
+    let commands = id_commands();
+    Ok(match shell {
+        CompletionShell::Bash => bash_ids(script, &commands),
+        CompletionShell::Zsh => zsh_ids(script)?,
+        CompletionShell::Fish => fish_ids(script, &commands),
+        CompletionShell::Powershell => powershell_ids(script, &commands),
+    })
+}
+
+/// The commands whose first argument is a task id.
+pub(crate) fn id_commands() -> Vec<String> {
+    Cli::command()
+        .get_subcommands()
+        .filter(|c| {
+            c.get_positionals()
+                .next()
+                .is_some_and(|arg| arg.get_id() == "id")
+        })
+        .map(|c| c.get_name().to_string())
+        .collect()
+}
+
+/// Wraps the generated completion function, completing ids right after an id command.
+fn bash_ids(script: String, commands: &[String]) -> String {
+    format!(
+        r#"{script}
+_tmgr_ids() {{
+    if [[ ${{COMP_CWORD}} -eq 2 ]]; then
+        case "${{COMP_WORDS[1]}}" in
+            {})
+                local IFS=$'\n'
+                COMPREPLY=( $(compgen -W "$(tmgr __complete-ids 2>/dev/null | cut -f1)" -- "${{COMP_WORDS[COMP_CWORD]}}") )
+                return 0
+                ;;
+        esac
+    fi
+    _tmgr "$@"
+}}
+
+if [[ "${{BASH_VERSINFO[0]}}" -eq 4 && "${{BASH_VERSINFO[1]}}" -ge 4 || "${{BASH_VERSINFO[0]}}" -gt 4 ]]; then
+    complete -F _tmgr_ids -o nosort -o bashdefault -o default tmgr
+else
+    complete -F _tmgr_ids -o bashdefault -o default tmgr
+fi
+"#,
+        commands.join("|")
+    )
+}
+
+/// Completes the `id` arguments with the ids and names of tasks.
+fn zsh_ids(script: String) -> Result<String, CompletionsError> {
+    let ending = "if [ \"$funcstack[1]\" = \"_tmgr\" ]; then";
+    let (body, end) = script.split_once(ending).ok_or(CompletionsError {
+        kind: CompletionsErrorKind::InvalidScript,
+        message: "The generated zsh script does not end as expected".to_string(),
+    })?;
+    let body = body
+        .lines()
+        .map(|line| match line.strip_suffix(":_default' \\") {
+            Some(spec) if line.starts_with("':id -- ") => format!("{spec}:_tmgr_ids' \\"),
+            _ => line.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    Ok(format!(
+        r#"{body}
+(( $+functions[_tmgr_ids] )) ||
+_tmgr_ids() {{
+    local -a ids
+    ids=(${{(f)"$(tmgr __complete-ids 2>/dev/null)"}})
+    ids=(${{ids//:/\\:}})
+    ids=(${{ids//$'\t'/:}})
+    _describe -t ids 'task id' ids
+}}
+
+{ending}{end}"#
+    ))
+}
+
+/// Completes the first argument of id commands with the ids and names of tasks.
+fn fish_ids(script: String, commands: &[String]) -> String {
+    format!(
+        "{script}complete -c tmgr -f -n \"__fish_seen_subcommand_from {}; and test (count (commandline -opc)) -eq 2\" -a \"(tmgr __complete-ids 2>/dev/null)\"\n",
+        commands.join(" ")
+    )
+}
+
+/// Adds the ids and names of tasks to the completions of id commands.
+fn powershell_ids(script: String, commands: &[String]) -> String {
+    let ids = r#"
+            foreach ($task in @(tmgr __complete-ids 2>$null)) {
+                $id, $name = $task -split "`t", 2
+                [CompletionResult]::new($id, $id, [CompletionResultType]::ParameterValue, $(if ($name) { $name } else { $id }))
+            }"#;
+    commands.iter().fold(script, |script, command| {
+        let block = format!("'tmgr;{command}' {{");
+        script.replace(&block, &format!("{block}{ids}"))
+    })
+}
+
+/// Prints the ids and names of the in-progress tasks, one `<id>\t<name>` line per task.
+pub(crate) async fn ids(
+    db: &DB,
+    prefix: Option<String>,
+) -> Result<CommandResult<Vec<Task>>, CompletionsError> {
+    let query = "SELECT * FROM task WHERE completed_at IS None";
+    let mut tasks: Vec<Task> = db
+        .client
+        .query(query)
+        .logged(query)
+        .await
+        .map_err(|e| CompletionsError {
+            message: e.to_string(),
+            kind: CompletionsErrorKind::DatabaseError(Some(e.into())),
+        })?
+        .take(0)
+        .map_err(|e| CompletionsError {
+            message: e.to_string(),
+            kind: CompletionsErrorKind::DatabaseError(Some(e.into())),
+        })?;
+    let prefix = prefix.unwrap_or_default().to_lowercase();
+    tasks.retain(|t| {
+        t.id()
+            .is_ok_and(|id| id.to_lowercase().starts_with(&prefix))
+    });
+    tasks.sort_by(|a, b| b.created_at().cmp(a.created_at()));
+
+    let lines: Vec<String> = tasks
+        .iter()
+        .filter_map(|t| {
+            let name = t.name().replace(['\t', '\n', '\r'], " ");
+            t.id().ok().map(|id| format!("{id}\t{name}"))
+        })
+        .collect();
+    Ok(CommandResult::new(lines.join("\n"), tasks))
+}
+
+// --- Completions Errors ---
+#[derive(Debug)]
+pub enum CompletionsErrorKind {
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    InvalidScript,
+}
+
+#[derive(Debug)]
+pub struct CompletionsError {
+    kind: CompletionsErrorKind,
+    message: String,
+}
+
+impl fmt::Display for CompletionsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (completions error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for CompletionsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            CompletionsErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CompletionsErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompletionsErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            CompletionsErrorKind::InvalidScript => write!(f, "Invalid script"),
+        }
+    }
+}
+
+impl ErrorCode for CompletionsErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            CompletionsErrorKind::DatabaseError(_) => "E-COMPLETIONS-001",
+            CompletionsErrorKind::InvalidScript => "E-COMPLETIONS-002",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            CompletionsErrorKind::DatabaseError(_) | CompletionsErrorKind::InvalidScript => {
+                ErrorCategory::Internal
+            }
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            CompletionsErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            _ => None,
+        }
+    }
+}
+
+impl From<CompletionsError> for TmgrError {
+    fn from(err: CompletionsError) -> Self {
+        TmgrError::new(TmgrErrorKind::CompletionsCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
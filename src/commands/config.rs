@@ -0,0 +1,224 @@
+use super::super::{
+    config::{Config, PROJECT_CONFIG_FILE},
+    model::{CommandResult, ErrorCategory, ErrorCode, TmgrError, TmgrErrorKind},
+};
+use super::note::open_note;
+use std::{
+    env::current_dir,
+    fmt,
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+/// Prints the effective value of a setting.
+pub(crate) fn get(
+    config: &Config,
+    key: String,
+) -> Result<CommandResult<Option<String>>, ConfigError> {
+    let value = config.get(&key).map_err(|e| ConfigError {
+        kind: ConfigErrorKind::InvalidConfig,
+        message: e.to_string(),
+    })?;
+    let message = value
+        .clone()
+        .unwrap_or(format!("'{key}' is not set, the default is used"));
+    Ok(CommandResult::new(message, value))
+}
+
+/// Sets a setting in the global config file, or in the project config file with `project`.
+pub(crate) fn set(
+    key: String,
+    value: String,
+    project: bool,
+) -> Result<CommandResult<PathBuf>, ConfigError> {
+    let (global, project_file) = config_files()?;
+    let file = set_in(&global, &project_file, project, &key, &value)?;
+    Ok(CommandResult::new(
+        format!("Set '{key}' to {value} in '{}'", file.display()),
+        file,
+    ))
+}
+
+/// Sets a setting in `global`, or in `project` with `in_project`, and returns the file changed.
+///
+/// The file is only written if the settings of both files are valid once merged.
+pub(super) fn set_in(
+    global: &Path,
+    project: &Path,
+    in_project: bool,
+    key: &str,
+    value: &str,
+) -> Result<PathBuf, ConfigError> {
+    let file = if in_project { project } else { global };
+    let contents = read_file(file)?;
+    let updated = Config::set_value(&contents, key, value).map_err(|e| ConfigError {
+        kind: ConfigErrorKind::InvalidConfig,
+        message: e.to_string(),
+    })?;
+
+    let mut sources = vec![
+        (global.to_path_buf(), read_file(global)?),
+        (project.to_path_buf(), read_file(project)?),
+    ];
+    sources[usize::from(in_project)].1 = updated.clone();
+    Config::from_sources(&sources).map_err(|e| ConfigError {
+        kind: ConfigErrorKind::InvalidConfig,
+        message: e.to_string(),
+    })?;
+
+    if let Some(dir) = file.parent() {
+        create_dir_all(dir).map_err(|e| ConfigError {
+            kind: ConfigErrorKind::IOError,
+            message: e.to_string(),
+        })?;
+    }
+    write(file, updated).map_err(|e| ConfigError {
+        kind: ConfigErrorKind::IOError,
+        message: format!("Failed to write config file '{}': {e}", file.display()),
+    })?;
+    Ok(file.to_path_buf())
+}
+
+/// Prints the config files and the effective settings.
+pub(crate) fn list(config: &Config) -> Result<CommandResult<Config>, ConfigError> {
+    let (global, project) = config_files()?;
+    let settings = config.to_toml().map_err(|e| ConfigError {
+        kind: ConfigErrorKind::InvalidConfig,
+        message: e.to_string(),
+    })?;
+    let message = format!(
+        "Config files:\n  global: {}\n  project: {}\n\nSettings:\n{}",
+        describe_file(&global),
+        describe_file(&project),
+        settings.trim_end()
+    );
+    Ok(CommandResult::new(message, config.clone()))
+}
+
+/// Opens the global config file, or the project config file, in the editor, then checks it.
+pub(crate) fn edit(project: bool) -> Result<CommandResult<PathBuf>, ConfigError> {
+    let (global, project_file) = config_files()?;
+    let file = if project { &project_file } else { &global };
+    if let Some(dir) = file.parent() {
+        create_dir_all(dir).map_err(|e| ConfigError {
+            kind: ConfigErrorKind::IOError,
+            message: e.to_string(),
+        })?;
+    }
+    open_note(&file.to_string_lossy()).map_err(|e| ConfigError {
+        kind: ConfigErrorKind::EditorError,
+        message: e.to_string(),
+    })?;
+    Config::load_files(&global, Some(&project_file)).map_err(|e| ConfigError {
+        kind: ConfigErrorKind::InvalidConfig,
+        message: e.to_string(),
+    })?;
+    Ok(CommandResult::new(
+        format!("Config file '{}' is valid", file.display()),
+        file.clone(),
+    ))
+}
+
+/// The global config file and the project config file, which is created in the current
+/// directory if there is none above it.
+fn config_files() -> Result<(PathBuf, PathBuf), ConfigError> {
+    let (global, project) = Config::config_files().map_err(|e| ConfigError {
+        kind: ConfigErrorKind::InvalidConfig,
+        message: e.to_string(),
+    })?;
+    let project = match project {
+        Some(project) => project,
+        None => current_dir()
+            .map_err(|e| ConfigError {
+                kind: ConfigErrorKind::IOError,
+                message: e.to_string(),
+            })?
+            .join(PROJECT_CONFIG_FILE),
+    };
+    Ok((global, project))
+}
+
+fn describe_file(path: &Path) -> String {
+    if path.is_file() {
+        path.display().to_string()
+    } else {
+        format!("{} (not found)", path.display())
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ConfigError> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    read_to_string(path).map_err(|e| ConfigError {
+        kind: ConfigErrorKind::IOError,
+        message: format!("Failed to read config file '{}': {e}", path.display()),
+    })
+}
+
+// --- Config Errors ---
+#[derive(Debug)]
+pub enum ConfigErrorKind {
+    EditorError,
+    InvalidConfig,
+    IOError,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    kind: ConfigErrorKind,
+    message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (config error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for ConfigErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigErrorKind::EditorError => write!(f, "Editor error"),
+            ConfigErrorKind::InvalidConfig => write!(f, "Invalid config"),
+            ConfigErrorKind::IOError => write!(f, "IO error"),
+        }
+    }
+}
+
+impl ErrorCode for ConfigErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ConfigErrorKind::EditorError => "E-CONFIG-001",
+            ConfigErrorKind::InvalidConfig => "E-CONFIG-002",
+            ConfigErrorKind::IOError => "E-CONFIG-003",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ConfigErrorKind::EditorError | ConfigErrorKind::IOError => ErrorCategory::IO,
+            ConfigErrorKind::InvalidConfig => ErrorCategory::Validation,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            ConfigErrorKind::EditorError => Some(
+                "Set the editor setting of the config file, or the $EDITOR environment variable",
+            ),
+            ConfigErrorKind::InvalidConfig => Some("Run 'tmgr config edit' again to fix it"),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for TmgrError {
+    fn from(err: ConfigError) -> Self {
+        TmgrError::new(TmgrErrorKind::ConfigCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -1,16 +1,24 @@
 use super::super::{
-    db::DB,
-    model::{CommandResult, Task, TmgrError, TmgrErrorKind},
+    db::{DB, DBError, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TmgrError, TmgrErrorKind,
+    },
+};
+use super::attach::task_attachments_dir;
+use std::{
+    fmt,
+    fs::{remove_dir_all, remove_file},
+    path::Path,
 };
-use std::{fmt, fs::remove_file, path::Path};
 
-pub(crate) async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, DeleteError> {
+/// Deletes the task whose id starts with `id`, along with its note.
+pub async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, DeleteError> {
     let task = db
         .select_task_by_partial_id(&id)
         .await
         .map_err(|e| DeleteError {
-            kind: DeleteErrorKind::DatabaseError,
             message: e.to_string(),
+            kind: DeleteErrorKind::TaskLookup(e),
         })?;
     let task_note_path = task.work_note_path();
     let task_id = task.id().map_err(|e| DeleteError {
@@ -22,10 +30,11 @@ pub(crate) async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, Dele
     let task: Task = db
         .client
         .delete(("task", &task_id))
+        .logged(&format!("DELETE task:{task_id}"))
         .await
-        .map_err(|_| DeleteError {
-            kind: DeleteErrorKind::FailedToDeleteTask,
-            message: "Failed to delete task".to_string(),
+        .map_err(|e| DeleteError {
+            message: format!("Failed to delete task: {e}"),
+            kind: DeleteErrorKind::DatabaseError(Some(e.into())),
         })?
         .ok_or_else(|| DeleteError {
             kind: DeleteErrorKind::FailedToDeleteTask,
@@ -44,6 +53,18 @@ pub(crate) async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, Dele
         }
     }
 
+    // Delete copied attachments if any
+    let attachments_dir = task_attachments_dir(&task_id).map_err(|e| DeleteError {
+        kind: DeleteErrorKind::FailedToDeleteAttachments,
+        message: e.to_string(),
+    })?;
+    if attachments_dir.exists() {
+        remove_dir_all(&attachments_dir).map_err(|e| DeleteError {
+            kind: DeleteErrorKind::FailedToDeleteAttachments,
+            message: e.to_string(),
+        })?;
+    }
+
     Ok(CommandResult::new(
         format!("Successfully deleted task '{task_id}'"),
         task,
@@ -54,9 +75,11 @@ pub(crate) async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, Dele
 #[derive(Debug)]
 pub enum DeleteErrorKind {
     BadTaskId,
-    DatabaseError,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    FailedToDeleteAttachments,
     FailedToDeleteTask,
     FailedToDeleteNote,
+    TaskLookup(DBError),
 }
 
 #[derive(Debug)]
@@ -71,13 +94,58 @@ impl fmt::Display for DeleteError {
     }
 }
 
+impl std::error::Error for DeleteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            DeleteErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            DeleteErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for DeleteErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             DeleteErrorKind::BadTaskId => write!(f, "Bad task id"),
-            DeleteErrorKind::DatabaseError => write!(f, "Database error"),
+            DeleteErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            DeleteErrorKind::FailedToDeleteAttachments => write!(f, "Failed to delete attachments"),
             DeleteErrorKind::FailedToDeleteNote => write!(f, "Failed to delete note"),
             DeleteErrorKind::FailedToDeleteTask => write!(f, "Failed to delete task"),
+            DeleteErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+        }
+    }
+}
+
+impl ErrorCode for DeleteErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            DeleteErrorKind::BadTaskId => "E-DELETE-001",
+            DeleteErrorKind::DatabaseError(_) => "E-DELETE-002",
+            DeleteErrorKind::FailedToDeleteAttachments => "E-DELETE-003",
+            DeleteErrorKind::FailedToDeleteTask => "E-DELETE-004",
+            DeleteErrorKind::FailedToDeleteNote => "E-DELETE-005",
+            DeleteErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            DeleteErrorKind::BadTaskId
+            | DeleteErrorKind::DatabaseError(_)
+            | DeleteErrorKind::FailedToDeleteTask => ErrorCategory::Internal,
+            DeleteErrorKind::FailedToDeleteAttachments | DeleteErrorKind::FailedToDeleteNote => {
+                ErrorCategory::IO
+            }
+            DeleteErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            DeleteErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            DeleteErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
         }
     }
 }
@@ -85,5 +153,7 @@ impl fmt::Display for DeleteErrorKind {
 impl From<DeleteError> for TmgrError {
     fn from(err: DeleteError) -> Self {
         TmgrError::new(TmgrErrorKind::DeleteCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
@@ -0,0 +1,495 @@
+use super::super::{
+    db::{DB, Logged},
+    model::{
+        CommandResult, ErrorCategory, ErrorCode, Task, TaskPriority, TmgrError, TmgrErrorKind,
+        log_error,
+    },
+};
+use super::{
+    link::{check_link, refresh_link, save_links},
+    note,
+};
+use std::{
+    collections::HashSet,
+    fmt,
+    fs::{create_dir_all, read_dir, rename},
+    path::{Path, PathBuf},
+};
+use surrealdb::Response;
+
+/// The records tmgr can address by id: those whose id is a string written as is, since
+/// commands strip the `task:` prefix from the id and use the rest as a string id.
+const ADDRESSABLE_ID: &str =
+    "type::is::string(record::id(id)) AND <string> id = string::concat('task:', record::id(id))";
+
+pub(crate) async fn run(
+    db: &DB,
+    fix: bool,
+) -> Result<CommandResult<Vec<DoctorIssue>>, DoctorError> {
+    let notes_dir = note::notes_dir().map_err(|e| DoctorError {
+        kind: DoctorErrorKind::UnableToDetermineNotesDirectory,
+        message: e.to_string(),
+    })?;
+
+    let mut issues = diagnose(db, &notes_dir).await?;
+    if fix {
+        repair(db, &notes_dir, &mut issues).await?;
+    }
+
+    Ok(CommandResult::new(report(&issues, fix), issues))
+}
+
+/// Scans the task table and the notes directory for inconsistencies.
+///
+/// The database is read field by field where possible so that a single broken
+/// record does not prevent the rest of the store from being checked.
+pub(super) async fn diagnose(db: &DB, notes_dir: &Path) -> Result<Vec<DoctorIssue>, DoctorError> {
+    let mut issues = Vec::new();
+
+    let ids: Vec<String> = query_values(
+        db,
+        &format!("SELECT VALUE <string> record::id(id) FROM task WHERE {ADDRESSABLE_ID}"),
+    )
+    .await?;
+
+    // numeric, complex or escaped ids select no task, or another one, so they are read raw
+    let bad_ids: Vec<String> = query_values(
+        db,
+        &format!("SELECT VALUE <string> id FROM task WHERE !({ADDRESSABLE_ID})"),
+    )
+    .await?;
+    for id in &bad_ids {
+        issues.push(DoctorIssue::new(
+            DoctorIssueKind::BadTaskId,
+            id,
+            "id is not a plain string, so tmgr cannot address the task",
+        ));
+    }
+
+    let valid_priorities: Vec<String> = TaskPriority::known_levels()
+        .iter()
+        .map(String::from)
+        .collect();
+    let query = format!(
+        "SELECT VALUE <string> record::id(id) FROM task WHERE priority NOTINSIDE $priorities AND {ADDRESSABLE_ID}"
+    );
+    let invalid_priority_ids: Vec<String> = db
+        .client
+        .query(&query)
+        .bind(("priorities", valid_priorities))
+        .logged(&query)
+        .await
+        .map_err(|e| DoctorError {
+            kind: DoctorErrorKind::DatabaseError(Some(e.into())),
+            message: "Failed to check task priorities".to_string(),
+        })?
+        .take(0)
+        .inspect_err(log_error)
+        .map_err(|e| DoctorError {
+            kind: DoctorErrorKind::SerializationError(Some(e.into())),
+            message: "Failed to deserialize task ids".to_string(),
+        })?;
+    for id in &invalid_priority_ids {
+        issues.push(DoctorIssue::new(
+            DoctorIssueKind::InvalidPriority,
+            id,
+            "priority is not one of the known priority levels",
+        ));
+    }
+
+    for id in ids.iter().filter(|id| !invalid_priority_ids.contains(id)) {
+        let task: Result<Option<Task>, _> = db
+            .client
+            .select(("task", id.as_str()))
+            .logged(&format!("SELECT * FROM task:{id}"))
+            .await;
+        match task {
+            Err(_) => issues.push(DoctorIssue::new(
+                DoctorIssueKind::UndeserializableRecord,
+                id,
+                "record does not match the task schema",
+            )),
+            Ok(None) => (),
+            Ok(Some(task)) => {
+                if let Some(path) = task.work_note_path()
+                    && !Path::new(path).exists()
+                {
+                    issues.push(DoctorIssue::new(
+                        DoctorIssueKind::DanglingNotePath,
+                        id,
+                        format!("note file '{path}' does not exist"),
+                    ));
+                }
+                for link in task.code_links() {
+                    let status = check_link(link);
+                    if status.is_stale() {
+                        issues.push(DoctorIssue::new(
+                            DoctorIssueKind::StaleCodeLink,
+                            id,
+                            format!("code link '{link}': {status}"),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    let referenced_notes: HashSet<PathBuf> = query_values::<String>(
+        db,
+        "SELECT VALUE work_note_path FROM task WHERE type::is::string(work_note_path)",
+    )
+    .await?
+    .into_iter()
+    .map(PathBuf::from)
+    .collect();
+    for note_path in note_files(notes_dir)? {
+        if !referenced_notes.contains(&note_path) {
+            issues.push(DoctorIssue::new(
+                DoctorIssueKind::OrphanedNote,
+                note_path.display().to_string(),
+                "no task references this note",
+            ));
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Repairs the given issues in place, recording the action taken on each one.
+///
+/// Broken records and orphaned notes are never deleted outright: records are
+/// moved to the `quarantine` table and notes to `<notes dir>/quarantine`.
+pub(super) async fn repair(
+    db: &DB,
+    notes_dir: &Path,
+    issues: &mut [DoctorIssue],
+) -> Result<(), DoctorError> {
+    for issue in issues.iter_mut() {
+        let action = match issue.kind {
+            DoctorIssueKind::DanglingNotePath => {
+                run_fix_query(
+                    db,
+                    "UPDATE type::thing('task', $id) SET work_note_path = NONE",
+                    &issue.subject,
+                )
+                .await?;
+                "cleared note path"
+            }
+            DoctorIssueKind::InvalidPriority => {
+                let query = "UPDATE type::thing('task', $id) SET priority = $priority";
+                db.client
+                    .query(query)
+                    .bind(("id", issue.subject.clone()))
+                    .bind(("priority", String::from(&TaskPriority::default())))
+                    .logged(query)
+                    .await
+                    .and_then(Response::check)
+                    .inspect_err(log_error)
+                    .map_err(|e| DoctorError {
+                        kind: DoctorErrorKind::DatabaseError(Some(e.into())),
+                        message: format!("Failed to reset priority of task '{}'", issue.subject),
+                    })?;
+                "reset priority to default"
+            }
+            DoctorIssueKind::BadTaskId => {
+                // the subject is the raw id, as the record cannot be addressed by type::thing
+                run_fix_query(
+                    db,
+                    "LET $record = (SELECT * FROM ONLY task WHERE <string> id = $id LIMIT 1);
+                     CREATE quarantine CONTENT {
+                         original_id: $id,
+                         record: $record,
+                         quarantined_at: time::now()
+                     };
+                     DELETE task WHERE <string> id = $id;",
+                    &issue.subject,
+                )
+                .await?;
+                "moved record to quarantine table"
+            }
+            DoctorIssueKind::UndeserializableRecord => {
+                run_fix_query(
+                    db,
+                    "LET $record = (SELECT * FROM ONLY type::thing('task', $id));
+                     CREATE quarantine CONTENT {
+                         original_id: <string> $record.id,
+                         record: $record,
+                         quarantined_at: time::now()
+                     };
+                     DELETE type::thing('task', $id);",
+                    &issue.subject,
+                )
+                .await?;
+                "moved record to quarantine table"
+            }
+            DoctorIssueKind::OrphanedNote => {
+                quarantine_note(notes_dir, Path::new(&issue.subject))?;
+                "moved note to quarantine directory"
+            }
+            DoctorIssueKind::StaleCodeLink => {
+                refresh_code_links(db, &issue.subject).await?;
+                "refreshed code links of the task, dropping those whose lines no longer exist"
+            }
+        };
+        issue.fixed = Some(action.to_string());
+    }
+    Ok(())
+}
+
+async fn refresh_code_links(db: &DB, id: &str) -> Result<(), DoctorError> {
+    let task: Option<Task> = db
+        .client
+        .select(("task", id))
+        .logged(&format!("SELECT * FROM task:{id}"))
+        .await
+        .map_err(|e| DoctorError {
+            kind: DoctorErrorKind::DatabaseError(Some(e.into())),
+            message: format!("Failed to get task '{id}'"),
+        })?;
+    let Some(task) = task else {
+        return Ok(());
+    };
+    let code_links = task.code_links().iter().filter_map(refresh_link).collect();
+    save_links(db, id, code_links)
+        .await
+        .map_err(|e| DoctorError {
+            message: e.to_string(),
+            kind: DoctorErrorKind::DatabaseError(Some(e.into())),
+        })?;
+    Ok(())
+}
+
+fn report(issues: &[DoctorIssue], fix: bool) -> String {
+    if issues.is_empty() {
+        return "No issues found".to_string();
+    }
+    let mut lines = vec![format!("Found {} issue(s):", issues.len())];
+    lines.extend(issues.iter().map(|issue| format!("  - {issue}")));
+    if !fix {
+        lines.push("Run 'tmgr doctor --fix' to repair these issues".to_string());
+    }
+    lines.join("\n")
+}
+
+async fn query_values<T>(db: &DB, query: &str) -> Result<Vec<T>, DoctorError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    db.client
+        .query(query)
+        .logged(query)
+        .await
+        .map_err(|e| DoctorError {
+            kind: DoctorErrorKind::DatabaseError(Some(e.into())),
+            message: "Failed to scan tasks".to_string(),
+        })?
+        .take(0)
+        .inspect_err(log_error)
+        .map_err(|e| DoctorError {
+            kind: DoctorErrorKind::SerializationError(Some(e.into())),
+            message: "Failed to deserialize scan results".to_string(),
+        })
+}
+
+async fn run_fix_query(db: &DB, query: &str, id: &str) -> Result<(), DoctorError> {
+    db.client
+        .query(query)
+        .bind(("id", id.to_string()))
+        .logged(query)
+        .await
+        .and_then(Response::check)
+        .inspect_err(log_error)
+        .map_err(|e| DoctorError {
+            kind: DoctorErrorKind::DatabaseError(Some(e.into())),
+            message: format!("Failed to repair task '{id}'"),
+        })?;
+    Ok(())
+}
+
+fn note_files(notes_dir: &Path) -> Result<Vec<PathBuf>, DoctorError> {
+    if !notes_dir.exists() {
+        return Ok(vec![]);
+    }
+    let entries = read_dir(notes_dir).map_err(|e| DoctorError {
+        kind: DoctorErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn quarantine_note(notes_dir: &Path, note_path: &Path) -> Result<(), DoctorError> {
+    let quarantine_dir = notes_dir.join("quarantine");
+    create_dir_all(&quarantine_dir).map_err(|e| DoctorError {
+        kind: DoctorErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    let file_name = note_path.file_name().ok_or(DoctorError {
+        kind: DoctorErrorKind::IOError,
+        message: format!("Could not get file name of note '{}'", note_path.display()),
+    })?;
+    rename(note_path, quarantine_dir.join(file_name)).map_err(|e| DoctorError {
+        kind: DoctorErrorKind::IOError,
+        message: e.to_string(),
+    })
+}
+
+// --- Doctor Issues ---
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum DoctorIssueKind {
+    BadTaskId,
+    DanglingNotePath,
+    InvalidPriority,
+    OrphanedNote,
+    StaleCodeLink,
+    UndeserializableRecord,
+}
+
+/// A single inconsistency found by the `doctor` command.
+///
+/// The subject is the id of the affected task, or the path of the affected note
+/// file for orphaned notes.
+#[derive(Debug)]
+pub(crate) struct DoctorIssue {
+    kind: DoctorIssueKind,
+    subject: String,
+    detail: String,
+    fixed: Option<String>,
+}
+
+impl DoctorIssue {
+    fn new(kind: DoctorIssueKind, subject: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            subject: subject.into(),
+            detail: detail.into(),
+            fixed: None,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub(super) fn kind(&self) -> &DoctorIssueKind {
+        &self.kind
+    }
+
+    #[allow(dead_code)]
+    pub(super) fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    #[allow(dead_code)]
+    pub(super) fn fixed(&self) -> &Option<String> {
+        &self.fixed
+    }
+}
+
+impl fmt::Display for DoctorIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            DoctorIssueKind::OrphanedNote => {
+                write!(f, "[{}] '{}': {}", self.kind, self.subject, self.detail)?
+            }
+            _ => write!(
+                f,
+                "[{}] task '{}': {}",
+                self.kind, self.subject, self.detail
+            )?,
+        }
+        if let Some(action) = &self.fixed {
+            write!(f, " (fixed: {action})")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DoctorIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DoctorIssueKind::BadTaskId => write!(f, "bad task id"),
+            DoctorIssueKind::DanglingNotePath => write!(f, "dangling note path"),
+            DoctorIssueKind::InvalidPriority => write!(f, "invalid priority"),
+            DoctorIssueKind::OrphanedNote => write!(f, "orphaned note"),
+            DoctorIssueKind::StaleCodeLink => write!(f, "stale code link"),
+            DoctorIssueKind::UndeserializableRecord => write!(f, "undeserializable record"),
+        }
+    }
+}
+
+// --- Doctor Errors ---
+#[derive(Debug)]
+pub enum DoctorErrorKind {
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    IOError,
+    SerializationError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    UnableToDetermineNotesDirectory,
+}
+
+#[derive(Debug)]
+pub struct DoctorError {
+    kind: DoctorErrorKind,
+    message: String,
+}
+
+impl fmt::Display for DoctorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (doctor error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for DoctorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            DoctorErrorKind::DatabaseError(Some(e))
+            | DoctorErrorKind::SerializationError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DoctorErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DoctorErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            DoctorErrorKind::IOError => write!(f, "IO error"),
+            DoctorErrorKind::SerializationError(_) => write!(f, "Serialization error"),
+            DoctorErrorKind::UnableToDetermineNotesDirectory => {
+                write!(f, "Unable to determine notes directory")
+            }
+        }
+    }
+}
+
+impl ErrorCode for DoctorErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            DoctorErrorKind::DatabaseError(_) => "E-DOCTOR-001",
+            DoctorErrorKind::IOError => "E-DOCTOR-002",
+            DoctorErrorKind::SerializationError(_) => "E-DOCTOR-003",
+            DoctorErrorKind::UnableToDetermineNotesDirectory => "E-DOCTOR-004",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            DoctorErrorKind::DatabaseError(_) | DoctorErrorKind::SerializationError(_) => {
+                ErrorCategory::Internal
+            }
+            DoctorErrorKind::IOError | DoctorErrorKind::UnableToDetermineNotesDirectory => {
+                ErrorCategory::IO
+            }
+        }
+    }
+}
+
+impl From<DoctorError> for TmgrError {
+    fn from(err: DoctorError) -> Self {
+        TmgrError::new(TmgrErrorKind::DoctorCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
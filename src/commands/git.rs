@@ -0,0 +1,196 @@
+use super::super::model::log_error;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The longest slug of a task name used in a branch name.
+const MAX_SLUG_LENGTH: usize = 40;
+
+/// The shortest prefix of a task id counted as a reference to the task in a commit message.
+pub(super) const MIN_COMMIT_ID_PREFIX: usize = 6;
+
+/// The words that close the task they are followed by in a commit message.
+const CLOSING_WORDS: [&str; 9] = [
+    "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+];
+
+/// A commit of the local repository.
+#[derive(Debug, PartialEq)]
+pub(super) struct Commit {
+    pub(super) hash: String,
+    pub(super) author: String,
+    pub(super) date: String,
+    pub(super) subject: String,
+}
+
+impl fmt::Display for Commit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} ({}, {})",
+            &self.hash[..self.hash.len().min(8)],
+            self.subject,
+            self.author,
+            self.date
+        )
+    }
+}
+
+/// Runs git in `dir` and returns its trimmed output.
+pub(super) fn git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| GitError {
+            kind: GitErrorKind::FailedToRunGit,
+            message: format!("{e} - HINT: make sure git is installed"),
+        })?;
+    if !output.status.success() {
+        return Err(GitError {
+            kind: GitErrorKind::GitCommandFailed,
+            message: format!(
+                "'git {}' failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The root of the work tree holding `dir`.
+pub(super) fn repository_root(dir: &Path) -> Result<PathBuf, GitError> {
+    git(dir, &["rev-parse", "--show-toplevel"])
+        .map(PathBuf::from)
+        .inspect_err(log_error)
+        .map_err(|e| GitError {
+            kind: GitErrorKind::NotInGitRepository,
+            message: format!("'{}' is not inside a git repository: {e}", dir.display()),
+        })
+}
+
+/// The name of the branch for a task: its id followed by its name in lowercase, with
+/// anything other than letters and digits turned into dashes.
+pub(super) fn branch_name(id: &str, name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= MAX_SLUG_LENGTH {
+            break;
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        id.to_string()
+    } else {
+        format!("{id}-{slug}")
+    }
+}
+
+/// The commits of every branch whose message mentions the task id, or a prefix of it at
+/// least `min_prefix` characters long, newest first.
+pub(super) fn commits_referencing(
+    dir: &Path,
+    id: &str,
+    min_prefix: usize,
+) -> Result<Vec<Commit>, GitError> {
+    let prefix = &id[..id.len().min(min_prefix)];
+    let log = git(
+        dir,
+        &[
+            "log",
+            "--all",
+            "--regexp-ignore-case",
+            &format!("--grep={prefix}"),
+            "--date=short",
+            "--format=%H%x1f%an%x1f%ad%x1f%s%x1f%B%x1e",
+        ],
+    )?;
+    Ok(log
+        .split('\u{1e}')
+        .filter_map(|record| {
+            let fields: Vec<&str> = record.trim().splitn(5, '\u{1f}').collect();
+            let [hash, author, date, subject, body] = fields[..] else {
+                return None;
+            };
+            mentions(body, id, min_prefix).then(|| Commit {
+                hash: hash.to_string(),
+                author: author.to_string(),
+                date: date.to_string(),
+                subject: subject.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Whether a message mentions the task id, or a prefix of it at least `min_prefix`
+/// characters long, as a whole word in any case.
+pub(super) fn mentions(message: &str, id: &str, min_prefix: usize) -> bool {
+    let id = id.to_lowercase();
+    words(message)
+        .any(|word| word.len() >= min_prefix.min(id.len()) && id.starts_with(&word.to_lowercase()))
+}
+
+/// The task ids (or id prefixes) a commit message closes, as in "closes 1w08w2" or
+/// "Fixes: 1w08w2, 9x8y7z". Comment lines starting with `#` are ignored, as are words
+/// shorter than `MIN_COMMIT_ID_PREFIX`, like the "a" of "Fixes a typo".
+pub(super) fn closing_references(message: &str) -> Vec<String> {
+    let mut references = Vec::new();
+    for line in message.lines().filter(|line| !line.starts_with('#')) {
+        let mut closing = false;
+        for word in words(line) {
+            if CLOSING_WORDS.contains(&word.to_lowercase().as_str()) {
+                closing = true;
+            } else if closing {
+                if word.len() >= MIN_COMMIT_ID_PREFIX && !references.iter().any(|r| r == word) {
+                    references.push(word.to_string());
+                }
+                closing = false;
+            }
+        }
+    }
+    references
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+// --- Git Errors ---
+#[derive(Debug)]
+pub enum GitErrorKind {
+    FailedToRunGit,
+    GitCommandFailed,
+    NotInGitRepository,
+}
+
+#[derive(Debug)]
+pub struct GitError {
+    kind: GitErrorKind,
+    message: String,
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (git error: {})", self.message, self.kind)
+    }
+}
+
+impl fmt::Display for GitErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GitErrorKind::FailedToRunGit => write!(f, "Failed to run git"),
+            GitErrorKind::GitCommandFailed => write!(f, "Git command failed"),
+            GitErrorKind::NotInGitRepository => write!(f, "Not in git repository"),
+        }
+    }
+}
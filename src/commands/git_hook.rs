@@ -0,0 +1,191 @@
+use super::super::{
+    db::DB,
+    model::{CommandResult, ErrorCategory, ErrorCode, Task, TmgrError, TmgrErrorKind},
+};
+use super::{
+    complete,
+    git::{closing_references, git, repository_root},
+};
+use std::{
+    env::{current_dir, current_exe},
+    fmt,
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// The line identifying a commit-msg hook installed by tmgr.
+const HOOK_MARKER: &str = "# installed by tmgr: completes the tasks closed by the commit message";
+
+/// Installs the commit-msg hook in the repository holding the current directory.
+pub(crate) fn install(force: bool) -> Result<CommandResult<PathBuf>, GitHookError> {
+    let dir = current_dir().map_err(|e| GitHookError {
+        kind: GitHookErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    let tmgr = current_exe().map_err(|e| GitHookError {
+        kind: GitHookErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    let hook = install_in(&dir, &tmgr, force)?;
+    Ok(CommandResult::new(
+        format!(
+            "Installed the commit-msg hook at '{}', commit messages with 'closes <ID>' will complete the task",
+            hook.display()
+        ),
+        hook,
+    ))
+}
+
+/// Writes the commit-msg hook into the hooks directory of the repository holding `dir`.
+///
+/// An existing hook that was not installed by tmgr is only replaced with `force`.
+pub(super) fn install_in(dir: &Path, tmgr: &Path, force: bool) -> Result<PathBuf, GitHookError> {
+    let root = repository_root(dir).map_err(|e| GitHookError {
+        kind: GitHookErrorKind::GitError,
+        message: e.to_string(),
+    })?;
+    let hooks_dir =
+        git(&root, &["rev-parse", "--git-path", "hooks"]).map_err(|e| GitHookError {
+            kind: GitHookErrorKind::GitError,
+            message: e.to_string(),
+        })?;
+    let hooks_dir = root.join(hooks_dir);
+    let hook = hooks_dir.join("commit-msg");
+    info!("Installing the commit-msg hook at '{}'", hook.display());
+    if let Ok(existing) = read_to_string(&hook)
+        && !existing.contains(HOOK_MARKER)
+        && !force
+    {
+        return Err(GitHookError {
+            kind: GitHookErrorKind::HookAlreadyExists,
+            message: format!(
+                "A commit-msg hook already exists at '{}', use --force to replace it",
+                hook.display()
+            ),
+        });
+    }
+
+    create_dir_all(&hooks_dir).map_err(|e| GitHookError {
+        kind: GitHookErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    let script = format!(
+        "#!/bin/sh\n{HOOK_MARKER}\n'{}' git-hook commit-msg \"$1\" || true\n",
+        tmgr.display().to_string().replace('\'', r"'\''")
+    );
+    write(&hook, script).map_err(|e| GitHookError {
+        kind: GitHookErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    make_executable(&hook)?;
+    Ok(hook)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), GitHookError> {
+    use std::{fs::set_permissions, os::unix::fs::PermissionsExt};
+    set_permissions(path, PermissionsExt::from_mode(0o755)).map_err(|e| GitHookError {
+        kind: GitHookErrorKind::IOError,
+        message: e.to_string(),
+    })
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), GitHookError> {
+    Ok(())
+}
+
+/// Completes the tasks closed by the commit message in the given file.
+///
+/// This is run by the commit-msg hook. References that do not match a task are skipped
+/// rather than failing the commit.
+pub(crate) async fn commit_msg(
+    db: &DB,
+    message_file: String,
+) -> Result<CommandResult<Vec<Task>>, GitHookError> {
+    let message = read_to_string(&message_file).map_err(|e| GitHookError {
+        kind: GitHookErrorKind::IOError,
+        message: format!("Failed to read commit message '{message_file}': {e}"),
+    })?;
+
+    let mut lines = Vec::new();
+    let mut completed = Vec::new();
+    for reference in closing_references(&message) {
+        match complete::run(db, reference.clone()).await {
+            Ok(res) => {
+                lines.push(format!("tmgr: {}", res.message()));
+                completed.push(res.into_result());
+            }
+            Err(e) => lines.push(format!("tmgr: skipped '{reference}': {e}")),
+        }
+    }
+    Ok(CommandResult::new(lines.join("\n"), completed))
+}
+
+// --- Git Hook Errors ---
+#[derive(Debug)]
+pub enum GitHookErrorKind {
+    GitError,
+    HookAlreadyExists,
+    IOError,
+}
+
+#[derive(Debug)]
+pub struct GitHookError {
+    kind: GitHookErrorKind,
+    message: String,
+}
+
+impl fmt::Display for GitHookError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (git hook error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for GitHookError {}
+
+impl fmt::Display for GitHookErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GitHookErrorKind::GitError => write!(f, "Git error"),
+            GitHookErrorKind::HookAlreadyExists => write!(f, "Hook already exists"),
+            GitHookErrorKind::IOError => write!(f, "IO error"),
+        }
+    }
+}
+
+impl ErrorCode for GitHookErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            GitHookErrorKind::GitError => "E-GITHOOK-001",
+            GitHookErrorKind::HookAlreadyExists => "E-GITHOOK-002",
+            GitHookErrorKind::IOError => "E-GITHOOK-003",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            GitHookErrorKind::GitError | GitHookErrorKind::IOError => ErrorCategory::IO,
+            GitHookErrorKind::HookAlreadyExists => ErrorCategory::Validation,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            GitHookErrorKind::GitError => Some(
+                "Make sure git is installed and the current directory is inside a git repository",
+            ),
+            GitHookErrorKind::HookAlreadyExists => Some("Use --force to replace the existing hook"),
+            _ => None,
+        }
+    }
+}
+
+impl From<GitHookError> for TmgrError {
+    fn from(err: GitHookError) -> Self {
+        TmgrError::new(TmgrErrorKind::GitHookCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -0,0 +1,391 @@
+use super::super::{
+    db::{DB, DBError, Logged},
+    model::{
+        CodeLink, CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TmgrError,
+        TmgrErrorKind,
+    },
+};
+use std::{
+    collections::BTreeMap,
+    env::current_dir,
+    fmt,
+    fs::read_to_string,
+    path::{Component, Path, PathBuf},
+};
+
+/// The number of lines shown in the excerpt of a code link.
+const EXCERPT_LINES: usize = 5;
+
+pub(crate) async fn run(
+    db: &DB,
+    id: String,
+    location: String,
+) -> Result<CommandResult<Task>, LinkError> {
+    let task = db
+        .select_task_by_partial_id(&id)
+        .await
+        .map_err(|e| LinkError {
+            message: e.to_string(),
+            kind: LinkErrorKind::TaskLookup(e),
+        })?;
+    let task_id = task.id().map_err(|e| LinkError {
+        kind: LinkErrorKind::BadTaskId,
+        message: e.to_string(),
+    })?;
+
+    let link = create_link(&location)?;
+    if task.code_links().iter().any(|l| {
+        l.root() == link.root()
+            && l.path() == link.path()
+            && l.start() == link.start()
+            && l.end() == link.end()
+    }) {
+        return Err(LinkError {
+            kind: LinkErrorKind::AlreadyLinked,
+            message: format!("'{link}' is already linked to task '{task_id}'"),
+        });
+    }
+    let message = format!("Successfully linked '{link}' to task '{task_id}'");
+
+    let mut code_links = task.code_links().clone();
+    code_links.push(link);
+    let updated_task = save_links(db, &task_id, code_links).await?;
+    Ok(CommandResult::new(message, updated_task))
+}
+
+/// Replaces the code links of a task.
+pub(super) async fn save_links(
+    db: &DB,
+    task_id: &str,
+    code_links: Vec<CodeLink>,
+) -> Result<Task, LinkError> {
+    let update: BTreeMap<&str, Vec<CodeLink>> = BTreeMap::from([("code_links", code_links)]);
+    db.client
+        .update(("task", task_id))
+        .merge(update)
+        .logged(&format!("UPDATE task:{task_id} MERGE $data"))
+        .await
+        .map_err(|e| LinkError {
+            kind: LinkErrorKind::DatabaseError(Some(e.into())),
+            message: "Failed to update task".to_string(),
+        })?
+        .ok_or_else(|| LinkError {
+            kind: LinkErrorKind::DatabaseError(None),
+            message: "Failed to update task".to_string(),
+        })
+}
+
+/// Creates a code link from a `path:line` or `path:start-end` location, resolving the path
+/// from the current directory.
+pub(super) fn create_link(location: &str) -> Result<CodeLink, LinkError> {
+    let (file, start, end) = parse_location(location)?;
+    let (root, path) = resolve(Path::new(&file))?;
+    let content = read_to_string(root.join(&path)).map_err(|e| LinkError {
+        kind: LinkErrorKind::IOError,
+        message: format!("Failed to read '{file}': {e}"),
+    })?;
+    let lines: Vec<&str> = content.lines().collect();
+    if end > lines.len() {
+        return Err(LinkError {
+            kind: LinkErrorKind::InvalidLocation,
+            message: format!("'{file}' only has {} lines", lines.len()),
+        });
+    }
+    Ok(CodeLink::new(
+        root.to_string_lossy().to_string(),
+        path,
+        start,
+        end,
+        lines[start - 1..end]
+            .iter()
+            .map(|l| l.to_string())
+            .collect(),
+    ))
+}
+
+/// Splits a `path:line` or `path:start-end` location into its path and line range.
+pub(super) fn parse_location(location: &str) -> Result<(String, usize, usize), LinkError> {
+    let invalid = |reason: &str| LinkError {
+        kind: LinkErrorKind::InvalidLocation,
+        message: format!(
+            "Invalid location '{location}': {reason}, expected 'path:line' or 'path:start-end'"
+        ),
+    };
+    let (file, lines) = location
+        .rsplit_once(':')
+        .filter(|(file, _)| !file.is_empty())
+        .ok_or_else(|| invalid("missing line number"))?;
+    let (start, end) = lines.split_once('-').unwrap_or((lines, lines));
+    let start: usize = start
+        .parse()
+        .map_err(|_| invalid("line numbers must be positive integers"))?;
+    let end: usize = end
+        .parse()
+        .map_err(|_| invalid("line numbers must be positive integers"))?;
+    if start == 0 || end < start {
+        return Err(invalid(
+            "lines start at 1 and the range must not be reversed",
+        ));
+    }
+    Ok((file.to_string(), start, end))
+}
+
+/// Finds the git repository holding a file, returning its root and the path of the file
+/// relative to it, with `/` separators.
+pub(super) fn resolve(file: &Path) -> Result<(PathBuf, String), LinkError> {
+    let absolute = current_dir()
+        .map(|dir| dir.join(file))
+        .and_then(|path| path.canonicalize())
+        .map_err(|e| LinkError {
+            kind: LinkErrorKind::FileNotFound,
+            message: format!("Unable to find '{}': {e}", file.display()),
+        })?;
+    let root = find_git_root(&absolute).ok_or_else(|| LinkError {
+        kind: LinkErrorKind::NotInGitRepository,
+        message: format!("'{}' is not inside a git repository", file.display()),
+    })?;
+    let relative = absolute
+        .strip_prefix(&root)
+        .unwrap_or(&absolute)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<String>>()
+        .join("/");
+    Ok((root, relative))
+}
+
+/// The closest directory above `path` holding a `.git` directory or file.
+pub(super) fn find_git_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Whether a code link points at the given file, given as a path from the current
+/// directory or as a path relative to the git root.
+pub(super) fn links_to_file(link: &CodeLink, file: &str) -> bool {
+    match resolve(Path::new(file)) {
+        Ok((root, path)) => link.root() == root.to_string_lossy() && link.path() == path,
+        Err(_) => link.path() == file.trim_start_matches("./"),
+    }
+}
+
+/// The state of the lines referenced by a code link.
+#[derive(Debug, PartialEq)]
+pub(super) enum LinkStatus {
+    Current,
+    /// The linked lines are unchanged, but now start at the given line.
+    Moved(usize),
+    Changed,
+    MissingLines,
+    MissingFile,
+    /// The link itself is broken: it starts at line 0, ends before it starts, or has no lines.
+    Invalid,
+}
+
+impl LinkStatus {
+    pub(super) fn is_stale(&self) -> bool {
+        *self != LinkStatus::Current
+    }
+}
+
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkStatus::Current => write!(f, "up to date"),
+            LinkStatus::Moved(start) => write!(f, "lines moved to line {start}"),
+            LinkStatus::Changed => write!(f, "lines changed"),
+            LinkStatus::MissingLines => write!(f, "lines no longer exist"),
+            LinkStatus::MissingFile => write!(f, "file no longer exists"),
+            LinkStatus::Invalid => write!(f, "invalid link"),
+        }
+    }
+}
+
+/// Compares the linked lines with the current content of the file.
+pub(super) fn check_link(link: &CodeLink) -> LinkStatus {
+    match read_to_string(Path::new(link.root()).join(link.path())) {
+        Ok(content) => check_content(link, &content),
+        Err(_) => LinkStatus::MissingFile,
+    }
+}
+
+/// Compares the linked lines with the given file content.
+pub(super) fn check_content(link: &CodeLink, content: &str) -> LinkStatus {
+    let lines: Vec<&str> = content.lines().collect();
+    let linked = link.lines();
+    if link.start() == 0 || link.end() < link.start() || linked.is_empty() {
+        return LinkStatus::Invalid;
+    }
+    if lines
+        .get(link.start() - 1..link.end())
+        .is_some_and(|current| same_lines(current, linked))
+    {
+        return LinkStatus::Current;
+    }
+    let moved = lines
+        .windows(linked.len())
+        .position(|window| same_lines(window, linked));
+    match moved {
+        Some(index) => LinkStatus::Moved(index + 1),
+        None if link.end() > lines.len() => LinkStatus::MissingLines,
+        None => LinkStatus::Changed,
+    }
+}
+
+/// Brings a stale code link up to date: moved lines are followed, and changed lines are
+/// linked as they are now. Returns `None` if the lines or the file no longer exist.
+pub(super) fn refresh_link(link: &CodeLink) -> Option<CodeLink> {
+    let content = read_to_string(Path::new(link.root()).join(link.path())).ok()?;
+    let (start, end) = match check_content(link, &content) {
+        LinkStatus::Current | LinkStatus::Changed => (link.start(), link.end()),
+        LinkStatus::Moved(start) => (start, start + link.end() - link.start()),
+        LinkStatus::MissingLines | LinkStatus::MissingFile | LinkStatus::Invalid => return None,
+    };
+    let lines = content
+        .lines()
+        .skip(start - 1)
+        .take(end - start + 1)
+        .map(str::to_string)
+        .collect();
+    Some(CodeLink::new(
+        link.root().to_string(),
+        link.path().to_string(),
+        start,
+        end,
+        lines,
+    ))
+}
+
+fn same_lines(current: &[&str], linked: &[String]) -> bool {
+    current.len() == linked.len() && current.iter().zip(linked).all(|(a, b)| a == b)
+}
+
+/// A code link followed by an excerpt of the current content of its lines, and its status
+/// if it is stale.
+pub(super) fn excerpt(link: &CodeLink) -> String {
+    let status = check_link(link);
+    let mut out = if status.is_stale() {
+        format!("{link} ({status})")
+    } else {
+        link.to_string()
+    };
+    let content = read_to_string(Path::new(link.root()).join(link.path())).unwrap_or_default();
+    let lines: Vec<&str> = content.lines().collect();
+    let end = link.end().min(lines.len());
+    let shown = (link.start()..=end).take(EXCERPT_LINES);
+    let width = end.to_string().len();
+    for number in shown {
+        out.push_str(&format!("\n  {number:>width$} | {}", lines[number - 1]));
+    }
+    if end >= link.start() && end - link.start() + 1 > EXCERPT_LINES {
+        out.push_str(&format!(
+            "\n  ... ({} more lines)",
+            end - link.start() + 1 - EXCERPT_LINES
+        ));
+    }
+    out
+}
+
+// --- Link Errors ---
+#[derive(Debug)]
+pub enum LinkErrorKind {
+    AlreadyLinked,
+    BadTaskId,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    FileNotFound,
+    InvalidLocation,
+    IOError,
+    NotInGitRepository,
+    TaskLookup(DBError),
+}
+
+#[derive(Debug)]
+pub struct LinkError {
+    kind: LinkErrorKind,
+    message: String,
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (link error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for LinkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            LinkErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            LinkErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LinkErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkErrorKind::AlreadyLinked => write!(f, "Already linked"),
+            LinkErrorKind::BadTaskId => write!(f, "Bad task id"),
+            LinkErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            LinkErrorKind::FileNotFound => write!(f, "File not found"),
+            LinkErrorKind::InvalidLocation => write!(f, "Invalid location"),
+            LinkErrorKind::IOError => write!(f, "IO error"),
+            LinkErrorKind::NotInGitRepository => write!(f, "Not in git repository"),
+            LinkErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+        }
+    }
+}
+
+impl ErrorCode for LinkErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            LinkErrorKind::AlreadyLinked => "E-LINK-001",
+            LinkErrorKind::BadTaskId => "E-LINK-002",
+            LinkErrorKind::DatabaseError(_) => "E-LINK-003",
+            LinkErrorKind::FileNotFound => "E-LINK-004",
+            LinkErrorKind::InvalidLocation => "E-LINK-005",
+            LinkErrorKind::IOError => "E-LINK-006",
+            LinkErrorKind::NotInGitRepository => "E-LINK-007",
+            LinkErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            LinkErrorKind::AlreadyLinked
+            | LinkErrorKind::InvalidLocation
+            | LinkErrorKind::NotInGitRepository => ErrorCategory::Validation,
+            LinkErrorKind::BadTaskId | LinkErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+            LinkErrorKind::FileNotFound => ErrorCategory::NotFound,
+            LinkErrorKind::IOError => ErrorCategory::IO,
+            LinkErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            LinkErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            LinkErrorKind::FileNotFound => {
+                Some("Check the path, relative paths are resolved from the current directory")
+            }
+            LinkErrorKind::InvalidLocation => {
+                Some("Give the location as path:line or path:start-end")
+            }
+            LinkErrorKind::NotInGitRepository => Some("Link files inside a git repository"),
+            LinkErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
+        }
+    }
+}
+
+impl From<LinkError> for TmgrError {
+    fn from(err: LinkError) -> Self {
+        TmgrError::new(TmgrErrorKind::LinkCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
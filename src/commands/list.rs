@@ -1,57 +1,192 @@
 use super::super::{
-    db::DB,
-    model::{CommandResult, TableRow, Task, TmgrError, TmgrErrorKind},
+    cli::model::{ListFilter, ListSort, OutputFormat},
+    config::Config,
+    db::{DB, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, DOCTOR_HINT, ErrorCategory, ErrorCode, TableRow, Task,
+        TaskPriority, TmgrError, TmgrErrorKind, log_error,
+    },
 };
-use comfy_table::{ContentArrangement::Dynamic, Table};
+use super::link::links_to_file;
+use comfy_table::{Attribute, Cell, Color, ContentArrangement::Dynamic, Table};
 use std::fmt;
 
-pub(crate) async fn run(db: &DB, all: bool) -> Result<CommandResult<Vec<Task>>, ListError> {
-    let tasks: Vec<Task> = if all {
-        db.client.select("task").await.map_err(|_| ListError {
-            kind: ListErrorKind::DatabaseError,
-            message: "Failed to get all tasks".to_string(),
-        })?
+/// Lists the tasks matching `filter`, open tasks only unless it includes all tasks or
+/// states.
+///
+/// The message renders the tasks in the `output` format, with the given table columns.
+pub async fn run(
+    db: &DB,
+    filter: ListFilter,
+    columns: Option<Vec<String>>,
+    output: OutputFormat,
+) -> Result<CommandResult<Vec<Task>>, ListError> {
+    let ListFilter {
+        all,
+        sort,
+        state: states,
+        priority: priorities,
+        file,
+    } = filter;
+    // filtering by state may include completed tasks, so all tasks are fetched
+    let mut tasks: Vec<Task> = if all || !states.is_empty() {
+        db.client
+            .select("task")
+            .logged("SELECT * FROM task")
+            .await
+            .map_err(|e| ListError {
+                kind: ListErrorKind::DatabaseError(Some(e.into())),
+                message: "Failed to get all tasks".to_string(),
+            })?
     } else {
         let query = "SELECT * FROM task WHERE completed_at IS None";
         db.client
             .query(query)
+            .logged(query)
             .await
-            .map_err(|_| ListError {
-                kind: ListErrorKind::DatabaseError,
+            .map_err(|e| ListError {
+                kind: ListErrorKind::DatabaseError(Some(e.into())),
                 message: "Failed to get in progress tasks".to_string(),
             })?
             .take(0)
-            .map_err(|_| ListError {
-                kind: ListErrorKind::SerializationError,
+            .inspect_err(log_error)
+            .map_err(|e| ListError {
+                kind: ListErrorKind::SerializationError(Some(e.into())),
                 message: "Failed to serialize tasks".to_string(),
             })?
     };
+    if !states.is_empty() {
+        tasks.retain(|t| states.contains(&t.state()));
+    }
+    if !priorities.is_empty() {
+        tasks.retain(|t| priorities.contains(t.priority()));
+    }
+    if let Some(file) = file {
+        tasks.retain(|t| t.code_links().iter().any(|l| links_to_file(l, &file)));
+    }
+    sort_tasks(&mut tasks, &sort.unwrap_or_default());
 
-    let mut table = Table::new();
-    let headers = vec![
-        "id".to_string(),
-        "name".to_string(),
-        "priority".to_string(),
-        "description".to_string(),
-        "created_at".to_string(),
-        "completed_at".to_string(),
-    ];
-    table.set_content_arrangement(Dynamic).set_header(&headers);
-
-    tasks.iter().for_each(|t| {
-        let row = t.to_table_rows_filtered(&headers);
-        let vals: Vec<String> = row.iter().map(|(_, v)| v.to_string()).collect();
-        table.add_row(vals);
+    let headers = columns.unwrap_or_else(|| {
+        DEFAULT_COLUMNS
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>()
     });
+    if let Some(column) = headers.iter().find(|c| !Task::FIELDS.contains(&c.as_str())) {
+        return Err(ListError {
+            kind: ListErrorKind::UnknownColumn,
+            message: format!(
+                "'{column}' is not a task field, expected one of: {}",
+                Task::FIELDS.join(", ")
+            ),
+        });
+    }
+
+    let message = match output {
+        OutputFormat::Table => {
+            let mut table = styled_table();
+            table.set_content_arrangement(Dynamic).set_header(&headers);
+            tasks.iter().for_each(|t| {
+                let row = t.to_table_rows_filtered(&headers);
+                let cells: Vec<Cell> = row
+                    .into_iter()
+                    .map(|(k, v)| match k.as_str() {
+                        "priority" => priority_cell(t.priority()),
+                        _ => Cell::new(v),
+                    })
+                    .collect();
+                table.add_row(cells);
+            });
+            table.to_string()
+        }
+        OutputFormat::Json => {
+            let rows: Vec<serde_json::Value> = tasks
+                .iter()
+                .map(|t| json_object(t.to_table_rows_filtered(&headers)))
+                .collect();
+            serde_json::to_string_pretty(&rows).unwrap_or_default()
+        }
+    };
+
+    Ok(CommandResult::new(message, tasks))
+}
+
+/// The task fields listed when no columns are configured.
+const DEFAULT_COLUMNS: [&str; 7] = [
+    "id",
+    "name",
+    "priority",
+    "state",
+    "description",
+    "created_at",
+    "completed_at",
+];
+
+/// A table that is colored unless colors are turned off in the config file.
+pub(super) fn styled_table() -> Table {
+    let mut table = Table::new();
+    match Config::active().color() {
+        Some(true) => {
+            table.enforce_styling();
+        }
+        Some(false) => {
+            table.force_no_tty();
+        }
+        None => {}
+    }
+    table
+}
+
+/// The fields of a task as a JSON object, keyed by field name.
+pub(super) fn json_object(row: Vec<(String, String)>) -> serde_json::Value {
+    serde_json::Value::Object(
+        row.into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect(),
+    )
+}
+
+/// Sorts tasks in place. Ties are broken by creation time, oldest first.
+pub(super) fn sort_tasks(tasks: &mut [Task], sort: &ListSort) {
+    match sort {
+        ListSort::Priority => tasks.sort_by(|a, b| {
+            b.priority()
+                .cmp(a.priority())
+                .then_with(|| a.created_at().cmp(b.created_at()))
+        }),
+        ListSort::Created => tasks.sort_by(|a, b| a.created_at().cmp(b.created_at())),
+        ListSort::Name => tasks.sort_by(|a, b| {
+            a.name()
+                .to_lowercase()
+                .cmp(&b.name().to_lowercase())
+                .then_with(|| a.created_at().cmp(b.created_at()))
+        }),
+    }
+}
 
-    Ok(CommandResult::new(table.to_string(), tasks))
+/// A table cell for the priority, colored by how urgent the priority is.
+fn priority_cell(priority: &TaskPriority) -> Cell {
+    let cell = Cell::new(priority.to_string());
+    let weight = priority.weight();
+    if weight >= TaskPriority::Critical.weight() {
+        cell.fg(Color::Red).add_attribute(Attribute::Bold)
+    } else if weight >= TaskPriority::High.weight() {
+        cell.fg(Color::Red)
+    } else if weight >= TaskPriority::Medium.weight() {
+        cell.fg(Color::Yellow)
+    } else if weight >= TaskPriority::Low.weight() {
+        cell.fg(Color::Green)
+    } else {
+        cell.fg(Color::DarkGrey)
+    }
 }
 
 // --- ListError ---
 #[derive(Debug)]
 pub enum ListErrorKind {
-    DatabaseError,
-    SerializationError,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    SerializationError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    UnknownColumn,
 }
 
 #[derive(Debug)]
@@ -60,17 +195,64 @@ pub struct ListError {
     message: String,
 }
 
+impl ListError {
+    pub fn kind(&self) -> &ListErrorKind {
+        &self.kind
+    }
+}
+
 impl fmt::Display for ListError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} (list error: {})", self.message, self.kind)
     }
 }
 
+impl std::error::Error for ListError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ListErrorKind::DatabaseError(Some(e)) | ListErrorKind::SerializationError(Some(e)) => {
+                Some(e.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for ListErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ListErrorKind::DatabaseError => write!(f, "Database error"),
-            ListErrorKind::SerializationError => write!(f, "Serialization error"),
+            ListErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            ListErrorKind::SerializationError(_) => write!(f, "Serialization error"),
+            ListErrorKind::UnknownColumn => write!(f, "Unknown column"),
+        }
+    }
+}
+
+impl ErrorCode for ListErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ListErrorKind::DatabaseError(_) => "E-LIST-001",
+            ListErrorKind::SerializationError(_) => "E-LIST-002",
+            ListErrorKind::UnknownColumn => "E-LIST-003",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ListErrorKind::DatabaseError(_) | ListErrorKind::SerializationError(_) => {
+                ErrorCategory::Internal
+            }
+            ListErrorKind::UnknownColumn => ErrorCategory::Validation,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            ListErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            ListErrorKind::SerializationError(_) => Some(DOCTOR_HINT),
+            ListErrorKind::UnknownColumn => {
+                Some("Run 'tmgr list --help' to see the available columns")
+            }
         }
     }
 }
@@ -78,5 +260,7 @@ impl fmt::Display for ListErrorKind {
 impl From<ListError> for TmgrError {
     fn from(err: ListError) -> TmgrError {
         TmgrError::new(TmgrErrorKind::ListCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
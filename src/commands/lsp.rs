@@ -0,0 +1,579 @@
+use super::super::{
+    db::{DB, DBErrorKind, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TmgrError, TmgrErrorKind,
+    },
+};
+use super::{
+    completions,
+    link::find_git_root,
+    scan::{self, file_comments},
+};
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+use tower_lsp::{
+    Client, LanguageServer, LspService, Server, jsonrpc,
+    lsp_types::{
+        CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+        CodeActionProviderCapability, CodeActionResponse, Command, Diagnostic, DiagnosticSeverity,
+        DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+        DidSaveTextDocumentParams, ExecuteCommandOptions, ExecuteCommandParams, Hover,
+        HoverContents, HoverParams, HoverProviderCapability, InitializeParams, InitializeResult,
+        InitializedParams, Location, MarkupContent, MarkupKind, MessageType, OneOf, Position,
+        Range, ServerCapabilities, ServerInfo, SymbolInformation, SymbolKind,
+        TextDocumentSyncCapability, TextDocumentSyncKind, Url, WorkspaceSymbolParams,
+    },
+};
+
+/// The command the code actions run to create a task from a comment.
+pub(super) const CREATE_TASK_COMMAND: &str = "tmgr.createTask";
+
+/// How tasks are referenced in documents, as in `// see tmgr:1w08w2`.
+const REFERENCE_PREFIX: &str = "tmgr:";
+
+/// The shortest partial id read as a reference, so that `tmgr:a` is not looked up.
+const MIN_REFERENCE_LENGTH: usize = 4;
+
+/// Runs the language server on stdin and stdout until the editor exits it.
+pub(crate) async fn run(db: &DB) -> Result<CommandResult<String>, LspError> {
+    let (service, socket) = LspService::new(|client| Backend {
+        client,
+        db: db.clone(),
+        documents: RwLock::default(),
+    });
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
+        .serve(service)
+        .await;
+    // stdout belongs to the protocol, so there is nothing to print
+    Ok(CommandResult::new(String::new(), String::new()))
+}
+
+struct Backend {
+    client: Client,
+    db: DB,
+    /// The content of the open documents, which can differ from the files until saved
+    documents: RwLock<HashMap<Url, String>>,
+}
+
+impl Backend {
+    fn text(&self, uri: &Url) -> Option<String> {
+        let open = self
+            .documents
+            .read()
+            .ok()
+            .and_then(|documents| documents.get(uri).cloned());
+        open.or_else(|| {
+            uri.to_file_path()
+                .ok()
+                .and_then(|path| read_to_string(path).ok())
+        })
+    }
+
+    fn set_text(&self, uri: Url, text: Option<String>) {
+        if let Ok(mut documents) = self.documents.write() {
+            match text {
+                Some(text) => documents.insert(uri, text),
+                None => documents.remove(&uri),
+            };
+        }
+    }
+
+    async fn publish_diagnostics(&self, uri: Url) {
+        let diagnostics = match self.text(&uri) {
+            Some(text) => diagnostics(&self.db, &text).await,
+            None => Vec::new(),
+        };
+        self.client
+            .publish_diagnostics(uri, diagnostics, None)
+            .await;
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _: InitializeParams) -> jsonrpc::Result<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::FULL,
+                )),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![CREATE_TASK_COMMAND.to_string()],
+                    ..Default::default()
+                }),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: "tmgr".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        self.client
+            .log_message(MessageType::INFO, "tmgr language server started")
+            .await;
+    }
+
+    async fn shutdown(&self) -> jsonrpc::Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.set_text(uri.clone(), Some(params.text_document.text));
+        self.publish_diagnostics(uri).await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        let uri = params.text_document.uri;
+        // the whole document is sent on every change
+        if let Some(change) = params.content_changes.into_iter().last() {
+            self.set_text(uri.clone(), Some(change.text));
+        }
+        self.publish_diagnostics(uri).await;
+    }
+
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        // the referenced tasks may have changed since the document was opened
+        self.publish_diagnostics(params.text_document.uri).await;
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.set_text(uri.clone(), None);
+        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+    }
+
+    async fn hover(&self, params: HoverParams) -> jsonrpc::Result<Option<Hover>> {
+        let position = params.text_document_position_params;
+        let Some(text) = self.text(&position.text_document.uri) else {
+            return Ok(None);
+        };
+        Ok(hover(&self.db, &text, position.position).await)
+    }
+
+    async fn code_action(
+        &self,
+        params: CodeActionParams,
+    ) -> jsonrpc::Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let (Some(text), Ok(path)) = (self.text(&uri), uri.to_file_path()) else {
+            return Ok(None);
+        };
+        let actions = code_actions(&self.db, &uri, &path, &text, params.range)
+            .await
+            .map_err(jsonrpc::Error::from)?;
+        Ok(Some(actions))
+    }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> jsonrpc::Result<Option<Value>> {
+        let (uri, line) = command_arguments(&params).map_err(jsonrpc::Error::from)?;
+        let path = uri.to_file_path().map_err(|_| LspError {
+            kind: LspErrorKind::InvalidArguments,
+            message: format!("'{uri}' is not a file"),
+        })?;
+        let text = self.text(&uri).ok_or_else(|| LspError {
+            kind: LspErrorKind::InvalidArguments,
+            message: format!("Unable to read '{}'", path.display()),
+        })?;
+        let task = create_task(&self.db, &path, &text, line).await?;
+        let id = task.id().unwrap_or_default();
+        self.client
+            .show_message(
+                MessageType::INFO,
+                format!("Created task '{id}': {}", task.name()),
+            )
+            .await;
+        Ok(Some(json!({ "id": id })))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> jsonrpc::Result<Option<Vec<SymbolInformation>>> {
+        let symbols = workspace_symbols(&self.db, &params.query).await?;
+        Ok(Some(symbols))
+    }
+}
+
+/// A task referenced in a document, as `tmgr:<ID>`.
+#[derive(Debug, PartialEq)]
+pub(super) struct TaskReference {
+    pub(super) id: String,
+    pub(super) range: Range,
+}
+
+/// Finds the `tmgr:<ID>` references in a document.
+pub(super) fn find_references(text: &str) -> Vec<TaskReference> {
+    let mut references = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        for (start, _) in line.match_indices(REFERENCE_PREFIX) {
+            if line[..start]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_')
+            {
+                continue;
+            }
+            let id_start = start + REFERENCE_PREFIX.len();
+            let id: String = line[id_start..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .collect();
+            if id.len() < MIN_REFERENCE_LENGTH {
+                continue;
+            }
+            let line_number = line_number as u32;
+            references.push(TaskReference {
+                range: Range::new(
+                    Position::new(line_number, utf16_length(&line[..start])),
+                    Position::new(line_number, utf16_length(&line[..id_start + id.len()])),
+                ),
+                id,
+            });
+        }
+    }
+    references
+}
+
+/// The length of a string in UTF-16 code units, which positions are counted in.
+fn utf16_length(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+/// The reference under a position, if any.
+pub(super) fn reference_at(text: &str, position: Position) -> Option<TaskReference> {
+    find_references(text).into_iter().find(|r| {
+        r.range.start.line == position.line
+            && r.range.start.character <= position.character
+            && position.character <= r.range.end.character
+    })
+}
+
+/// Flags the references to tasks that are done, cancelled, deleted or ambiguous.
+pub(super) async fn diagnostics(db: &DB, text: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for reference in find_references(text) {
+        let id = &reference.id;
+        let (severity, message) = match db.select_task_by_partial_id(id).await {
+            Ok(task) if task.state().is_closed() => (
+                DiagnosticSeverity::INFORMATION,
+                format!("Task '{id}' is {}: {}", task.state(), task.name()),
+            ),
+            Ok(_) => continue,
+            Err(e) => match e.kind() {
+                DBErrorKind::NoTasksFound => (
+                    DiagnosticSeverity::WARNING,
+                    format!("Task '{id}' does not exist, it may have been deleted"),
+                ),
+                DBErrorKind::MultipleTasksFound => (
+                    DiagnosticSeverity::WARNING,
+                    format!("'{id}' matches several tasks, add characters of the id"),
+                ),
+                _ => continue,
+            },
+        };
+        diagnostics.push(Diagnostic {
+            range: reference.range,
+            severity: Some(severity),
+            source: Some("tmgr".to_string()),
+            message,
+            ..Default::default()
+        });
+    }
+    diagnostics
+}
+
+/// Describes the task referenced under a position.
+pub(super) async fn hover(db: &DB, text: &str, position: Position) -> Option<Hover> {
+    let reference = reference_at(text, position)?;
+    let task = db.select_task_by_partial_id(&reference.id).await.ok()?;
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: hover_text(&task),
+        }),
+        range: Some(reference.range),
+    })
+}
+
+/// The name, id, state and priority of a task, then its description and note, in markdown.
+pub(super) fn hover_text(task: &Task) -> String {
+    let mut text = format!(
+        "**{}**\n\n`{}` · {} · {} priority",
+        task.name(),
+        task.id().unwrap_or_default(),
+        task.state(),
+        task.priority()
+    );
+    if let Some(description) = task.description() {
+        text.push_str(&format!("\n\n{description}"));
+    }
+    if let Some(note) = task.work_note_path() {
+        text.push_str(&format!("\n\nNote: {note}"));
+    }
+    text
+}
+
+/// Offers to create a task from each comment in a range that no task was created from yet.
+pub(super) async fn code_actions(
+    db: &DB,
+    uri: &Url,
+    path: &Path,
+    text: &str,
+    range: Range,
+) -> Result<Vec<CodeActionOrCommand>, LspError> {
+    let lines = range.start.line as usize + 1..=range.end.line as usize + 1;
+    let mut actions = Vec::new();
+    for (hash, comment, _) in file_comments(&comment_root(path), path, text) {
+        if !lines.contains(&comment.line) || tracking_task(db, &hash).await?.is_some() {
+            continue;
+        }
+        let title = format!("Create a tmgr task from this {}", comment.marker);
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.clone(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            command: Some(Command {
+                title,
+                command: CREATE_TASK_COMMAND.to_string(),
+                arguments: Some(vec![json!(uri), json!(comment.line)]),
+            }),
+            ..Default::default()
+        }));
+    }
+    Ok(actions)
+}
+
+/// The document and line given to the create task command.
+fn command_arguments(params: &ExecuteCommandParams) -> Result<(Url, usize), LspError> {
+    if params.command != CREATE_TASK_COMMAND {
+        return Err(LspError {
+            kind: LspErrorKind::InvalidArguments,
+            message: format!("Unknown command '{}'", params.command),
+        });
+    }
+    match params.arguments.as_slice() {
+        [uri, line] => {
+            let uri = serde_json::from_value(uri.clone()).ok();
+            let line = line.as_u64().map(|l| l as usize);
+            uri.zip(line)
+        }
+        _ => None,
+    }
+    .ok_or_else(|| LspError {
+        kind: LspErrorKind::InvalidArguments,
+        message: format!("{CREATE_TASK_COMMAND} expects a document and a line"),
+    })
+}
+
+/// Creates the task of the comment on a line, as `tmgr scan` would, so that scanning does not
+/// create it again.
+pub(super) async fn create_task(
+    db: &DB,
+    path: &Path,
+    text: &str,
+    line: usize,
+) -> Result<Task, LspError> {
+    let (hash, comment, link) = file_comments(&comment_root(path), path, text)
+        .into_iter()
+        .find(|(_, comment, _)| comment.line == line)
+        .ok_or_else(|| LspError {
+            kind: LspErrorKind::NoComment,
+            message: format!("There is no TODO, FIXME or HACK comment on line {line}"),
+        })?;
+    if let Some(task) = tracking_task(db, &hash).await? {
+        return Err(LspError {
+            kind: LspErrorKind::AlreadyTracked,
+            message: format!(
+                "Task '{}' was already created from this comment",
+                task.id().unwrap_or_default()
+            ),
+        });
+    }
+    scan::create_task(db, &hash, &comment, &link)
+        .await
+        .map_err(|e| LspError {
+            message: e.to_string(),
+            kind: LspErrorKind::DatabaseError(Some(e.into())),
+        })
+}
+
+/// The directory comments are linked relative to: the git repository of the file, as for
+/// `tmgr scan`, or its directory.
+fn comment_root(path: &Path) -> PathBuf {
+    find_git_root(path)
+        .or(path.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+}
+
+async fn tracking_task(db: &DB, hash: &str) -> Result<Option<Task>, LspError> {
+    let query = "SELECT * FROM task WHERE comment_hash = $hash";
+    let tasks: Vec<Task> = db
+        .client
+        .query(query)
+        .bind(("hash", hash.to_string()))
+        .logged(query)
+        .await
+        .map_err(|e| LspError {
+            message: e.to_string(),
+            kind: LspErrorKind::DatabaseError(Some(e.into())),
+        })?
+        .take(0)
+        .map_err(|e| LspError {
+            message: e.to_string(),
+            kind: LspErrorKind::DatabaseError(Some(e.into())),
+        })?;
+    Ok(tasks.into_iter().next())
+}
+
+/// The in-progress tasks whose name contains the query or whose id starts with it, located at
+/// their first code link, or at their note.
+#[allow(deprecated)]
+pub(super) async fn workspace_symbols(
+    db: &DB,
+    query: &str,
+) -> Result<Vec<SymbolInformation>, LspError> {
+    let tasks = completions::ids(db, None).await.map_err(|e| LspError {
+        message: e.to_string(),
+        kind: LspErrorKind::DatabaseError(Some(e.into())),
+    })?;
+    let query = query.to_lowercase();
+    Ok(tasks
+        .result()
+        .iter()
+        .filter(|t| {
+            t.name().to_lowercase().contains(&query)
+                || t.id().is_ok_and(|id| id.starts_with(&query))
+        })
+        .filter_map(|t| {
+            Some(SymbolInformation {
+                name: t.name().to_string(),
+                kind: SymbolKind::EVENT,
+                tags: None,
+                deprecated: None,
+                location: task_location(t)?,
+                container_name: Some(format!("{REFERENCE_PREFIX}{}", t.id().ok()?)),
+            })
+        })
+        .collect())
+}
+
+fn task_location(task: &Task) -> Option<Location> {
+    if let Some(link) = task.code_links().first() {
+        let uri = Url::from_file_path(Path::new(link.root()).join(link.path())).ok()?;
+        let range = Range::new(
+            Position::new(link.start().saturating_sub(1) as u32, 0),
+            Position::new(link.end().saturating_sub(1) as u32, 0),
+        );
+        return Some(Location::new(uri, range));
+    }
+    let note = task.work_note_path().as_ref()?;
+    let uri = Url::from_file_path(note).ok()?;
+    Some(Location::new(uri, Range::default()))
+}
+
+// --- Lsp Errors ---
+#[derive(Debug)]
+pub enum LspErrorKind {
+    AlreadyTracked,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    InvalidArguments,
+    NoComment,
+}
+
+#[derive(Debug)]
+pub struct LspError {
+    kind: LspErrorKind,
+    message: String,
+}
+
+impl fmt::Display for LspError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (lsp error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for LspError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            LspErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LspErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LspErrorKind::AlreadyTracked => write!(f, "Already tracked"),
+            LspErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            LspErrorKind::InvalidArguments => write!(f, "Invalid arguments"),
+            LspErrorKind::NoComment => write!(f, "No comment"),
+        }
+    }
+}
+
+impl ErrorCode for LspErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            LspErrorKind::AlreadyTracked => "E-LSP-001",
+            LspErrorKind::DatabaseError(_) => "E-LSP-002",
+            LspErrorKind::InvalidArguments => "E-LSP-003",
+            LspErrorKind::NoComment => "E-LSP-004",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            LspErrorKind::AlreadyTracked
+            | LspErrorKind::InvalidArguments
+            | LspErrorKind::NoComment => ErrorCategory::Validation,
+            LspErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            LspErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            _ => None,
+        }
+    }
+}
+
+impl From<LspError> for jsonrpc::Error {
+    fn from(err: LspError) -> Self {
+        let code = match err.kind {
+            LspErrorKind::AlreadyTracked
+            | LspErrorKind::InvalidArguments
+            | LspErrorKind::NoComment => jsonrpc::ErrorCode::InvalidParams,
+            LspErrorKind::DatabaseError(_) => jsonrpc::ErrorCode::InternalError,
+        };
+        jsonrpc::Error {
+            code,
+            message: err.message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<LspError> for TmgrError {
+    fn from(err: LspError) -> Self {
+        TmgrError::new(TmgrErrorKind::LspCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
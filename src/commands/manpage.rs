@@ -0,0 +1,228 @@
+use super::super::{
+    cli::model::Cli,
+    model::{CommandResult, ErrorCategory, ErrorCode, TmgrError, TmgrErrorKind},
+};
+use clap::{Arg, Command, CommandFactory};
+use clap_mangen::Man;
+use std::{
+    fmt,
+    fs::create_dir_all,
+    path::{Path, PathBuf},
+};
+
+/// Prints the man page of tmgr.
+pub(crate) fn run() -> Result<CommandResult<String>, ManpageError> {
+    let mut buffer = Vec::new();
+    Man::new(Cli::command())
+        .render(&mut buffer)
+        .map_err(|e| ManpageError {
+            kind: ManpageErrorKind::IOError,
+            message: e.to_string(),
+        })?;
+    let page = String::from_utf8_lossy(&buffer).to_string();
+    Ok(CommandResult::new(page.clone(), page))
+}
+
+/// Writes the man page of tmgr and of every command into a directory.
+pub(crate) fn write_pages(dir: String) -> Result<CommandResult<Vec<PathBuf>>, ManpageError> {
+    let pages = write_pages_in(Path::new(&dir))?;
+    Ok(CommandResult::new(
+        format!("Wrote {} man pages into '{dir}'", pages.len()),
+        pages,
+    ))
+}
+
+/// Writes `tmgr.1` and a `tmgr-<command>.1` page per command, and returns the files written.
+pub(super) fn write_pages_in(dir: &Path) -> Result<Vec<PathBuf>, ManpageError> {
+    create_dir_all(dir).map_err(|e| ManpageError {
+        kind: ManpageErrorKind::IOError,
+        message: format!("Failed to create '{}': {e}", dir.display()),
+    })?;
+    clap_mangen::generate_to(Cli::command(), dir).map_err(|e| ManpageError {
+        kind: ManpageErrorKind::IOError,
+        message: format!("Failed to write man pages into '{}': {e}", dir.display()),
+    })?;
+    let mut cli = Cli::command();
+    cli.build();
+    Ok(page_names(&cli, "tmgr")
+        .into_iter()
+        .map(|name| dir.join(format!("{name}.1")))
+        .collect())
+}
+
+/// The names of the man pages of a command and its subcommands, as `tmgr-config-get`.
+fn page_names(command: &Command, name: &str) -> Vec<String> {
+    let mut names = vec![name.to_string()];
+    for subcommand in visible_subcommands(command) {
+        names.extend(page_names(
+            subcommand,
+            &format!("{name}-{}", subcommand.get_name()),
+        ));
+    }
+    names
+}
+
+/// Prints the markdown reference of every command.
+pub(crate) fn markdown() -> Result<CommandResult<String>, ManpageError> {
+    let reference = markdown_reference();
+    Ok(CommandResult::new(reference.clone(), reference))
+}
+
+/// The reference of every command, with its arguments and options, in markdown.
+pub(super) fn markdown_reference() -> String {
+    let mut cli = Cli::command();
+    cli.build();
+    let mut reference = format!(
+        "# tmgr command reference\n\n<!-- generated by `tmgr manpage --markdown`, do not edit by hand -->\n\n{}\n",
+        cli.get_about().map(|a| a.to_string()).unwrap_or_default()
+    );
+    for command in visible_subcommands(&cli) {
+        command_reference(&mut reference, command, "tmgr", 2);
+    }
+    reference.trim_end().to_string()
+}
+
+fn command_reference(reference: &mut String, command: &Command, parent: &str, level: usize) {
+    let name = format!("{parent} {}", command.get_name());
+    reference.push_str(&format!("\n{} `{name}`\n\n", "#".repeat(level)));
+    if let Some(about) = command.get_long_about().or(command.get_about()) {
+        reference.push_str(&format!("{about}\n\n"));
+    }
+    let usage = command.clone().render_usage().to_string();
+    let usage = usage.strip_prefix("Usage: ").unwrap_or(&usage);
+    reference.push_str(&format!("```\n{usage}\n```\n"));
+
+    let visible =
+        |arg: &&Arg| !arg.is_hide_set() && !["help", "version"].contains(&arg.get_id().as_str());
+    let positionals: Vec<&Arg> = command.get_positionals().filter(visible).collect();
+    if !positionals.is_empty() {
+        reference.push_str("\nArguments:\n\n");
+        for arg in positionals {
+            let value = arg
+                .get_value_names()
+                .and_then(|names| names.first())
+                .map(|n| n.to_string())
+                .unwrap_or(arg.get_id().to_string().to_uppercase());
+            reference.push_str(&format!("- `<{value}>`: {}\n", help(arg)));
+        }
+    }
+    let options: Vec<&Arg> = command
+        .get_opts()
+        .chain(flags(command))
+        .filter(visible)
+        .collect();
+    if !options.is_empty() {
+        reference.push_str("\nOptions:\n\n");
+        for arg in options {
+            reference.push_str(&format!("- `{}`: {}\n", option_names(arg), help(arg)));
+        }
+    }
+    for subcommand in visible_subcommands(command) {
+        command_reference(reference, subcommand, &name, level + 1);
+    }
+}
+
+/// The options that take no value.
+fn flags(command: &Command) -> impl Iterator<Item = &Arg> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.get_action().takes_values())
+}
+
+fn option_names(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("-{short}"));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("--{long}"));
+    }
+    let mut names = names.join(", ");
+    if arg.get_action().takes_values() {
+        let value = arg
+            .get_value_names()
+            .and_then(|names| names.first())
+            .map(|n| n.to_string())
+            .unwrap_or(arg.get_id().to_string().to_uppercase());
+        names.push_str(&format!(" <{value}>"));
+    }
+    names
+}
+
+fn help(arg: &Arg) -> String {
+    let mut help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+    let values: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|v| !v.is_hide_set())
+        .map(|v| format!("`{}`", v.get_name()))
+        .collect();
+    if !values.is_empty() {
+        help.push_str(&format!(" (one of {})", values.join(", ")));
+    }
+    if let Some(default) = arg
+        .get_default_values()
+        .first()
+        .filter(|_| arg.get_action().takes_values())
+    {
+        help.push_str(&format!(" (default: `{}`)", default.to_string_lossy()));
+    }
+    help
+}
+
+/// The subcommands shown in help, without the generated `help` command.
+pub(super) fn visible_subcommands(command: &Command) -> impl Iterator<Item = &Command> {
+    command
+        .get_subcommands()
+        .filter(|c| !c.is_hide_set() && c.get_name() != "help")
+}
+
+// --- Manpage Errors ---
+#[derive(Debug)]
+pub enum ManpageErrorKind {
+    IOError,
+}
+
+#[derive(Debug)]
+pub struct ManpageError {
+    kind: ManpageErrorKind,
+    message: String,
+}
+
+impl fmt::Display for ManpageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (manpage error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for ManpageError {}
+
+impl fmt::Display for ManpageErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManpageErrorKind::IOError => write!(f, "IO error"),
+        }
+    }
+}
+
+impl ErrorCode for ManpageErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ManpageErrorKind::IOError => "E-MANPAGE-001",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ManpageErrorKind::IOError => ErrorCategory::IO,
+        }
+    }
+}
+
+impl From<ManpageError> for TmgrError {
+    fn from(err: ManpageError) -> Self {
+        TmgrError::new(TmgrErrorKind::ManpageCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -0,0 +1,304 @@
+use ratatui::crossterm::terminal;
+use std::io::{IsTerminal, stdout};
+
+/// Renders markdown for stdout: wrapped to the terminal width on a TTY and unwrapped
+/// otherwise, and styled when `color` is set.
+pub(super) fn render_for_terminal(markdown: &str, color: bool) -> String {
+    let width = stdout()
+        .is_terminal()
+        .then(|| terminal::size().map(|(columns, _)| columns as usize).ok())
+        .flatten();
+    render(markdown, width, color)
+}
+
+/// Renders markdown as terminal text.
+///
+/// Headings, lists, block quotes, code blocks, rules, inline code, emphasis and links are
+/// supported; anything else is shown as is. Paragraphs and list items are wrapped to
+/// `width` when given. Without `styled`, the output has no escape codes.
+pub(super) fn render(markdown: &str, width: Option<usize>, styled: bool) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            out.push(paint(&format!("    {line}"), Inline::Code, None, styled));
+            continue;
+        }
+        if trimmed.starts_with("<!--") && trimmed.ends_with("-->") {
+            continue;
+        }
+        if trimmed.is_empty() {
+            if out.last().is_some_and(|last| !last.is_empty()) {
+                out.push(String::new());
+            }
+            continue;
+        }
+
+        if let Some((level, text)) = heading(trimmed) {
+            out.extend(wrap(
+                &parse_inline(text),
+                "",
+                "",
+                width,
+                Some(level),
+                styled,
+            ));
+        } else if is_rule(trimmed) {
+            let rule = "─".repeat(width.unwrap_or(40).min(80));
+            out.push(if styled { sgr(&rule, &[DIMMED]) } else { rule });
+        } else if let Some(text) = trimmed.strip_prefix('>') {
+            let bar = if styled {
+                sgr("│ ", &[DIMMED])
+            } else {
+                "| ".to_string()
+            };
+            out.extend(wrap(
+                &parse_inline(text.trim_start()),
+                &bar,
+                &bar,
+                width,
+                None,
+                styled,
+            ));
+        } else if let Some((marker, text)) = list_item(line) {
+            let indent = " ".repeat(marker.chars().count());
+            out.extend(wrap(
+                &parse_inline(text),
+                &marker,
+                &indent,
+                width,
+                None,
+                styled,
+            ));
+        } else {
+            out.extend(wrap(&parse_inline(trimmed), "", "", width, None, styled));
+        }
+    }
+    while out.last().is_some_and(String::is_empty) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Inline {
+    Plain,
+    Bold,
+    Italic,
+    Code,
+    Link,
+    Url,
+}
+
+/// Splits a line of markdown into styled spans, dropping the markup characters.
+fn parse_inline(text: &str) -> Vec<(String, Inline)> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        // underscores inside words, as in snake_case, are not emphasis
+        let in_word = plain.chars().last().is_some_and(char::is_alphanumeric);
+        let span = match c {
+            '_' if in_word => None,
+            '`' => delimited(rest, "`").map(|(inner, len)| (vec![(inner, Inline::Code)], len)),
+            '*' | '_' if rest.starts_with("**") || rest.starts_with("__") => {
+                delimited(rest, &rest[..2]).map(|(inner, len)| (vec![(inner, Inline::Bold)], len))
+            }
+            '*' | '_' => {
+                delimited(rest, &rest[..1]).map(|(inner, len)| (vec![(inner, Inline::Italic)], len))
+            }
+            '[' => link(rest),
+            _ => None,
+        };
+        match span {
+            Some((parsed, len)) => {
+                if !plain.is_empty() {
+                    spans.push((std::mem::take(&mut plain), Inline::Plain));
+                }
+                spans.extend(parsed);
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push((plain, Inline::Plain));
+    }
+    spans
+}
+
+/// The text between a delimiter at the start of `text` and its closing delimiter, and the
+/// length of the whole delimited span.
+fn delimited(text: &str, delimiter: &str) -> Option<(String, usize)> {
+    let inner = &text[delimiter.len()..];
+    let end = inner.find(delimiter).filter(|end| *end > 0)?;
+    Some((inner[..end].to_string(), delimiter.len() * 2 + end))
+}
+
+/// A `[text](url)` link at the start of `text`, shown as the text followed by the url.
+fn link(text: &str) -> Option<(Vec<(String, Inline)>, usize)> {
+    let label_end = text.find("](")?;
+    let url_end = label_end + text[label_end..].find(')')?;
+    let label = &text[1..label_end];
+    let url = &text[label_end + 2..url_end];
+    Some((
+        vec![
+            (label.to_string(), Inline::Link),
+            (format!(" ({url})"), Inline::Url),
+        ],
+        url_end + 1,
+    ))
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, text.trim()))
+}
+
+fn is_rule(line: &str) -> bool {
+    let chars: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|marker| chars.chars().all(|c| c == *marker))
+}
+
+/// The rendered marker of a list item, including its nesting indent, and the item text.
+fn list_item(line: &str) -> Option<(String, &str)> {
+    let indent = line.len() - line.trim_start().len();
+    let item = line.trim_start();
+    if let Some(text) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| item.strip_prefix(bullet))
+    {
+        return Some((format!("{}• ", " ".repeat(indent)), text.trim()));
+    }
+    let digits = item.chars().take_while(char::is_ascii_digit).count();
+    let text = item[digits..].strip_prefix(". ").filter(|_| digits > 0)?;
+    Some((
+        format!("{}{}. ", " ".repeat(indent), &item[..digits]),
+        text.trim(),
+    ))
+}
+
+/// Lays out spans into lines of at most `width` visible characters, breaking between
+/// words. The first line starts with `first_prefix` and the others with `prefix`.
+fn wrap(
+    spans: &[(String, Inline)],
+    first_prefix: &str,
+    prefix: &str,
+    width: Option<usize>,
+    heading: Option<usize>,
+    styled: bool,
+) -> Vec<String> {
+    // a word is made of the pieces of every span it touches
+    let mut words: Vec<Vec<(String, Inline)>> = Vec::new();
+    let mut word: Vec<(String, Inline)> = Vec::new();
+    for (text, inline) in spans {
+        let mut piece = String::new();
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !piece.is_empty() {
+                    word.push((std::mem::take(&mut piece), *inline));
+                }
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            } else {
+                piece.push(c);
+            }
+        }
+        if !piece.is_empty() {
+            word.push((piece, *inline));
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    let prefix_width = visible_width(prefix);
+    let available = width.map(|w| w.saturating_sub(prefix_width).max(10));
+    let mut lines = Vec::new();
+    let mut line = first_prefix.to_string();
+    let mut line_width = 0;
+    for word in words {
+        let word_width: usize = word.iter().map(|(text, _)| text.chars().count()).sum();
+        if line_width > 0 {
+            if available.is_some_and(|available| line_width + 1 + word_width > available) {
+                lines.push(std::mem::replace(&mut line, prefix.to_string()));
+                line_width = 0;
+            } else {
+                line.push(' ');
+                line_width += 1;
+            }
+        }
+        for (text, inline) in &word {
+            line.push_str(&paint(text, *inline, heading, styled));
+        }
+        line_width += word_width;
+    }
+    lines.push(line);
+    lines
+}
+
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        match c {
+            '\u{1b}' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if !in_escape => width += 1,
+            _ => {}
+        }
+    }
+    width
+}
+
+fn paint(text: &str, inline: Inline, heading: Option<usize>, styled: bool) -> String {
+    if !styled {
+        return text.to_string();
+    }
+    let mut codes: Vec<&str> = match inline {
+        Inline::Plain => vec![],
+        Inline::Bold => vec![BOLD],
+        Inline::Italic => vec![ITALIC],
+        Inline::Code => vec![YELLOW],
+        Inline::Link => vec![BLUE, UNDERLINE],
+        Inline::Url => vec![DIMMED],
+    };
+    match heading {
+        Some(1) => codes.extend([BOLD, UNDERLINE, MAGENTA]),
+        Some(2) => codes.extend([BOLD, MAGENTA]),
+        Some(_) => codes.push(BOLD),
+        None => {}
+    }
+    sgr(text, &codes)
+}
+
+// SGR parameters of the styles used, written out here so the output does not depend on the
+// global color switch of `colored`.
+const BOLD: &str = "1";
+const DIMMED: &str = "2";
+const ITALIC: &str = "3";
+const UNDERLINE: &str = "4";
+const YELLOW: &str = "33";
+const BLUE: &str = "34";
+const MAGENTA: &str = "35";
+
+/// Wraps `text` in the escape codes of the given SGR parameters.
+fn sgr(text: &str, codes: &[&str]) -> String {
+    if codes.is_empty() {
+        return text.to_string();
+    }
+    format!("\u{1b}[{}m{text}\u{1b}[0m", codes.join(";"))
+}
@@ -1,7 +1,7 @@
 use super::super::{
     cli::model::TmgrVersion,
-    db::DB,
-    model::{CommandResult, TmgrError, TmgrErrorKind},
+    db::{DB, Logged},
+    model::{CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, TmgrError, TmgrErrorKind},
 };
 use std::fmt;
 
@@ -24,23 +24,26 @@ pub(crate) async fn run(
         TmgrVersion::V2 => {
             db.client
                 .query(v2_fix_low)
+                .logged(v2_fix_low)
                 .await
-                .map_err(|_| MigrateError {
-                    kind: MigrateErrorKind::DatabaseError,
+                .map_err(|e| MigrateError {
+                    kind: MigrateErrorKind::DatabaseError(Some(e.into())),
                     message: "Failed to convert low priority v2 tasks to v3".to_string(),
                 })?;
             db.client
                 .query(v2_fix_medium)
+                .logged(v2_fix_medium)
                 .await
-                .map_err(|_| MigrateError {
-                    kind: MigrateErrorKind::DatabaseError,
+                .map_err(|e| MigrateError {
+                    kind: MigrateErrorKind::DatabaseError(Some(e.into())),
                     message: "Failed to convert medium priority v2 tasks to v3".to_string(),
                 })?;
             db.client
                 .query(v2_fix_high)
+                .logged(v2_fix_high)
                 .await
-                .map_err(|_| MigrateError {
-                    kind: MigrateErrorKind::DatabaseError,
+                .map_err(|e| MigrateError {
+                    kind: MigrateErrorKind::DatabaseError(Some(e.into())),
                     message: "Failed to convert high priority v2 tasks to v3".to_string(),
                 })?;
         }
@@ -73,7 +76,7 @@ pub(crate) async fn run(
 // --- Migrate Errors ---
 #[derive(Debug)]
 pub enum MigrateErrorKind {
-    DatabaseError,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
     UnableToGetTmgrVersion,
 }
 
@@ -89,17 +92,52 @@ impl fmt::Display for MigrateError {
     }
 }
 
+impl std::error::Error for MigrateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            MigrateErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for MigrateErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            MigrateErrorKind::DatabaseError => write!(f, "Database error"),
+            MigrateErrorKind::DatabaseError(_) => write!(f, "Database error"),
             MigrateErrorKind::UnableToGetTmgrVersion => write!(f, "Unable to get tmgr version"),
         }
     }
 }
 
+impl ErrorCode for MigrateErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            MigrateErrorKind::DatabaseError(_) => "E-MIGRATE-001",
+            MigrateErrorKind::UnableToGetTmgrVersion => "E-MIGRATE-002",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            MigrateErrorKind::DatabaseError(_) | MigrateErrorKind::UnableToGetTmgrVersion => {
+                ErrorCategory::Internal
+            }
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            MigrateErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            _ => None,
+        }
+    }
+}
+
 impl From<MigrateError> for TmgrError {
     fn from(err: MigrateError) -> Self {
         TmgrError::new(TmgrErrorKind::MigrateCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
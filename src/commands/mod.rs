@@ -1,12 +1,40 @@
-pub(super) mod add;
-pub(super) mod complete;
-pub(super) mod delete;
-pub(super) mod list;
+/// Adding tasks.
+pub mod add;
+pub(super) mod attach;
+pub(super) mod board;
+/// Completing tasks.
+pub mod complete;
+pub(super) mod completions;
+pub(super) mod config;
+/// Deleting tasks and their notes.
+pub mod delete;
+pub(super) mod doctor;
+pub(super) mod git;
+pub(super) mod git_hook;
+pub(super) mod link;
+/// Listing and filtering tasks.
+pub mod list;
+pub(super) mod lsp;
+pub(super) mod manpage;
+pub(super) mod markdown;
 pub(super) mod migrate;
-pub(super) mod note;
-pub(super) mod status;
-pub(super) mod update;
+/// Moving tasks between states.
+pub mod move_task;
+/// Creating, reading and appending to the markdown notes of tasks.
+pub mod note;
+pub(super) mod open;
+pub(super) mod query;
+pub(super) mod scan;
+pub(super) mod serve;
+pub(super) mod start_branch;
+/// File locations and task counts.
+pub mod status;
+pub(super) mod task_editor;
+pub(super) mod tui;
+/// Changing the name, priority and description of tasks.
+pub mod update;
 pub(super) mod upgrade;
-pub(super) mod view;
+/// Showing a single task.
+pub mod view;
 
 mod tests;
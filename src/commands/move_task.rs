@@ -0,0 +1,217 @@
+use super::super::{
+    db::{DB, DBError, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, StateTransition, Task, TaskState,
+        TmgrError, TmgrErrorKind,
+    },
+};
+use super::note::sync_header;
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+use surrealdb::sql::Datetime;
+
+/// Moves the task whose id starts with `id` to `state`, if `transitions` allow it.
+///
+/// Pass [`TaskState::default_transitions`] or [`Config::transitions`](crate::Config::transitions)
+/// as the allowed transitions.
+pub async fn run(
+    db: &DB,
+    id: String,
+    state: TaskState,
+    transitions: &BTreeMap<TaskState, Vec<TaskState>>,
+) -> Result<CommandResult<Task>, MoveError> {
+    let task = db
+        .select_task_by_partial_id(&id)
+        .await
+        .map_err(|e| MoveError {
+            message: e.to_string(),
+            kind: MoveErrorKind::TaskLookup(e),
+        })?;
+    let task_id = task.id().map_err(|e| MoveError {
+        kind: MoveErrorKind::BadTaskId,
+        message: e.to_string(),
+    })?;
+
+    let from = task.state();
+    if from == state {
+        return Err(MoveError {
+            kind: MoveErrorKind::AlreadyInState,
+            message: format!("Task '{task_id}' is already in state '{state}'"),
+        });
+    }
+    let allowed = transitions.get(&from).cloned().unwrap_or_default();
+    if !allowed.contains(&state) {
+        let allowed: Vec<String> = allowed.iter().map(String::from).collect();
+        return Err(MoveError {
+            kind: MoveErrorKind::TransitionNotAllowed,
+            message: format!(
+                "Task '{task_id}' cannot move from '{from}' to '{state}', allowed states are: {}",
+                if allowed.is_empty() {
+                    "none".to_string()
+                } else {
+                    allowed.join(", ")
+                }
+            ),
+        });
+    }
+
+    let updated_task = transition_task(db, &task, state.clone()).await?;
+    Ok(CommandResult::new(
+        format!("Successfully moved task '{task_id}' from '{from}' to '{state}'"),
+        updated_task,
+    ))
+}
+
+#[derive(Serialize)]
+struct StateUpdate {
+    state: TaskState,
+    state_history: Vec<StateTransition>,
+    completed_at: Option<Datetime>,
+}
+
+/// Moves a task to the given state without checking whether the transition is allowed.
+///
+/// The transition is appended to the task's state history. Moving into a closed state
+/// sets `completed_at`, and moving out of one clears it.
+pub(super) async fn transition_task(
+    db: &DB,
+    task: &Task,
+    to: TaskState,
+) -> Result<Task, MoveError> {
+    let task_id = task.id().map_err(|e| MoveError {
+        kind: MoveErrorKind::BadTaskId,
+        message: e.to_string(),
+    })?;
+    let from = task.state();
+
+    let mut state_history = task.state_history().clone();
+    if from != to {
+        state_history.push(StateTransition::new(from.clone(), to.clone()));
+    }
+    let completed_at = match (from.is_closed(), to.is_closed()) {
+        (true, true) => task.completed_at().clone(),
+        (false, true) => Some(Datetime::default()),
+        (_, false) => None,
+    };
+    let update = StateUpdate {
+        state: to,
+        state_history,
+        completed_at,
+    };
+
+    let updated_task: Task = db
+        .client
+        .update(("task", &task_id))
+        .merge(update)
+        .logged(&format!("UPDATE task:{task_id} MERGE $data"))
+        .await
+        .map_err(|e| MoveError {
+            kind: MoveErrorKind::DatabaseError(Some(e.into())),
+            message: "Failed to update task state".to_string(),
+        })?
+        .ok_or_else(|| MoveError {
+            kind: MoveErrorKind::DatabaseError(None),
+            message: "Failed to update task state".to_string(),
+        })?;
+
+    sync_header(&updated_task).map_err(|e| MoveError {
+        kind: MoveErrorKind::NoteError,
+        message: format!("Task '{task_id}' was moved, but its note was not updated: {e}"),
+    })?;
+    Ok(updated_task)
+}
+
+// --- Move Errors ---
+#[derive(Debug)]
+pub enum MoveErrorKind {
+    AlreadyInState,
+    BadTaskId,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    NoteError,
+    TaskLookup(DBError),
+    TransitionNotAllowed,
+}
+
+#[derive(Debug)]
+pub struct MoveError {
+    kind: MoveErrorKind,
+    message: String,
+}
+
+impl MoveError {
+    pub fn kind(&self) -> &MoveErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (move error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for MoveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            MoveErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            MoveErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MoveErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveErrorKind::AlreadyInState => write!(f, "Already in state"),
+            MoveErrorKind::BadTaskId => write!(f, "Bad task id"),
+            MoveErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            MoveErrorKind::NoteError => write!(f, "Note error"),
+            MoveErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+            MoveErrorKind::TransitionNotAllowed => write!(f, "Transition not allowed"),
+        }
+    }
+}
+
+impl ErrorCode for MoveErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            MoveErrorKind::AlreadyInState => "E-MOVE-001",
+            MoveErrorKind::BadTaskId => "E-MOVE-002",
+            MoveErrorKind::DatabaseError(_) => "E-MOVE-003",
+            MoveErrorKind::NoteError => "E-MOVE-004",
+            MoveErrorKind::TransitionNotAllowed => "E-MOVE-005",
+            MoveErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            MoveErrorKind::AlreadyInState | MoveErrorKind::TransitionNotAllowed => {
+                ErrorCategory::Validation
+            }
+            MoveErrorKind::BadTaskId | MoveErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+            MoveErrorKind::NoteError => ErrorCategory::IO,
+            MoveErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            MoveErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            MoveErrorKind::TransitionNotAllowed => {
+                Some("The allowed moves are set by the transitions setting of the config file")
+            }
+            MoveErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
+        }
+    }
+}
+
+impl From<MoveError> for TmgrError {
+    fn from(err: MoveError) -> Self {
+        TmgrError::new(TmgrErrorKind::MoveCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -1,28 +1,51 @@
 use super::super::{
-    db::DB,
-    model::{CommandResult, Task, TmgrError, TmgrErrorKind},
+    config::Config,
+    db::{DB, DBError, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, TableRow, Task, TmgrError,
+        TmgrErrorKind, format_datetime, log_error,
+    },
 };
+use super::markdown::render_for_terminal;
 use std::{
+    collections::BTreeMap,
     env::{current_exe, var},
     fmt::{self, Formatter},
-    fs::{File, create_dir_all},
-    io::Write,
-    path::PathBuf,
+    fs::{File, create_dir_all, read_dir, read_to_string, write},
+    io::{Read, Write, stdin},
+    path::{Path, PathBuf},
     process::{Command, ExitStatus},
 };
-use surrealdb::opt::PatchOp;
+use surrealdb::{opt::PatchOp, sql::Datetime};
+use tracing::info;
 
-pub(crate) async fn run(
+/// The name of the template used when no template is given or configured.
+pub(super) const DEFAULT_TEMPLATE_NAME: &str = "default";
+
+/// The built-in default template, used unless a `default.md` template exists.
+const DEFAULT_TEMPLATE: &str = "{{header}}\n\n## Notes\n\n";
+
+/// The markers delimiting the generated header of a note, which is regenerated whenever
+/// the task changes.
+const HEADER_START: &str = "<!-- tmgr:header:start -->";
+const HEADER_END: &str = "<!-- tmgr:header:end -->";
+
+/// Creates the note of the task whose id starts with `id` from a template, unless it
+/// exists, and opens it in the editor if `open_editor` is set.
+///
+/// The message is the path of the note.
+pub async fn run(
     db: &DB,
     id: String,
     open_editor: bool,
+    template: Option<String>,
 ) -> Result<CommandResult<Task>, NoteError> {
     let task = db
         .select_task_by_partial_id(&id)
         .await
         .map_err(|e| NoteError {
-            kind: NoteErrorKind::DatabaseError,
             message: e.to_string(),
+            kind: NoteErrorKind::TaskLookup(e),
         })?;
 
     if let Some(note_path) = task.work_note_path() {
@@ -35,8 +58,8 @@ pub(crate) async fn run(
             kind: NoteErrorKind::BadTaskId,
             message: e.to_string(),
         })?;
-        let task_name = task.name();
-        let task_description = task.description().as_deref().unwrap_or_default();
+        let template = load_template(template.as_deref().unwrap_or(DEFAULT_TEMPLATE_NAME))?;
+        let contents = render_template(&template, &task)?;
         let note_path = path_from_id(task_id.as_str())?;
 
         // create file
@@ -54,42 +77,33 @@ pub(crate) async fn run(
         })?;
 
         // write to file
-        let task_header = format!("# Task {task_id} - {task_name}\n\n");
-        f.write_all(task_header.as_bytes()).map_err(|_| NoteError {
-            kind: NoteErrorKind::IOError,
-            message: "Failed to write to file".to_string(),
-        })?;
-        if !task_description.is_empty() {
-            let subheader = format!("{task_description}\n\n");
-            f.write_all(subheader.as_bytes()).map_err(|_| NoteError {
+        f.write_all(contents.as_bytes())
+            .inspect_err(log_error)
+            .map_err(|e| NoteError {
                 kind: NoteErrorKind::IOError,
-                message: "Failed to write to file".to_string(),
+                message: format!("Failed to write to '{}': {e}", note_path.display()),
             })?;
-        }
-        f.write_all(b"## Notes\n\n").map_err(|_| NoteError {
-            kind: NoteErrorKind::IOError,
-            message: "Failed to write to file".to_string(),
-        })?;
 
         // close file
-        f.flush().map_err(|_| NoteError {
+        f.flush().inspect_err(log_error).map_err(|e| NoteError {
             kind: NoteErrorKind::IOError,
-            message: "Failed to write to file".to_string(),
+            message: format!("Failed to write to '{}': {e}", note_path.display()),
         })?;
 
         let note_path_string = note_path.to_string_lossy().to_string();
         // Update task
         let updated_task: Task = db
             .client
-            .upsert(("task", task_id))
+            .upsert(("task", &task_id))
             .patch(PatchOp::replace("/work_note_path", Some(&note_path_string)))
+            .logged(&format!("UPSERT task:{task_id} PATCH $patch"))
             .await
-            .map_err(|_| NoteError {
-                kind: NoteErrorKind::DatabaseError,
+            .map_err(|e| NoteError {
+                kind: NoteErrorKind::DatabaseError(Some(e.into())),
                 message: "Failed to update task".to_string(),
             })?
             .ok_or_else(|| NoteError {
-                kind: NoteErrorKind::DatabaseError,
+                kind: NoteErrorKind::DatabaseError(None),
                 message: "Failed to update task".to_string(),
             })?;
 
@@ -101,7 +115,145 @@ pub(crate) async fn run(
     }
 }
 
+/// Renders the note of a task for the terminal.
+pub async fn show(db: &DB, id: String) -> Result<CommandResult<Task>, NoteError> {
+    let task = db
+        .select_task_by_partial_id(&id)
+        .await
+        .map_err(|e| NoteError {
+            message: e.to_string(),
+            kind: NoteErrorKind::TaskLookup(e),
+        })?;
+    let rendered = render_note(&task)?;
+    Ok(CommandResult::new(rendered, task))
+}
+
+/// Reads the note of a task and renders it for the terminal.
+pub(super) fn render_note(task: &Task) -> Result<String, NoteError> {
+    Ok(render_for_terminal(
+        &read_note(task)?,
+        colored::control::SHOULD_COLORIZE.should_colorize(),
+    ))
+}
+
+/// Reads the markdown of the note of a task.
+pub(super) fn read_note(task: &Task) -> Result<String, NoteError> {
+    let task_id = task.id().map_err(|e| NoteError {
+        kind: NoteErrorKind::BadTaskId,
+        message: e.to_string(),
+    })?;
+    let note_path = task.work_note_path().as_ref().ok_or_else(|| NoteError {
+        kind: NoteErrorKind::NoteNotFound,
+        message: format!("Task '{task_id}' has no note, create one with 'tmgr note {task_id}'"),
+    })?;
+    read_to_string(note_path).map_err(|e| NoteError {
+        kind: NoteErrorKind::IOError,
+        message: format!("Failed to read note '{note_path}': {e}"),
+    })
+}
+
+/// Appends a timestamped entry to the note of a task, creating the note if needed.
+///
+/// If `text` is `-`, the entry is read from stdin.
+pub async fn append(
+    db: &DB,
+    id: String,
+    text: String,
+    template: Option<String>,
+) -> Result<CommandResult<Task>, NoteError> {
+    let text = if text == "-" {
+        let mut input = String::new();
+        stdin().read_to_string(&mut input).map_err(|e| NoteError {
+            kind: NoteErrorKind::IOError,
+            message: format!("Failed to read the entry from stdin: {e}"),
+        })?;
+        input
+    } else {
+        text
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(NoteError {
+            kind: NoteErrorKind::EmptyEntry,
+            message: "Nothing to append, the entry is empty".to_string(),
+        });
+    }
+
+    let res = run(db, id, false, template).await?;
+    let note_path = res.message().to_string();
+    let note = read_to_string(&note_path).map_err(|e| NoteError {
+        kind: NoteErrorKind::IOError,
+        message: format!("Failed to read note '{note_path}': {e}"),
+    })?;
+    let timestamp = Datetime::default()
+        .format("%Y-%m-%d %H:%M:%S UTC")
+        .to_string();
+    write(&note_path, append_entry(&note, &timestamp, text)).map_err(|e| NoteError {
+        kind: NoteErrorKind::IOError,
+        message: format!("Failed to write note '{note_path}': {e}"),
+    })?;
+
+    Ok(CommandResult::new(
+        format!("Successfully appended an entry to note '{note_path}'"),
+        res.into_result(),
+    ))
+}
+
+/// Adds an entry at the end of the `## Notes` section of a note.
+///
+/// The section ends at the next heading of the same or a higher level outside of a code
+/// block. If the note has no `## Notes` section, one is added at the end.
+pub(super) fn append_entry(note: &str, timestamp: &str, text: &str) -> String {
+    let entry = format!("### {timestamp}\n\n{text}\n");
+    let mut offset = 0;
+    let mut in_notes = false;
+    let mut insert_at = None;
+    let mut in_code_block = false;
+    for line in note.split_inclusive('\n') {
+        let heading = line.trim_end();
+        if heading.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block {
+            offset += line.len();
+            continue;
+        }
+        if in_notes && (heading.starts_with("# ") || heading.starts_with("## ")) {
+            insert_at = Some(offset);
+            break;
+        }
+        if heading == "## Notes" {
+            in_notes = true;
+        }
+        offset += line.len();
+    }
+
+    let (before, after) = note.split_at(insert_at.unwrap_or(note.len()));
+    let mut updated = before.trim_end().to_string();
+    if !in_notes {
+        updated.push_str("\n\n## Notes");
+    }
+    updated.push_str("\n\n");
+    updated.push_str(&entry);
+    if !after.is_empty() {
+        updated.push('\n');
+        updated.push_str(after);
+    }
+    updated.trim_start().to_string()
+}
+
 pub(super) fn path_from_id(id: &str) -> Result<PathBuf, NoteError> {
+    let path = notes_dir()?.join(format!("{id}.md"));
+    info!("The note of task '{id}' is '{}'", path.display());
+    Ok(path)
+}
+
+/// The directory where note files are created, set in the config file or next to the tmgr
+/// executable.
+pub(super) fn notes_dir() -> Result<PathBuf, NoteError> {
+    if let Some(dir) = Config::active().notes_dir() {
+        return Ok(dir);
+    }
     let exe_path = current_exe().map_err(|e| NoteError {
         kind: NoteErrorKind::UnableToDetermineTmgrExecutablePath,
         message: e.to_string(),
@@ -110,22 +262,220 @@ pub(super) fn path_from_id(id: &str) -> Result<PathBuf, NoteError> {
         kind: NoteErrorKind::IOError,
         message: "Could not get parent directory of tmgr executable".to_string(),
     })?;
-    Ok(dir_path.join("tmgr_notes").join(format!("{id}.md")))
+    Ok(dir_path.join("tmgr_notes"))
+}
+
+/// The directory holding user note templates, inside the tmgr config directory.
+pub(super) fn templates_dir() -> Result<PathBuf, NoteError> {
+    Config::get_config_dir()
+        .map(|dir| dir.join("templates"))
+        .map_err(|e| NoteError {
+            kind: NoteErrorKind::IOError,
+            message: e.to_string(),
+        })
 }
 
-fn open_note(note_path: &str) -> Result<ExitStatus, NoteError> {
-    let editor = var("EDITOR").unwrap_or("vi".to_string());
-    let res = Command::new(editor)
+fn load_template(name: &str) -> Result<String, NoteError> {
+    load_template_from(&templates_dir()?, name)
+}
+
+/// Loads the template `<name>.md` from the given directory.
+///
+/// The default template falls back to the built-in template if the directory does not
+/// contain one.
+pub(super) fn load_template_from(dir: &Path, name: &str) -> Result<String, NoteError> {
+    let path = dir.join(format!("{name}.md"));
+    if path.is_file() {
+        info!("Using the template '{}'", path.display());
+        return read_to_string(&path).map_err(|e| NoteError {
+            kind: NoteErrorKind::IOError,
+            message: format!("Failed to read template '{}': {e}", path.display()),
+        });
+    }
+    if name == DEFAULT_TEMPLATE_NAME {
+        return Ok(DEFAULT_TEMPLATE.to_string());
+    }
+
+    let mut available = vec![DEFAULT_TEMPLATE_NAME.to_string()];
+    if let Ok(entries) = read_dir(dir) {
+        available.extend(entries.flatten().filter_map(|entry| {
+            let path = entry.path();
+            (path.extension()? == "md")
+                .then(|| path.file_stem()?.to_str().map(str::to_string))
+                .flatten()
+        }));
+    }
+    available.sort();
+    available.dedup();
+    Err(NoteError {
+        kind: NoteErrorKind::TemplateNotFound,
+        message: format!(
+            "Template '{name}' not found in '{}', available templates are: {}",
+            dir.display(),
+            available.join(", ")
+        ),
+    })
+}
+
+/// Renders a note template for the given task.
+///
+/// Every task field is available as a `{{field}}` placeholder, along with `{{header}}` for
+/// the generated header. A line holding nothing but
+/// a placeholder with an empty value is left out, along with the blank line after it.
+pub(super) fn render_template(template: &str, task: &Task) -> Result<String, NoteError> {
+    let values = template_values(task);
+    let mut rendered = String::new();
+    let mut skip_blank_line = false;
+    for line in template.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        if skip_blank_line && content.trim().is_empty() {
+            skip_blank_line = false;
+            continue;
+        }
+        skip_blank_line = false;
+
+        let line = replace_placeholders(line, &values)?;
+        let trimmed = content.trim();
+        if trimmed.starts_with("{{")
+            && trimmed.ends_with("}}")
+            && trimmed.matches("{{").count() == 1
+            && line.trim().is_empty()
+        {
+            skip_blank_line = true;
+            continue;
+        }
+        rendered.push_str(&line);
+    }
+    Ok(rendered)
+}
+
+fn template_values(task: &Task) -> BTreeMap<String, String> {
+    let mut values: BTreeMap<String, String> = task.to_table_rows().into_iter().collect();
+    // unset fields render as empty rather than as their table placeholders
+    if task.description().is_none() {
+        values.insert("description".to_string(), String::new());
+    }
+    if task.completed_at().is_none() {
+        values.insert("completed_at".to_string(), String::new());
+    }
+    values.insert("header".to_string(), render_header(task));
+    values
+}
+
+/// Renders the delimited header of a note: the task title, a status line and the description.
+pub(super) fn render_header(task: &Task) -> String {
+    let mut status = format!(
+        "**State:** `{}` | **Priority:** {}",
+        task.state(),
+        task.priority()
+    );
+    if let Some(completed_at) = task.completed_at() {
+        status.push_str(&format!(
+            " | **Completed:** {}",
+            format_datetime(completed_at)
+        ));
+    }
+
+    let mut lines = vec![
+        HEADER_START.to_string(),
+        format!(
+            "# Task {} - {}",
+            task.id().unwrap_or("Error getting ID".to_string()),
+            task.name()
+        ),
+        String::new(),
+        status,
+    ];
+    if let Some(description) = task.description().as_ref().filter(|d| !d.is_empty()) {
+        lines.push(String::new());
+        lines.push(description.to_string());
+    }
+    lines.push(HEADER_END.to_string());
+    lines.join("\n")
+}
+
+/// Replaces the delimited header of a note with a freshly rendered one.
+///
+/// Everything outside of the header markers is kept as is. Returns `None` if the note has
+/// no header markers.
+pub(super) fn replace_header(note: &str, task: &Task) -> Option<String> {
+    let start = note.find(HEADER_START)?;
+    let end = start + note[start..].find(HEADER_END)? + HEADER_END.len();
+    Some(format!(
+        "{}{}{}",
+        &note[..start],
+        render_header(task),
+        &note[end..]
+    ))
+}
+
+/// Regenerates the header of the task's note, if the task has a note with a header.
+pub(super) fn sync_header(task: &Task) -> Result<(), NoteError> {
+    let Some(note_path) = task.work_note_path() else {
+        return Ok(());
+    };
+    // a missing note is reported by the doctor command, not here
+    let Ok(note) = read_to_string(note_path) else {
+        return Ok(());
+    };
+    match replace_header(&note, task) {
+        Some(updated) if updated != note => write(note_path, updated).map_err(|e| NoteError {
+            kind: NoteErrorKind::IOError,
+            message: format!("Failed to update the header of note '{note_path}': {e}"),
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn replace_placeholders(
+    line: &str,
+    values: &BTreeMap<String, String>,
+) -> Result<String, NoteError> {
+    let mut replaced = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let key = rest[start + 2..start + end].trim();
+        let value = values.get(key).ok_or_else(|| NoteError {
+            kind: NoteErrorKind::UnknownPlaceholder,
+            message: format!(
+                "Unknown placeholder '{{{{{key}}}}}', available placeholders are: {}",
+                values.keys().cloned().collect::<Vec<String>>().join(", ")
+            ),
+        })?;
+        replaced.push_str(&rest[..start]);
+        replaced.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+    replaced.push_str(rest);
+    Ok(replaced)
+}
+
+/// Opens a file with the editor of the config file, `$EDITOR`, or vi.
+///
+/// The editor may be a command with arguments, as in `code --wait`.
+pub(super) fn open_note(note_path: &str) -> Result<ExitStatus, NoteError> {
+    let editor = Config::active()
+        .editor()
+        .or(var("EDITOR").ok())
+        .unwrap_or("vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let res = Command::new(program)
+        .args(words)
         .arg(note_path)
         .spawn()
         .map_err(|e| NoteError {
             kind: NoteErrorKind::FailedToOpenEditor,
-            message: format!("{e} - HINT: make sure EDITOR is set or vi is installed"),
+            message: format!("{e} - HINT: make sure the editor of the config file or EDITOR is set, or vi is installed"),
         })?
         .wait()
-        .map_err(|_| NoteError {
+        .inspect_err(log_error)
+        .map_err(|e| NoteError {
             kind: NoteErrorKind::ExecutionError,
-            message: "Command was not running as expected".to_string(),
+            message: format!("Command was not running as expected: {e}"),
         })?;
     Ok(res)
 }
@@ -134,11 +484,16 @@ fn open_note(note_path: &str) -> Result<ExitStatus, NoteError> {
 #[derive(Debug)]
 pub enum NoteErrorKind {
     BadTaskId,
-    DatabaseError,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    EmptyEntry,
     ExecutionError,
     FailedToOpenEditor,
     IOError,
+    NoteNotFound,
+    TaskLookup(DBError),
+    TemplateNotFound,
     UnableToDetermineTmgrExecutablePath,
+    UnknownPlaceholder,
 }
 
 #[derive(Debug)]
@@ -147,23 +502,90 @@ pub struct NoteError {
     message: String,
 }
 
+impl NoteError {
+    pub fn kind(&self) -> &NoteErrorKind {
+        &self.kind
+    }
+}
+
 impl fmt::Display for NoteError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} (note error: {})", self.message, self.kind)
     }
 }
 
+impl std::error::Error for NoteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            NoteErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            NoteErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for NoteErrorKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             NoteErrorKind::BadTaskId => write!(f, "Bad task id"),
-            NoteErrorKind::DatabaseError => write!(f, "Database error"),
+            NoteErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            NoteErrorKind::EmptyEntry => write!(f, "Empty entry"),
             NoteErrorKind::ExecutionError => write!(f, "Execution error"),
             NoteErrorKind::FailedToOpenEditor => write!(f, "Failed to open editor"),
             NoteErrorKind::IOError => write!(f, "IO error"),
+            NoteErrorKind::NoteNotFound => write!(f, "Note not found"),
+            NoteErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+            NoteErrorKind::TemplateNotFound => write!(f, "Template not found"),
             NoteErrorKind::UnableToDetermineTmgrExecutablePath => {
                 write!(f, "Unable to determine tmgr executable path")
             }
+            NoteErrorKind::UnknownPlaceholder => write!(f, "Unknown placeholder"),
+        }
+    }
+}
+
+impl ErrorCode for NoteErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            NoteErrorKind::BadTaskId => "E-NOTE-001",
+            NoteErrorKind::DatabaseError(_) => "E-NOTE-002",
+            NoteErrorKind::EmptyEntry => "E-NOTE-003",
+            NoteErrorKind::ExecutionError => "E-NOTE-004",
+            NoteErrorKind::FailedToOpenEditor => "E-NOTE-005",
+            NoteErrorKind::IOError => "E-NOTE-006",
+            NoteErrorKind::NoteNotFound => "E-NOTE-007",
+            NoteErrorKind::TemplateNotFound => "E-NOTE-008",
+            NoteErrorKind::UnableToDetermineTmgrExecutablePath => "E-NOTE-009",
+            NoteErrorKind::UnknownPlaceholder => "E-NOTE-010",
+            NoteErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            NoteErrorKind::BadTaskId | NoteErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+            NoteErrorKind::EmptyEntry | NoteErrorKind::UnknownPlaceholder => {
+                ErrorCategory::Validation
+            }
+            NoteErrorKind::ExecutionError
+            | NoteErrorKind::FailedToOpenEditor
+            | NoteErrorKind::IOError
+            | NoteErrorKind::UnableToDetermineTmgrExecutablePath => ErrorCategory::IO,
+            NoteErrorKind::NoteNotFound | NoteErrorKind::TemplateNotFound => {
+                ErrorCategory::NotFound
+            }
+            NoteErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            NoteErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            NoteErrorKind::ExecutionError | NoteErrorKind::FailedToOpenEditor => Some(
+                "Set the editor setting of the config file, or the $EDITOR environment variable",
+            ),
+            NoteErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
         }
     }
 }
@@ -171,5 +593,7 @@ impl fmt::Display for NoteErrorKind {
 impl From<NoteError> for TmgrError {
     fn from(err: NoteError) -> Self {
         TmgrError::new(TmgrErrorKind::NoteCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
@@ -0,0 +1,170 @@
+use super::super::{
+    db::{DB, DBError},
+    model::{
+        Attachment, AttachmentKind, CommandResult, ErrorCategory, ErrorCode, Task, TmgrError,
+        TmgrErrorKind,
+    },
+};
+use std::{fmt, path::Path, process::Command};
+
+pub(crate) async fn run(
+    db: &DB,
+    id: String,
+    number: Option<usize>,
+) -> Result<CommandResult<Task>, OpenError> {
+    let task = db
+        .select_task_by_partial_id(&id)
+        .await
+        .map_err(|e| OpenError {
+            message: e.to_string(),
+            kind: OpenErrorKind::TaskLookup(e),
+        })?;
+    let attachment = select_attachment(&task, number)?;
+    let target = attachment.target().to_string();
+    if attachment.kind() != &AttachmentKind::Url && !Path::new(&target).exists() {
+        return Err(OpenError {
+            kind: OpenErrorKind::MissingFile,
+            message: format!("The attached file '{target}' no longer exists"),
+        });
+    }
+    open_target(&target)?;
+
+    Ok(CommandResult::new(format!("Opened '{target}'"), task))
+}
+
+/// Picks the attachment to open: the `number`th one (starting at 1), or the only one if
+/// no number is given.
+pub(super) fn select_attachment(
+    task: &Task,
+    number: Option<usize>,
+) -> Result<&Attachment, OpenError> {
+    let attachments = task.attachments();
+    match number {
+        _ if attachments.is_empty() => Err(OpenError {
+            kind: OpenErrorKind::NoAttachments,
+            message: "The task has no attachments, add one with 'tmgr attach'".to_string(),
+        }),
+        None if attachments.len() == 1 => Ok(&attachments[0]),
+        Some(n) if (1..=attachments.len()).contains(&n) => Ok(&attachments[n - 1]),
+        _ => Err(OpenError {
+            kind: OpenErrorKind::InvalidAttachmentNumber,
+            message: format!(
+                "Choose an attachment between 1 and {}:\n{}",
+                attachments.len(),
+                attachments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, a)| format!("{}. {a}", i + 1))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+        }),
+    }
+}
+
+/// Opens a file or URL with the default application of the platform.
+fn open_target(target: &str) -> Result<(), OpenError> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command.arg(target).status().map_err(|e| OpenError {
+        kind: OpenErrorKind::FailedToOpen,
+        message: format!("{e} - HINT: make sure a default application opener is installed"),
+    })?;
+    if !status.success() {
+        return Err(OpenError {
+            kind: OpenErrorKind::FailedToOpen,
+            message: format!("Failed to open '{target}' ({status})"),
+        });
+    }
+    Ok(())
+}
+
+// --- Open Errors ---
+#[derive(Debug)]
+pub enum OpenErrorKind {
+    FailedToOpen,
+    InvalidAttachmentNumber,
+    MissingFile,
+    NoAttachments,
+    TaskLookup(DBError),
+}
+
+#[derive(Debug)]
+pub struct OpenError {
+    kind: OpenErrorKind,
+    message: String,
+}
+
+impl fmt::Display for OpenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (open error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for OpenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            OpenErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OpenErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OpenErrorKind::FailedToOpen => write!(f, "Failed to open"),
+            OpenErrorKind::InvalidAttachmentNumber => write!(f, "Invalid attachment number"),
+            OpenErrorKind::MissingFile => write!(f, "Missing file"),
+            OpenErrorKind::NoAttachments => write!(f, "No attachments"),
+            OpenErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+        }
+    }
+}
+
+impl ErrorCode for OpenErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            OpenErrorKind::FailedToOpen => "E-OPEN-001",
+            OpenErrorKind::InvalidAttachmentNumber => "E-OPEN-002",
+            OpenErrorKind::MissingFile => "E-OPEN-003",
+            OpenErrorKind::NoAttachments => "E-OPEN-004",
+            OpenErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            OpenErrorKind::FailedToOpen => ErrorCategory::IO,
+            OpenErrorKind::InvalidAttachmentNumber => ErrorCategory::Validation,
+            OpenErrorKind::MissingFile | OpenErrorKind::NoAttachments => ErrorCategory::NotFound,
+            OpenErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            OpenErrorKind::InvalidAttachmentNumber => {
+                Some("Run 'tmgr view <ID>' to see the attachments of the task")
+            }
+            OpenErrorKind::NoAttachments => Some("Attach files or URLs with 'tmgr attach'"),
+            OpenErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
+        }
+    }
+}
+
+impl From<OpenError> for TmgrError {
+    fn from(err: OpenError) -> Self {
+        TmgrError::new(TmgrErrorKind::OpenCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -0,0 +1,269 @@
+use super::super::{
+    cli::model::{ListFilter, OutputFormat},
+    config::Config,
+    db::{DB, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TmgrError, TmgrErrorKind,
+    },
+};
+use super::list::{self, ListError};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The table holding saved queries, keyed by name.
+const QUERY_TABLE: &str = "query";
+
+/// List filters saved under a name in the database.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct SavedQuery {
+    pub(crate) name: String,
+    pub(crate) filter: ListFilter,
+}
+
+/// Saves list filters under a name. An existing query is only replaced with `force`.
+pub(crate) async fn save(
+    db: &DB,
+    name: String,
+    filter: ListFilter,
+    force: bool,
+) -> Result<CommandResult<SavedQuery>, QueryError> {
+    check_name(&name)?;
+    let existing = select(db, &name).await?;
+    if existing.is_some() && !force {
+        return Err(QueryError {
+            kind: QueryErrorKind::QueryAlreadyExists,
+            message: format!("A query named '{name}' already exists, use --force to replace it"),
+        });
+    }
+
+    let query = SavedQuery {
+        name: name.clone(),
+        filter,
+    };
+    let _: Option<SavedQuery> = db
+        .client
+        .upsert((QUERY_TABLE, name.as_str()))
+        .content(query.clone())
+        .logged(&format!("UPSERT {QUERY_TABLE}:{name} CONTENT $query"))
+        .await
+        .map_err(|e| QueryError {
+            message: e.to_string(),
+            kind: QueryErrorKind::DatabaseError(Some(e.into())),
+        })?;
+    let verb = if existing.is_some() {
+        "replaced"
+    } else {
+        "saved"
+    };
+    Ok(CommandResult::new(
+        format!("Query '{name}' {verb}, run it with 'tmgr query run {name}'"),
+        query,
+    ))
+}
+
+/// Lists tasks with the filters of a saved query, completed with the defaults of the config.
+pub(crate) async fn run(
+    db: &DB,
+    config: &Config,
+    name: String,
+    output: OutputFormat,
+) -> Result<CommandResult<Vec<Task>>, QueryError> {
+    let query = select(db, &name).await?.ok_or_else(|| QueryError {
+        kind: QueryErrorKind::QueryNotFound,
+        message: format!("No query named '{name}', run 'tmgr query list' to see the saved queries"),
+    })?;
+    list::run(
+        db,
+        config.list_filter(query.filter),
+        config.list_columns(),
+        output,
+    )
+    .await
+    .map_err(|e| QueryError {
+        message: e.to_string(),
+        kind: QueryErrorKind::ListError(e),
+    })
+}
+
+/// Lists the saved queries with their filters, as they would be typed after `tmgr list`.
+pub(crate) async fn list(db: &DB) -> Result<CommandResult<Vec<SavedQuery>>, QueryError> {
+    let mut queries: Vec<SavedQuery> = db
+        .client
+        .select(QUERY_TABLE)
+        .logged(&format!("SELECT * FROM {QUERY_TABLE}"))
+        .await
+        .map_err(|e| QueryError {
+            message: e.to_string(),
+            kind: QueryErrorKind::DatabaseError(Some(e.into())),
+        })?;
+    queries.sort_by(|a, b| a.name.cmp(&b.name));
+    let message = if queries.is_empty() {
+        "No saved queries".to_string()
+    } else {
+        queries
+            .iter()
+            .map(|q| {
+                format!("{}: tmgr list {}", q.name, filter_args(&q.filter))
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    Ok(CommandResult::new(message, queries))
+}
+
+/// Deletes a saved query.
+pub(crate) async fn delete(db: &DB, name: String) -> Result<CommandResult<SavedQuery>, QueryError> {
+    let deleted: Option<SavedQuery> = db
+        .client
+        .delete((QUERY_TABLE, name.as_str()))
+        .logged(&format!("DELETE {QUERY_TABLE}:{name}"))
+        .await
+        .map_err(|e| QueryError {
+            message: e.to_string(),
+            kind: QueryErrorKind::DatabaseError(Some(e.into())),
+        })?;
+    let query = deleted.ok_or_else(|| QueryError {
+        kind: QueryErrorKind::QueryNotFound,
+        message: format!("No query named '{name}'"),
+    })?;
+    Ok(CommandResult::new(format!("Query '{name}' deleted"), query))
+}
+
+async fn select(db: &DB, name: &str) -> Result<Option<SavedQuery>, QueryError> {
+    db.client
+        .select((QUERY_TABLE, name))
+        .logged(&format!("SELECT * FROM {QUERY_TABLE}:{name}"))
+        .await
+        .map_err(|e| QueryError {
+            message: e.to_string(),
+            kind: QueryErrorKind::DatabaseError(Some(e.into())),
+        })
+}
+
+/// Query names are used in record ids and typed on the command line, so they are kept to
+/// letters, digits, `-` and `_`.
+fn check_name(name: &str) -> Result<(), QueryError> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(QueryError {
+            kind: QueryErrorKind::InvalidName,
+            message: format!("Invalid query name '{name}', use only letters, digits, '-' and '_'"),
+        });
+    }
+    Ok(())
+}
+
+/// The command line arguments of `tmgr list` applying the filter.
+pub(super) fn filter_args(filter: &ListFilter) -> String {
+    let mut args = Vec::new();
+    if filter.all {
+        args.push("--all".to_string());
+    }
+    if let Some(sort) = filter.sort.as_ref().and_then(|s| s.to_possible_value()) {
+        args.push(format!("--sort {}", sort.get_name()));
+    }
+    for state in &filter.state {
+        args.push(format!("--state {state}"));
+    }
+    for priority in &filter.priority {
+        args.push(format!(
+            "--priority {}",
+            shell_words::quote(&priority.to_string().to_lowercase())
+        ));
+    }
+    if let Some(file) = &filter.file {
+        args.push(format!("--file {}", shell_words::quote(file)));
+    }
+    args.join(" ")
+}
+
+// --- Query Errors ---
+#[derive(Debug)]
+pub enum QueryErrorKind {
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    InvalidName,
+    ListError(ListError),
+    QueryAlreadyExists,
+    QueryNotFound,
+}
+
+#[derive(Debug)]
+pub struct QueryError {
+    kind: QueryErrorKind,
+    message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (query error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for QueryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            QueryErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            QueryErrorKind::ListError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for QueryErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            QueryErrorKind::InvalidName => write!(f, "Invalid name"),
+            QueryErrorKind::ListError(e) => write!(f, "{}", e.kind()),
+            QueryErrorKind::QueryAlreadyExists => write!(f, "Query already exists"),
+            QueryErrorKind::QueryNotFound => write!(f, "Query not found"),
+        }
+    }
+}
+
+impl ErrorCode for QueryErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            QueryErrorKind::DatabaseError(_) => "E-QUERY-001",
+            QueryErrorKind::InvalidName => "E-QUERY-002",
+            QueryErrorKind::ListError(e) => e.kind().code(),
+            QueryErrorKind::QueryAlreadyExists => "E-QUERY-004",
+            QueryErrorKind::QueryNotFound => "E-QUERY-005",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            QueryErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+            QueryErrorKind::InvalidName | QueryErrorKind::QueryAlreadyExists => {
+                ErrorCategory::Validation
+            }
+            QueryErrorKind::QueryNotFound => ErrorCategory::NotFound,
+            QueryErrorKind::ListError(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            QueryErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            QueryErrorKind::QueryAlreadyExists => Some("Use --force to replace the saved query"),
+            QueryErrorKind::QueryNotFound => Some("Run 'tmgr query list' to see the saved queries"),
+            QueryErrorKind::ListError(e) => e.kind().hint(),
+            _ => None,
+        }
+    }
+}
+
+impl From<QueryError> for TmgrError {
+    fn from(err: QueryError) -> Self {
+        TmgrError::new(TmgrErrorKind::QueryCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -0,0 +1,436 @@
+use super::super::{
+    db::{DB, Logged},
+    model::{
+        CodeLink, CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TaskPriority,
+        TaskState, TmgrError, TmgrErrorKind,
+    },
+};
+use super::{
+    link::{find_git_root, save_links},
+    move_task::transition_task,
+};
+use ignore::WalkBuilder;
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::read_to_string,
+    path::{Component, Path},
+};
+
+/// The comment markers that are turned into tasks.
+const MARKERS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+
+/// The tokens that start a comment, in the languages tmgr is likely to be pointed at.
+const COMMENT_TOKENS: [&str; 6] = ["//", "#", "/*", "*", "--", ";"];
+
+/// A TODO-style comment found in a source file.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct SourceComment {
+    pub(super) marker: String,
+    pub(super) text: String,
+    pub(super) line: usize,
+    pub(super) source_line: String,
+}
+
+/// The changes made by a scan.
+#[derive(Debug, Default)]
+pub(crate) struct ScanSummary {
+    files: usize,
+    comments: usize,
+    created: Vec<Task>,
+    updated: Vec<Task>,
+    completed: Vec<Task>,
+}
+
+impl ScanSummary {
+    #[allow(dead_code)]
+    pub(super) fn created(&self) -> &Vec<Task> {
+        &self.created
+    }
+
+    #[allow(dead_code)]
+    pub(super) fn updated(&self) -> &Vec<Task> {
+        &self.updated
+    }
+
+    #[allow(dead_code)]
+    pub(super) fn completed(&self) -> &Vec<Task> {
+        &self.completed
+    }
+}
+
+pub(crate) async fn run(db: &DB, path: String) -> Result<CommandResult<ScanSummary>, ScanError> {
+    let scan_path = Path::new(&path).canonicalize().map_err(|e| ScanError {
+        kind: ScanErrorKind::PathNotFound,
+        message: format!("Unable to scan '{path}': {e}"),
+    })?;
+    let scan_dir = if scan_path.is_dir() {
+        scan_path.as_path()
+    } else {
+        scan_path.parent().unwrap_or(&scan_path)
+    };
+    let root = find_git_root(scan_dir).unwrap_or(scan_dir.to_path_buf());
+    let root_string = root.to_string_lossy().to_string();
+    let scope = relative_path(&root, &scan_path);
+
+    let mut summary = ScanSummary::default();
+    let mut found: HashMap<String, CodeLink> = HashMap::new();
+    let mut comments: HashMap<String, SourceComment> = HashMap::new();
+    for entry in WalkBuilder::new(&scan_path).require_git(false).build() {
+        let entry = entry.map_err(|e| ScanError {
+            kind: ScanErrorKind::IOError,
+            message: e.to_string(),
+        })?;
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        // binary and non UTF-8 files cannot hold comments we can read
+        let Ok(content) = read_to_string(entry.path()) else {
+            continue;
+        };
+        summary.files += 1;
+        for (hash, comment, link) in file_comments(&root, entry.path(), &content) {
+            found.insert(hash.clone(), link);
+            comments.insert(hash, comment);
+        }
+    }
+    summary.comments = found.len();
+
+    let tasks: Vec<Task> = db
+        .client
+        .select("task")
+        .logged("SELECT * FROM task")
+        .await
+        .map_err(|e| ScanError {
+            kind: ScanErrorKind::DatabaseError(Some(e.into())),
+            message: "Failed to get all tasks".to_string(),
+        })?;
+    let mut existing: HashMap<String, Task> = tasks
+        .into_iter()
+        .filter_map(|t| t.comment_hash().clone().map(|hash| (hash, t)))
+        .collect();
+
+    let mut hashes: Vec<&String> = found.keys().collect();
+    hashes.sort_by(|a, b| {
+        (found[*a].path(), found[*a].start()).cmp(&(found[*b].path(), found[*b].start()))
+    });
+    for hash in hashes {
+        let link = &found[hash];
+        match existing.remove(hash) {
+            Some(task) => {
+                let current = task.code_links().iter().find(|l| l.path() == link.path());
+                if current != Some(link) {
+                    let mut code_links: Vec<CodeLink> = task
+                        .code_links()
+                        .iter()
+                        .filter(|l| l.path() != link.path())
+                        .cloned()
+                        .collect();
+                    code_links.push(link.clone());
+                    let task_id = task_id(&task)?;
+                    let task =
+                        save_links(db, &task_id, code_links)
+                            .await
+                            .map_err(|e| ScanError {
+                                message: e.to_string(),
+                                kind: ScanErrorKind::DatabaseError(Some(e.into())),
+                            })?;
+                    summary.updated.push(task);
+                }
+            }
+            None => {
+                let task = create_task(db, hash, &comments[hash], link).await?;
+                summary.created.push(task);
+            }
+        }
+    }
+
+    // tasks whose comment is gone from the scanned files are done
+    for task in existing.into_values() {
+        let in_scope = task.code_links().iter().any(|l| {
+            l.root() == root_string
+                && (scope.is_empty()
+                    || l.path() == scope
+                    || l.path().starts_with(&format!("{scope}/")))
+        });
+        if in_scope && !task.state().is_closed() {
+            let task = transition_task(db, &task, TaskState::Done)
+                .await
+                .map_err(|e| ScanError {
+                    message: e.to_string(),
+                    kind: ScanErrorKind::DatabaseError(Some(e.into())),
+                })?;
+            summary.completed.push(task);
+        }
+    }
+
+    Ok(CommandResult::new(report(&summary), summary))
+}
+
+/// Finds the TODO, FIXME and HACK comments in the content of a file.
+///
+/// A marker counts when it is a whole uppercase word directly following a comment token,
+/// as in `// TODO: text`, `# FIXME(name) text` or `/* HACK */`, outside of quotes.
+pub(super) fn extract_comments(content: &str) -> Vec<SourceComment> {
+    let mut comments = Vec::new();
+    for (index, source_line) in content.lines().enumerate() {
+        for marker in MARKERS {
+            let Some(position) = find_marker(source_line, marker) else {
+                continue;
+            };
+            let before = source_line[..position].trim_end();
+            // markers quoted in strings or inline code are not comments
+            let quoted = ['"', '`']
+                .iter()
+                .any(|q| before.matches(*q).count() % 2 == 1);
+            if quoted || !COMMENT_TOKENS.iter().any(|token| before.ends_with(token)) {
+                continue;
+            }
+            let mut text = source_line[position + marker.len()..].trim_start();
+            // an author or issue in parentheses, as in TODO(name):
+            if text.starts_with('(')
+                && let Some(end) = text.find(')')
+            {
+                text = text[end + 1..].trim_start();
+            }
+            let text = text
+                .trim_start_matches([':', '-'])
+                .trim_end_matches("*/")
+                .trim_end_matches("-->")
+                .trim();
+            comments.push(SourceComment {
+                marker: marker.to_string(),
+                text: text.to_string(),
+                line: index + 1,
+                source_line: source_line.to_string(),
+            });
+            break;
+        }
+    }
+    comments
+}
+
+/// The TODO-style comments of a file under `root`, each with the hash identifying it and the
+/// link to its line.
+pub(super) fn file_comments(
+    root: &Path,
+    path: &Path,
+    content: &str,
+) -> Vec<(String, SourceComment, CodeLink)> {
+    let root_string = root.to_string_lossy().to_string();
+    let file = relative_path(root, path);
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    extract_comments(content)
+        .into_iter()
+        .map(|comment| {
+            let key = format!("{}\0{}", comment.marker, comment.text);
+            let occurrence = occurrences.entry(key).or_default();
+            let hash = comment_hash(&file, &comment, *occurrence);
+            *occurrence += 1;
+            let link = CodeLink::new(
+                root_string.clone(),
+                file.clone(),
+                comment.line,
+                comment.line,
+                vec![comment.source_line.clone()],
+            );
+            (hash, comment, link)
+        })
+        .collect()
+}
+
+/// The position of `marker` in `line` as a whole word.
+fn find_marker(line: &str, marker: &str) -> Option<usize> {
+    line.match_indices(marker).map(|(i, _)| i).find(|i| {
+        let before = line[..*i].chars().next_back();
+        let after = line[i + marker.len()..].chars().next();
+        !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+            && !after.is_some_and(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+/// A hash identifying a comment across scans, even if lines are added above it.
+///
+/// It is built from the file, the marker, the text and the number of identical comments
+/// before it in the file, so it does not depend on the line number.
+pub(super) fn comment_hash(file: &str, comment: &SourceComment, occurrence: usize) -> String {
+    // FNV-1a, which unlike the standard library hasher is stable across releases
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in format!("{file}\0{}\0{}\0{occurrence}", comment.marker, comment.text).bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Creates the task of a comment, identified by its hash so that scans do not create it again.
+pub(super) async fn create_task(
+    db: &DB,
+    hash: &str,
+    comment: &SourceComment,
+    link: &CodeLink,
+) -> Result<Task, ScanError> {
+    let name = if comment.text.is_empty() {
+        format!("{} in {link}", comment.marker)
+    } else {
+        comment.text.clone()
+    };
+    let priority = match comment.marker.as_str() {
+        "FIXME" => TaskPriority::Medium,
+        _ => TaskPriority::Low,
+    };
+    let task = Task::builder()
+        .name(name)
+        .priority(priority)
+        .description(format!("{} comment in {link}", comment.marker))
+        .state(TaskState::Todo)
+        .code_links(vec![link.clone()])
+        .comment_hash(hash)
+        .build();
+    let task: Option<Task> = db
+        .client
+        .create("task")
+        .content(task)
+        .logged("CREATE task CONTENT $task")
+        .await
+        .map_err(|e| ScanError {
+            kind: ScanErrorKind::DatabaseError(Some(e.into())),
+            message: format!("Failed to create task for '{link}'"),
+        })?;
+    task.ok_or_else(|| ScanError {
+        kind: ScanErrorKind::DatabaseError(None),
+        message: format!("Database did not return a task for '{link}'"),
+    })
+}
+
+fn task_id(task: &Task) -> Result<String, ScanError> {
+    task.id().map_err(|e| ScanError {
+        kind: ScanErrorKind::BadTaskId,
+        message: e.to_string(),
+    })
+}
+
+/// The path relative to `root`, with `/` separators.
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+fn report(summary: &ScanSummary) -> String {
+    let mut lines = vec![format!(
+        "Scanned {} file(s) and found {} comment(s): {} task(s) created, {} updated, {} completed",
+        summary.files,
+        summary.comments,
+        summary.created.len(),
+        summary.updated.len(),
+        summary.completed.len()
+    )];
+    let describe = |task: &Task| {
+        format!(
+            "'{}' {}",
+            task.id().unwrap_or("Error getting ID".to_string()),
+            task.name()
+        )
+    };
+    lines.extend(
+        summary
+            .created
+            .iter()
+            .map(|t| format!("  created {}", describe(t))),
+    );
+    lines.extend(
+        summary
+            .updated
+            .iter()
+            .map(|t| format!("  updated {}", describe(t))),
+    );
+    lines.extend(
+        summary
+            .completed
+            .iter()
+            .map(|t| format!("  completed {}", describe(t))),
+    );
+    lines.join("\n")
+}
+
+// --- Scan Errors ---
+#[derive(Debug)]
+pub enum ScanErrorKind {
+    BadTaskId,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    IOError,
+    PathNotFound,
+}
+
+#[derive(Debug)]
+pub struct ScanError {
+    kind: ScanErrorKind,
+    message: String,
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (scan error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for ScanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ScanErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ScanErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScanErrorKind::BadTaskId => write!(f, "Bad task id"),
+            ScanErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            ScanErrorKind::IOError => write!(f, "IO error"),
+            ScanErrorKind::PathNotFound => write!(f, "Path not found"),
+        }
+    }
+}
+
+impl ErrorCode for ScanErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ScanErrorKind::BadTaskId => "E-SCAN-001",
+            ScanErrorKind::DatabaseError(_) => "E-SCAN-002",
+            ScanErrorKind::IOError => "E-SCAN-003",
+            ScanErrorKind::PathNotFound => "E-SCAN-004",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ScanErrorKind::BadTaskId | ScanErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+            ScanErrorKind::IOError => ErrorCategory::IO,
+            ScanErrorKind::PathNotFound => ErrorCategory::NotFound,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            ScanErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            _ => None,
+        }
+    }
+}
+
+impl From<ScanError> for TmgrError {
+    fn from(err: ScanError) -> Self {
+        TmgrError::new(TmgrErrorKind::ScanCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -0,0 +1,604 @@
+use super::super::{
+    cli::model::{ListFilter, ListSort, OutputFormat},
+    config::Config,
+    db::{DB, DBError, DBErrorKind},
+    model::{
+        CommandResult, ErrorCategory, ErrorCode, Task, TaskPriority, TaskState, TmgrError,
+        TmgrErrorKind,
+    },
+};
+use super::{
+    add::{AddError, AddErrorKind},
+    complete::{CompleteError, CompleteErrorKind},
+    delete::{DeleteError, DeleteErrorKind},
+    list::{ListError, ListErrorKind},
+    move_task::{MoveError, MoveErrorKind},
+    note::{self, NoteError, NoteErrorKind},
+    status::{Status, StatusError, StatusErrorKind},
+    update::{UpdateError, UpdateErrorKind},
+    view::{ViewError, ViewErrorKind},
+};
+use axum::{
+    Json, Router,
+    extract::{
+        Path, Query, Request, State,
+        rejection::{JsonRejection, QueryRejection},
+    },
+    http::{Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{fmt, sync::Arc};
+use tokio::{net::TcpListener, signal};
+
+/// Serves the REST API on an address until interrupted.
+pub(crate) async fn run(
+    db: &DB,
+    config: &Config,
+    bind: String,
+    token: Option<String>,
+) -> Result<CommandResult<String>, ServeError> {
+    let listener = TcpListener::bind(&bind).await.map_err(|e| ServeError {
+        kind: ServeErrorKind::BindError,
+        message: format!("Failed to bind to '{bind}': {e}"),
+    })?;
+    let address = listener.local_addr().map(|a| a.to_string()).unwrap_or(bind);
+    if token.is_none() && !is_loopback(&listener) {
+        eprintln!(
+            "Warning: serving on '{address}' without a token, anyone who can reach it can change tasks"
+        );
+    }
+    println!("Serving the tmgr API on http://{address} (press Ctrl-C to stop)");
+
+    axum::serve(listener, router(db.clone(), config.clone(), token))
+        .with_graceful_shutdown(async {
+            let _ = signal::ctrl_c().await;
+        })
+        .await
+        .map_err(|e| ServeError {
+            kind: ServeErrorKind::ServerError,
+            message: e.to_string(),
+        })?;
+
+    let message = format!("Stopped serving on '{address}'");
+    Ok(CommandResult::new(message.clone(), message))
+}
+
+fn is_loopback(listener: &TcpListener) -> bool {
+    listener.local_addr().is_ok_and(|a| a.ip().is_loopback())
+}
+
+struct ServerState {
+    db: DB,
+    config: Config,
+    token: Option<String>,
+}
+
+type AppState = State<Arc<ServerState>>;
+
+/// The routes of the API. Every route requires the bearer token, when one is given.
+pub(super) fn router(db: DB, config: Config, token: Option<String>) -> Router {
+    let state = Arc::new(ServerState { db, config, token });
+    Router::new()
+        .route("/status", get(status))
+        .route("/tasks", get(list_tasks).post(add_task))
+        .route(
+            "/tasks/{id}",
+            get(view_task).patch(update_task).delete(delete_task),
+        )
+        .route("/tasks/{id}/complete", post(complete_task))
+        .route("/tasks/{id}/move", post(move_task))
+        .route("/tasks/{id}/note", get(read_note).post(append_note))
+        .route_layer(middleware::from_fn(require_json))
+        .fallback(not_found)
+        .layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+async fn authorize(State(state): AppState, request: Request, next: Next) -> Response {
+    let Some(token) = &state.token else {
+        return next.run(request).await;
+    };
+    let given = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if given.is_some_and(|given| same_token(given, token)) {
+        next.run(request).await
+    } else {
+        let mut response = ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "A valid bearer token is required".to_string(),
+        )
+        .into_response();
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, "Bearer".parse().unwrap());
+        response
+    }
+}
+
+/// Refuses requests that change tasks unless they are sent as JSON. Browsers send forms to
+/// other sites without asking, but must ask before sending JSON, which the API never allows.
+async fn require_json(request: Request, next: Next) -> Response {
+    let changes = !matches!(*request.method(), Method::GET | Method::HEAD);
+    let json = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|v| v.trim().eq_ignore_ascii_case("application/json"));
+    if changes && !json {
+        return ApiError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Requests that change tasks must be sent with 'Content-Type: application/json'"
+                .to_string(),
+        )
+        .into_response();
+    }
+    next.run(request).await
+}
+
+/// Compares tokens in a time that does not depend on where they differ.
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn not_found() -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND, "No such endpoint".to_string())
+}
+
+async fn status(State(state): AppState) -> Result<Json<Status>, ApiError> {
+    let res = super::status::run(&state.db).await?;
+    Ok(Json(res.into_result()))
+}
+
+/// The filters of `GET /tasks`. Lists of states and priorities are comma separated.
+#[derive(Deserialize)]
+struct ListParams {
+    all: Option<bool>,
+    sort: Option<String>,
+    state: Option<String>,
+    priority: Option<String>,
+    file: Option<String>,
+}
+
+async fn list_tasks(
+    State(state): AppState,
+    params: Result<Query<ListParams>, QueryRejection>,
+) -> Result<Json<Vec<Value>>, ApiError> {
+    let Query(params) = params.map_err(|e| ApiError::bad_request(e.body_text()))?;
+    let filter = ListFilter {
+        all: params.all.unwrap_or_default(),
+        sort: params
+            .sort
+            .map(|s| ListSort::from_str(&s, true))
+            .transpose()
+            .map_err(ApiError::bad_request)?,
+        state: split_param(params.state, |s| TaskState::from_str(s, true))?,
+        priority: split_param(params.priority, str::parse::<TaskPriority>)?,
+        file: params.file,
+    };
+    let res = super::list::run(
+        &state.db,
+        state.config.list_filter(filter),
+        None,
+        OutputFormat::Json,
+    )
+    .await?;
+    Ok(Json(res.result().iter().map(task_json).collect()))
+}
+
+fn split_param<T>(
+    param: Option<String>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Vec<T>, ApiError> {
+    param
+        .iter()
+        .flat_map(|p| p.split(','))
+        .map(|s| parse(s.trim()).map_err(ApiError::bad_request))
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct AddBody {
+    name: String,
+    priority: Option<String>,
+    description: Option<String>,
+}
+
+async fn add_task(
+    State(state): AppState,
+    body: Result<Json<AddBody>, JsonRejection>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let Json(body) = body.map_err(|e| ApiError::bad_request(e.body_text()))?;
+    if body.name.trim().is_empty() {
+        return Err(ApiError::bad_request(
+            "The name of a task cannot be empty".to_string(),
+        ));
+    }
+    let priority = match body.priority {
+        Some(priority) => Some(priority.parse().map_err(ApiError::bad_request)?),
+        None => state.config.default_priority(),
+    };
+    let res = super::add::run(&state.db, body.name, priority, body.description).await?;
+    Ok((StatusCode::CREATED, Json(task_json(res.result()))))
+}
+
+async fn view_task(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let id = resolve(&state.db, &id).await?;
+    let res = super::view::run(&state.db, id, false, OutputFormat::Json).await?;
+    Ok(Json(task_json(res.result())))
+}
+
+#[derive(Deserialize)]
+struct UpdateBody {
+    name: Option<String>,
+    priority: Option<String>,
+    description: Option<String>,
+}
+
+async fn update_task(
+    State(state): AppState,
+    Path(id): Path<String>,
+    body: Result<Json<UpdateBody>, JsonRejection>,
+) -> Result<Json<Value>, ApiError> {
+    let Json(body) = body.map_err(|e| ApiError::bad_request(e.body_text()))?;
+    let priority = body
+        .priority
+        .map(|p| p.parse::<TaskPriority>())
+        .transpose()
+        .map_err(ApiError::bad_request)?;
+    let id = resolve(&state.db, &id).await?;
+    let res = super::update::run(&state.db, id, body.name, priority, body.description).await?;
+    Ok(Json(task_json(res.result())))
+}
+
+async fn delete_task(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let id = resolve(&state.db, &id).await?;
+    let res = super::delete::run(&state.db, id).await?;
+    Ok(Json(task_json(res.result())))
+}
+
+async fn complete_task(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let id = resolve(&state.db, &id).await?;
+    let res = super::complete::run(&state.db, id).await?;
+    Ok(Json(task_json(res.result())))
+}
+
+#[derive(Deserialize)]
+struct MoveBody {
+    state: String,
+}
+
+async fn move_task(
+    State(state): AppState,
+    Path(id): Path<String>,
+    body: Result<Json<MoveBody>, JsonRejection>,
+) -> Result<Json<Value>, ApiError> {
+    let Json(body) = body.map_err(|e| ApiError::bad_request(e.body_text()))?;
+    let to = TaskState::from_str(&body.state, true).map_err(ApiError::bad_request)?;
+    let id = resolve(&state.db, &id).await?;
+    let res = super::move_task::run(&state.db, id, to, &state.config.transitions()).await?;
+    Ok(Json(task_json(res.result())))
+}
+
+/// The note of a task, as markdown.
+#[derive(Serialize)]
+struct NoteJson {
+    id: String,
+    path: Option<String>,
+    content: String,
+}
+
+async fn read_note(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> Result<Json<NoteJson>, ApiError> {
+    let id = resolve(&state.db, &id).await?;
+    Ok(Json(note_json(&state.db, id).await?))
+}
+
+#[derive(Deserialize)]
+struct AppendBody {
+    text: String,
+}
+
+async fn append_note(
+    State(state): AppState,
+    Path(id): Path<String>,
+    body: Result<Json<AppendBody>, JsonRejection>,
+) -> Result<Json<NoteJson>, ApiError> {
+    let Json(body) = body.map_err(|e| ApiError::bad_request(e.body_text()))?;
+    // `-` would make the command read the entry from the stdin of the server
+    if body.text.trim() == "-" {
+        return Err(ApiError::bad_request(
+            "The entry cannot be '-', send the text of the entry instead".to_string(),
+        ));
+    }
+    let id = resolve(&state.db, &id).await?;
+    note::append(
+        &state.db,
+        id.clone(),
+        body.text,
+        state.config.default_template(),
+    )
+    .await?;
+    Ok(Json(note_json(&state.db, id).await?))
+}
+
+async fn note_json(db: &DB, id: String) -> Result<NoteJson, ApiError> {
+    let task = db.select_task_by_partial_id(&id).await?;
+    let content = note::read_note(&task)?;
+    Ok(NoteJson {
+        id,
+        path: task.work_note_path().clone(),
+        content,
+    })
+}
+
+/// Resolves a partial id to the full id of a task, so that an unknown or ambiguous id is
+/// reported as such rather than as a failure of the command.
+async fn resolve(db: &DB, id: &str) -> Result<String, ApiError> {
+    let task = db.select_task_by_partial_id(id).await?;
+    task.id()
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// A task as JSON, with the fields of `Task` and the id without its `task:` prefix.
+pub(super) fn task_json(task: &Task) -> Value {
+    let mut value = serde_json::to_value(task).unwrap_or_default();
+    if let Value::Object(fields) = &mut value {
+        if let Ok(id) = task.id() {
+            fields.insert("id".to_string(), Value::String(id));
+        }
+        // tasks created before states existed have none stored
+        fields.insert("state".to_string(), Value::String(task.state().to_string()));
+    }
+    value
+}
+
+/// An error response, with the error as a JSON body.
+#[derive(Debug)]
+pub(super) struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: String) -> Self {
+        Self { status, message }
+    }
+
+    fn bad_request(message: String) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+impl From<DBError> for ApiError {
+    fn from(err: DBError) -> Self {
+        ApiError::new(lookup_status(err.kind()), err.to_string())
+    }
+}
+
+/// The status of a failed lookup of a task by id.
+fn lookup_status(kind: &DBErrorKind) -> StatusCode {
+    match kind {
+        DBErrorKind::NoTasksFound => StatusCode::NOT_FOUND,
+        DBErrorKind::MultipleTasksFound => StatusCode::CONFLICT,
+        DBErrorKind::DatabaseError
+        | DBErrorKind::ExpectedOneTask
+        | DBErrorKind::IOError
+        | DBErrorKind::SerializationError
+        | DBErrorKind::UnableToDetermineTmgrExecutablePath => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<AddError> for ApiError {
+    fn from(err: AddError) -> Self {
+        let status = match err.kind() {
+            AddErrorKind::BadTaskId
+            | AddErrorKind::DatabaseError(_)
+            | AddErrorKind::EditorError
+            | AddErrorKind::FailedToCreateTask => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+impl From<CompleteError> for ApiError {
+    fn from(err: CompleteError) -> Self {
+        let status = match err.kind() {
+            CompleteErrorKind::TaskLookup(e) => lookup_status(e.kind()),
+            CompleteErrorKind::MoveError(e) => move_status(e.kind()),
+            CompleteErrorKind::BadTaskId => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+impl From<DeleteError> for ApiError {
+    fn from(err: DeleteError) -> Self {
+        let status = match &err.kind {
+            DeleteErrorKind::TaskLookup(e) => lookup_status(e.kind()),
+            DeleteErrorKind::BadTaskId
+            | DeleteErrorKind::DatabaseError(_)
+            | DeleteErrorKind::FailedToDeleteAttachments
+            | DeleteErrorKind::FailedToDeleteTask
+            | DeleteErrorKind::FailedToDeleteNote => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+impl From<ListError> for ApiError {
+    fn from(err: ListError) -> Self {
+        let status = match err.kind() {
+            ListErrorKind::UnknownColumn => StatusCode::BAD_REQUEST,
+            ListErrorKind::DatabaseError(_) | ListErrorKind::SerializationError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+impl From<MoveError> for ApiError {
+    fn from(err: MoveError) -> Self {
+        ApiError::new(move_status(err.kind()), err.to_string())
+    }
+}
+
+/// The status of a failed move of a task to another state.
+fn move_status(kind: &MoveErrorKind) -> StatusCode {
+    match kind {
+        MoveErrorKind::TaskLookup(e) => lookup_status(e.kind()),
+        MoveErrorKind::AlreadyInState | MoveErrorKind::TransitionNotAllowed => StatusCode::CONFLICT,
+        MoveErrorKind::BadTaskId | MoveErrorKind::DatabaseError(_) | MoveErrorKind::NoteError => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+impl From<NoteError> for ApiError {
+    fn from(err: NoteError) -> Self {
+        let status = match err.kind() {
+            NoteErrorKind::TaskLookup(e) => lookup_status(e.kind()),
+            NoteErrorKind::NoteNotFound => StatusCode::NOT_FOUND,
+            NoteErrorKind::EmptyEntry => StatusCode::BAD_REQUEST,
+            NoteErrorKind::BadTaskId
+            | NoteErrorKind::DatabaseError(_)
+            | NoteErrorKind::ExecutionError
+            | NoteErrorKind::FailedToOpenEditor
+            | NoteErrorKind::IOError
+            | NoteErrorKind::TemplateNotFound
+            | NoteErrorKind::UnableToDetermineTmgrExecutablePath
+            | NoteErrorKind::UnknownPlaceholder => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+impl From<StatusError> for ApiError {
+    fn from(err: StatusError) -> Self {
+        let status = match err.kind() {
+            StatusErrorKind::DatabaseError(_)
+            | StatusErrorKind::SerializationError(_)
+            | StatusErrorKind::UnableToDetermineTmgrExecutablePath
+            | StatusErrorKind::UnableToDetermineDbFilePath
+            | StatusErrorKind::UnableToDetermineConfigFilePath => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+impl From<UpdateError> for ApiError {
+    fn from(err: UpdateError) -> Self {
+        let status = match &err.kind {
+            UpdateErrorKind::TaskLookup(e) => lookup_status(e.kind()),
+            UpdateErrorKind::NoFieldsToUpdate => StatusCode::BAD_REQUEST,
+            UpdateErrorKind::BadTaskId
+            | UpdateErrorKind::DatabaseError(_)
+            | UpdateErrorKind::EditorError
+            | UpdateErrorKind::NoteError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+impl From<ViewError> for ApiError {
+    fn from(err: ViewError) -> Self {
+        let status = match err.kind() {
+            ViewErrorKind::TaskLookup(e) => lookup_status(e.kind()),
+            ViewErrorKind::BadTaskId
+            | ViewErrorKind::DatabaseError(_)
+            | ViewErrorKind::NoteError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::new(status, err.to_string())
+    }
+}
+
+// --- Serve Errors ---
+#[derive(Debug)]
+pub enum ServeErrorKind {
+    BindError,
+    ServerError,
+}
+
+#[derive(Debug)]
+pub struct ServeError {
+    kind: ServeErrorKind,
+    message: String,
+}
+
+impl fmt::Display for ServeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (serve error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for ServeError {}
+
+impl fmt::Display for ServeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServeErrorKind::BindError => write!(f, "Bind error"),
+            ServeErrorKind::ServerError => write!(f, "Server error"),
+        }
+    }
+}
+
+impl ErrorCode for ServeErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ServeErrorKind::BindError => "E-SERVE-001",
+            ServeErrorKind::ServerError => "E-SERVE-002",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ServeErrorKind::BindError | ServeErrorKind::ServerError => ErrorCategory::Network,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            ServeErrorKind::BindError => Some("Use --bind to pick another address or port"),
+            _ => None,
+        }
+    }
+}
+
+impl From<ServeError> for TmgrError {
+    fn from(err: ServeError) -> Self {
+        TmgrError::new(TmgrErrorKind::ServeCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
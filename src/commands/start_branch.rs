@@ -0,0 +1,151 @@
+use super::super::{
+    db::{DB, DBError},
+    model::{CommandResult, ErrorCategory, ErrorCode, Task, TaskState, TmgrError, TmgrErrorKind},
+};
+use super::{
+    git::{branch_name, git, repository_root},
+    move_task::{MoveError, transition_task},
+};
+use std::{env::current_dir, fmt, path::Path};
+
+pub(crate) async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, StartBranchError> {
+    let dir = current_dir().map_err(|e| StartBranchError {
+        kind: StartBranchErrorKind::IOError,
+        message: e.to_string(),
+    })?;
+    run_in(db, &dir, id).await
+}
+
+/// Creates and switches to the branch of a task in the repository holding `dir`.
+///
+/// A task that is still to do is then moved to in progress. The branch is created first, as
+/// it is the step most likely to fail, so a failed move leaves the user on the new branch.
+pub(super) async fn run_in(
+    db: &DB,
+    dir: &Path,
+    id: String,
+) -> Result<CommandResult<Task>, StartBranchError> {
+    let task = db
+        .select_task_by_partial_id(&id)
+        .await
+        .map_err(|e| StartBranchError {
+            message: e.to_string(),
+            kind: StartBranchErrorKind::TaskLookup(e),
+        })?;
+    let task_id = task.id().map_err(|e| StartBranchError {
+        kind: StartBranchErrorKind::BadTaskId,
+        message: e.to_string(),
+    })?;
+    let root = repository_root(dir).map_err(|e| StartBranchError {
+        kind: StartBranchErrorKind::GitError,
+        message: e.to_string(),
+    })?;
+
+    let branch = branch_name(&task_id, task.name());
+    git(&root, &["switch", "--create", &branch]).map_err(|e| StartBranchError {
+        kind: StartBranchErrorKind::GitError,
+        message: e.to_string(),
+    })?;
+
+    let task = if task.state() == TaskState::Todo {
+        transition_task(db, &task, TaskState::InProgress)
+            .await
+            .map_err(|e| StartBranchError {
+                message: format!(
+                    "Switched to new branch '{branch}', but task '{task_id}' was not moved to in progress: {e}"
+                ),
+                kind: StartBranchErrorKind::MoveError(e),
+            })?
+    } else {
+        task
+    };
+
+    Ok(CommandResult::new(
+        format!("Switched to new branch '{branch}' for task '{task_id}'"),
+        task,
+    ))
+}
+
+// --- Start Branch Errors ---
+#[derive(Debug)]
+pub enum StartBranchErrorKind {
+    BadTaskId,
+    GitError,
+    IOError,
+    MoveError(MoveError),
+    TaskLookup(DBError),
+}
+
+#[derive(Debug)]
+pub struct StartBranchError {
+    kind: StartBranchErrorKind,
+    message: String,
+}
+
+impl fmt::Display for StartBranchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (start branch error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for StartBranchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            StartBranchErrorKind::MoveError(e) => Some(e),
+            StartBranchErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for StartBranchErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StartBranchErrorKind::BadTaskId => write!(f, "Bad task id"),
+            StartBranchErrorKind::GitError => write!(f, "Git error"),
+            StartBranchErrorKind::IOError => write!(f, "IO error"),
+            StartBranchErrorKind::MoveError(e) => write!(f, "{}", e.kind()),
+            StartBranchErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+        }
+    }
+}
+
+impl ErrorCode for StartBranchErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            StartBranchErrorKind::BadTaskId => "E-BRANCH-001",
+            StartBranchErrorKind::GitError => "E-BRANCH-003",
+            StartBranchErrorKind::IOError => "E-BRANCH-004",
+            StartBranchErrorKind::MoveError(e) => e.kind().code(),
+            StartBranchErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            StartBranchErrorKind::BadTaskId => ErrorCategory::Internal,
+            StartBranchErrorKind::GitError | StartBranchErrorKind::IOError => ErrorCategory::IO,
+            StartBranchErrorKind::MoveError(e) => e.kind().category(),
+            StartBranchErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            StartBranchErrorKind::GitError => Some(
+                "Make sure git is installed and the current directory is inside a git repository",
+            ),
+            StartBranchErrorKind::MoveError(e) => e.kind().hint(),
+            StartBranchErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
+        }
+    }
+}
+
+impl From<StartBranchError> for TmgrError {
+    fn from(err: StartBranchError) -> Self {
+        TmgrError::new(TmgrErrorKind::StartBranchCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
@@ -1,16 +1,23 @@
 use super::super::{
-    db::DB,
-    model::{CommandResult, TmgrError, TmgrErrorKind},
+    config::Config,
+    db::{DB, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, DOCTOR_HINT, ErrorCategory, ErrorCode, TmgrError,
+        TmgrErrorKind, log_error,
+    },
 };
+use serde::Serialize;
 use std::{env::current_exe, fmt};
 
-pub(crate) async fn run(db: &DB) -> Result<CommandResult<Status>, StatusError> {
+/// Reports where tmgr keeps its files and how many tasks the database holds.
+pub async fn run(db: &DB) -> Result<CommandResult<Status>, StatusError> {
     let status = Status {
         tmgr_executable_path: current_exe()
             .map(|p| p.display().to_string())
-            .map_err(|_| StatusError {
+            .inspect_err(log_error)
+            .map_err(|e| StatusError {
                 kind: StatusErrorKind::UnableToDetermineTmgrExecutablePath,
-                message: "Unable to determine executable location".to_string(),
+                message: format!("Unable to determine executable location: {e}"),
             })?,
         db_file_path: DB::get_db_file_path()
             .map_err(|e| StatusError {
@@ -19,23 +26,56 @@ pub(crate) async fn run(db: &DB) -> Result<CommandResult<Status>, StatusError> {
             })?
             .display()
             .to_string(),
+        config_file_path: Config::get_config_file_path()
+            .map_err(|e| StatusError {
+                kind: StatusErrorKind::UnableToDetermineConfigFilePath,
+                message: e.to_string(),
+            })?
+            .display()
+            .to_string(),
         task_status: get_number_of_tasks(db).await?,
     };
 
     Ok(CommandResult::new(status.to_string(), status))
 }
 
-pub(crate) struct Status {
+/// Where tmgr keeps its files, and how many tasks the database holds.
+#[derive(Serialize)]
+pub struct Status {
     tmgr_executable_path: String,
     db_file_path: String,
+    config_file_path: String,
     task_status: TaskCount,
 }
 
+impl Status {
+    /// The path of the running tmgr executable.
+    pub fn tmgr_executable_path(&self) -> &str {
+        &self.tmgr_executable_path
+    }
+
+    /// The path of the database file.
+    pub fn db_file_path(&self) -> &str {
+        &self.db_file_path
+    }
+
+    /// The path of the global config file.
+    pub fn config_file_path(&self) -> &str {
+        &self.config_file_path
+    }
+
+    /// The number of tasks in the database.
+    pub fn task_status(&self) -> &TaskCount {
+        &self.task_status
+    }
+}
+
 impl fmt::Display for Status {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "File locations:")?;
         writeln!(f, "  tmgr executable: {}", self.tmgr_executable_path)?;
         writeln!(f, "  database: {}", self.db_file_path)?;
+        writeln!(f, "  config: {}", self.config_file_path)?;
         writeln!(f, "General statistics:")?;
         writeln!(f, "  completed tasks: {}", self.task_status.completed)?;
         writeln!(f, "  in progress tasks: {}", self.task_status.in_progress)?;
@@ -43,30 +83,58 @@ impl fmt::Display for Status {
     }
 }
 
-pub(crate) struct TaskCount {
+/// The number of completed, in progress and total tasks.
+#[derive(Serialize)]
+pub struct TaskCount {
     completed: i32,
     in_progress: i32,
     total: i32,
 }
 
+impl TaskCount {
+    /// The number of completed tasks.
+    pub fn completed(&self) -> i32 {
+        self.completed
+    }
+
+    /// The number of tasks that are not completed.
+    pub fn in_progress(&self) -> i32 {
+        self.in_progress
+    }
+
+    /// The number of tasks.
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+}
+
 async fn get_number_of_tasks(db: &DB) -> Result<TaskCount, StatusError> {
+    let query = "SELECT count() as total, count(completed_at != None) as completed  FROM task GROUP BY total;";
     let mut db_res = db
         .client
-        .query("SELECT count() as total, count(completed_at != None) as completed  FROM task GROUP BY total;")
+        .query(query)
+        .logged(query)
         .await
-        .map_err(|_| StatusError {
-            kind: StatusErrorKind::DatabaseError,
-            message: "Unable to determine number of tasks in current database".to_string()
+        .map_err(|e| StatusError {
+            kind: StatusErrorKind::DatabaseError(Some(e.into())),
+            message: "Unable to determine number of tasks in current database".to_string(),
         })?;
 
-    let total: Option<i32> = db_res.take("total").map_err(|_| StatusError {
-        kind: StatusErrorKind::SerializationError,
-        message: "Token 'total' not found in database response".to_string(),
-    })?;
-    let completed: Option<i32> = db_res.take("completed").map_err(|_| StatusError {
-        kind: StatusErrorKind::SerializationError,
-        message: "Token 'completed' not found in database response".to_string(),
-    })?;
+    let total: Option<i32> =
+        db_res
+            .take("total")
+            .inspect_err(log_error)
+            .map_err(|e| StatusError {
+                kind: StatusErrorKind::SerializationError(Some(e.into())),
+                message: "Token 'total' not found in database response".to_string(),
+            })?;
+    let completed: Option<i32> = db_res
+        .take("completed")
+        .inspect_err(log_error)
+        .map_err(|e| StatusError {
+            kind: StatusErrorKind::SerializationError(Some(e.into())),
+            message: "Token 'completed' not found in database response".to_string(),
+        })?;
 
     let mut task_count = TaskCount {
         completed: 0,
@@ -86,10 +154,11 @@ async fn get_number_of_tasks(db: &DB) -> Result<TaskCount, StatusError> {
 
 #[derive(Debug)]
 pub enum StatusErrorKind {
-    DatabaseError,
-    SerializationError,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    SerializationError(Option<Box<dyn std::error::Error + Send + Sync>>),
     UnableToDetermineTmgrExecutablePath,
     UnableToDetermineDbFilePath,
+    UnableToDetermineConfigFilePath,
 }
 
 #[derive(Debug)]
@@ -98,23 +167,73 @@ pub struct StatusError {
     message: String,
 }
 
+impl StatusError {
+    pub fn kind(&self) -> &StatusErrorKind {
+        &self.kind
+    }
+}
+
 impl fmt::Display for StatusError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} (status error: {})", self.message, self.kind)
     }
 }
 
+impl std::error::Error for StatusError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            StatusErrorKind::DatabaseError(Some(e))
+            | StatusErrorKind::SerializationError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for StatusErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            StatusErrorKind::DatabaseError => write!(f, "Database error"),
-            StatusErrorKind::SerializationError => write!(f, "Serialization error"),
+            StatusErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            StatusErrorKind::SerializationError(_) => write!(f, "Serialization error"),
             StatusErrorKind::UnableToDetermineTmgrExecutablePath => {
                 write!(f, "Unable to determine tmgr executable path")
             }
             StatusErrorKind::UnableToDetermineDbFilePath => {
                 write!(f, "Unable to determine database file path")
             }
+            StatusErrorKind::UnableToDetermineConfigFilePath => {
+                write!(f, "Unable to determine config file path")
+            }
+        }
+    }
+}
+
+impl ErrorCode for StatusErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            StatusErrorKind::DatabaseError(_) => "E-STATUS-001",
+            StatusErrorKind::SerializationError(_) => "E-STATUS-002",
+            StatusErrorKind::UnableToDetermineTmgrExecutablePath => "E-STATUS-003",
+            StatusErrorKind::UnableToDetermineDbFilePath => "E-STATUS-004",
+            StatusErrorKind::UnableToDetermineConfigFilePath => "E-STATUS-005",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            StatusErrorKind::DatabaseError(_) | StatusErrorKind::SerializationError(_) => {
+                ErrorCategory::Internal
+            }
+            StatusErrorKind::UnableToDetermineTmgrExecutablePath
+            | StatusErrorKind::UnableToDetermineDbFilePath
+            | StatusErrorKind::UnableToDetermineConfigFilePath => ErrorCategory::IO,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            StatusErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            StatusErrorKind::SerializationError(_) => Some(DOCTOR_HINT),
+            _ => None,
         }
     }
 }
@@ -122,5 +241,7 @@ impl fmt::Display for StatusErrorKind {
 impl From<StatusError> for TmgrError {
     fn from(err: StatusError) -> Self {
         TmgrError::new(TmgrErrorKind::StatusCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
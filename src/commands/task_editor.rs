@@ -0,0 +1,194 @@
+use super::super::model::TaskPriority;
+use super::note::open_note;
+use std::{
+    env::temp_dir,
+    fmt,
+    fs::{read_to_string, remove_file, write},
+    path::Path,
+    process,
+    str::FromStr,
+};
+
+/// The editable fields of a task, as written in the editor document.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct TaskFields {
+    pub(super) name: String,
+    pub(super) priority: TaskPriority,
+    pub(super) description: Option<String>,
+}
+
+/// Opens `$EDITOR` on a document describing the given fields and returns the edited fields.
+///
+/// If the document is invalid, the editor is opened again with the errors added as
+/// comments at the top. Emptying the document cancels the edit.
+pub(super) fn edit_task(initial: &TaskFields) -> Result<TaskFields, TaskEditorError> {
+    let path = temp_dir().join(format!("tmgr-task-{}.md", process::id()));
+    let res = edit_task_with(initial, &path, |path| {
+        open_note(&path.to_string_lossy())
+            .map(|_| ())
+            .map_err(|e| TaskEditorError {
+                kind: TaskEditorErrorKind::FailedToOpenEditor,
+                message: e.to_string(),
+            })
+    });
+    let _ = remove_file(&path);
+    res
+}
+
+/// Runs the edit loop on the document at `path`, using `open` to let the user edit it.
+pub(super) fn edit_task_with(
+    initial: &TaskFields,
+    path: &Path,
+    mut open: impl FnMut(&Path) -> Result<(), TaskEditorError>,
+) -> Result<TaskFields, TaskEditorError> {
+    let mut document = render(initial);
+    loop {
+        write(path, &document).map_err(|e| TaskEditorError {
+            kind: TaskEditorErrorKind::IOError,
+            message: e.to_string(),
+        })?;
+        open(path)?;
+        let edited = read_to_string(path).map_err(|e| TaskEditorError {
+            kind: TaskEditorErrorKind::IOError,
+            message: e.to_string(),
+        })?;
+        if edited.trim().is_empty() {
+            return Err(TaskEditorError {
+                kind: TaskEditorErrorKind::Cancelled,
+                message: "Edit cancelled, the document was empty".to_string(),
+            });
+        }
+        match parse(&edited) {
+            Ok(fields) => return Ok(fields),
+            Err(errors) => document = with_errors(&edited, &errors),
+        }
+    }
+}
+
+/// Renders the fields as a document with front matter for the name and priority, and
+/// the description as the body.
+pub(super) fn render(fields: &TaskFields) -> String {
+    let levels: Vec<String> = TaskPriority::known_levels()
+        .iter()
+        .map(String::from)
+        .collect();
+    format!(
+        "# Edit the task below, then save and close the editor.\n\
+         # Lines starting with '#' above the description are ignored.\n\
+         # Priorities: {} (or p0-p4, !-!!!!)\n\
+         # Leave the file empty to cancel.\n\
+         ---\n\
+         name: {}\n\
+         priority: {}\n\
+         ---\n\
+         {}\n",
+        levels.join(", "),
+        fields.name,
+        fields.priority,
+        fields.description.as_deref().unwrap_or_default()
+    )
+}
+
+/// Parses an edited document, returning every validation error found.
+pub(super) fn parse(document: &str) -> Result<TaskFields, Vec<String>> {
+    let mut errors = Vec::new();
+    let mut lines = document
+        .lines()
+        .skip_while(|line| line.trim().is_empty() || line.starts_with('#'));
+    if lines.next().map(str::trim) != Some("---") {
+        return Err(vec![
+            "the document must start with a '---' line opening the front matter".to_string(),
+        ]);
+    }
+
+    let mut name = None;
+    let mut priority = None;
+    let mut closed = false;
+    for line in lines.by_ref() {
+        let line = line.trim();
+        if line == "---" {
+            closed = true;
+            break;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once(':') {
+            Some((key, value)) => match key.trim() {
+                "name" => name = Some(value.trim().to_string()),
+                "priority" => priority = Some(value.trim().to_string()),
+                key => errors.push(format!("unknown field '{key}'")),
+            },
+            None => errors.push(format!("expected 'field: value', but got '{line}'")),
+        }
+    }
+    if !closed {
+        errors.push("the front matter must be closed with a '---' line".to_string());
+    }
+
+    let name = name.unwrap_or_default();
+    if name.is_empty() {
+        errors.push("name is required".to_string());
+    }
+    let priority = match priority.as_deref() {
+        None | Some("") => TaskPriority::default(),
+        Some(priority) => TaskPriority::from_str(priority).unwrap_or_else(|e| {
+            errors.push(e);
+            TaskPriority::default()
+        }),
+    };
+    let description = lines.collect::<Vec<&str>>().join("\n").trim().to_string();
+
+    if errors.is_empty() {
+        Ok(TaskFields {
+            name,
+            priority,
+            description: (!description.is_empty()).then_some(description),
+        })
+    } else {
+        Err(errors)
+    }
+}
+
+/// Replaces the error comments of a document with the given errors, keeping the user's edits.
+fn with_errors(document: &str, errors: &[String]) -> String {
+    let mut with_errors: String = errors.iter().map(|e| format!("# ERROR: {e}\n")).collect();
+    document
+        .lines()
+        .filter(|line| !line.starts_with("# ERROR: "))
+        .for_each(|line| {
+            with_errors.push_str(line);
+            with_errors.push('\n');
+        });
+    with_errors
+}
+
+// --- Task Editor Errors ---
+#[derive(Debug)]
+pub enum TaskEditorErrorKind {
+    Cancelled,
+    FailedToOpenEditor,
+    IOError,
+}
+
+#[derive(Debug)]
+pub struct TaskEditorError {
+    kind: TaskEditorErrorKind,
+    message: String,
+}
+
+impl fmt::Display for TaskEditorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (task editor error: {})", self.message, self.kind)
+    }
+}
+
+impl fmt::Display for TaskEditorErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TaskEditorErrorKind::Cancelled => write!(f, "Cancelled"),
+            TaskEditorErrorKind::FailedToOpenEditor => write!(f, "Failed to open editor"),
+            TaskEditorErrorKind::IOError => write!(f, "IO error"),
+        }
+    }
+}
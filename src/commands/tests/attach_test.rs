@@ -0,0 +1,154 @@
+use super::super::super::{
+    db,
+    model::{AttachmentKind, Task},
+};
+use super::super::{
+    attach::{self, copy_into, task_attachments_dir},
+    delete,
+};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, read_to_string, remove_dir_all, write},
+    path::Path,
+};
+
+#[tokio::test]
+async fn given_url_when_attaching_then_url_should_be_recorded_on_the_task() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = attach::run(
+        &db,
+        id.clone(),
+        "https://example.com/spec".to_string(),
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        res.message(),
+        format!("Successfully attached 'https://example.com/spec' to task '{id}' as attachment 1")
+    );
+    let attachments = res.result().attachments();
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0].kind(), &AttachmentKind::Url);
+    assert_eq!(attachments[0].target(), "https://example.com/spec");
+
+    let res = attach::run(
+        &db,
+        id.clone(),
+        "https://example.com/spec".to_string(),
+        false,
+    )
+    .await;
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        format!(
+            "'https://example.com/spec' is already attached to task '{id}' (attach error: Already attached)"
+        )
+    );
+}
+
+#[tokio::test]
+async fn given_file_when_attaching_then_file_should_be_copied_and_removed_on_delete() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    write(file.path(), "log output").unwrap();
+
+    let res = attach::run(
+        &db,
+        id.clone(),
+        file.path().to_string_lossy().to_string(),
+        false,
+    )
+    .await
+    .unwrap();
+    let attachment = &res.result().attachments()[0];
+    assert_eq!(attachment.kind(), &AttachmentKind::Copy);
+    assert!(
+        attachment
+            .target()
+            .starts_with(&*task_attachments_dir(&id).unwrap().to_string_lossy())
+    );
+    assert_eq!(read_to_string(attachment.target()).unwrap(), "log output");
+
+    delete::run(&db, id.clone()).await.unwrap();
+    assert!(!task_attachments_dir(&id).unwrap().exists());
+}
+
+#[tokio::test]
+async fn given_file_when_linking_then_file_should_be_referenced_in_place() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+
+    let res = attach::run(
+        &db,
+        id.clone(),
+        file.path().to_string_lossy().to_string(),
+        true,
+    )
+    .await
+    .unwrap();
+    let attachment = &res.result().attachments()[0];
+    assert_eq!(attachment.kind(), &AttachmentKind::Path);
+    assert_eq!(
+        Path::new(attachment.target()),
+        file.path().canonicalize().unwrap()
+    );
+    assert!(!task_attachments_dir(&id).unwrap().exists());
+}
+
+#[tokio::test]
+async fn given_missing_file_when_attaching_then_error_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = attach::run(&db, id, "does/not/exist.txt".to_string(), false).await;
+    assert!(
+        res.unwrap_err()
+            .to_string()
+            .ends_with("(attach error: File not found)")
+    );
+}
+
+#[test]
+fn given_file_with_the_same_name_when_copying_then_copy_should_be_renamed() {
+    let dir = temp_dir().join(format!("tmgr-attach-{}", std::process::id()));
+    let source_dir = dir.join("source");
+    create_dir_all(&source_dir).unwrap();
+    let source = source_dir.join("report.txt");
+    write(&source, "report").unwrap();
+
+    let first = copy_into(&dir.join("task"), &source).unwrap();
+    let second = copy_into(&dir.join("task"), &source).unwrap();
+    assert!(first.ends_with("report.txt"));
+    assert!(second.ends_with("report-1.txt"));
+
+    remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,75 @@
+use super::super::super::{
+    db,
+    model::{Task, TaskState},
+};
+use super::super::board;
+use std::collections::BTreeMap;
+
+#[tokio::test]
+async fn given_tasks_in_different_states_when_showing_board_then_open_columns_should_be_shown() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    for (name, state) in [
+        ("first todo", TaskState::Todo),
+        ("second todo", TaskState::Todo),
+        ("blocked task", TaskState::Blocked),
+        ("done task", TaskState::Done),
+    ] {
+        let task = Task::builder().name(name).state(state).build();
+        let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    }
+
+    let res = board::run(&db, false, &TaskState::default_transitions())
+        .await
+        .unwrap();
+    let board = res.message();
+    assert!(board.contains("todo (2)"));
+    assert!(board.contains("in-progress (0)"));
+    assert!(board.contains("blocked (1)"));
+    assert!(board.contains("blocked task"));
+    assert!(!board.contains("done (1)"));
+    assert!(!board.contains("done task"));
+}
+
+#[tokio::test]
+async fn given_done_task_when_showing_full_board_then_closed_columns_should_be_shown() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let task = Task::builder()
+        .name("done task")
+        .state(TaskState::Done)
+        .build();
+    let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+
+    let res = board::run(&db, true, &TaskState::default_transitions())
+        .await
+        .unwrap();
+    assert!(res.message().contains("done (1)"));
+    assert!(res.message().contains("cancelled (0)"));
+    assert!(res.message().contains("done task"));
+}
+
+#[tokio::test]
+async fn given_workflow_without_some_states_when_showing_board_then_only_used_columns_should_be_shown()
+ {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let task = Task::builder()
+        .name("blocked task")
+        .state(TaskState::Blocked)
+        .build();
+    let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    let transitions = BTreeMap::from([
+        (TaskState::Todo, vec![TaskState::InProgress]),
+        (TaskState::InProgress, vec![TaskState::Done]),
+        (TaskState::Blocked, vec![TaskState::Todo]),
+        (TaskState::Done, vec![]),
+    ]);
+
+    let res = board::run(&db, true, &transitions).await.unwrap();
+    let board = res.message();
+    assert!(board.contains("todo (0)"));
+    assert!(board.contains("in-progress (0)"));
+    assert!(board.contains("done (0)"));
+    // blocked is not part of the workflow, but still holds a task
+    assert!(board.contains("blocked (1)"));
+    assert!(!board.contains("in-review"));
+    assert!(!board.contains("cancelled"));
+}
@@ -1,6 +1,6 @@
 use super::super::super::{
     db,
-    model::{Task, TaskPriority},
+    model::{Task, TaskPriority, TaskState},
 };
 use super::super::complete;
 
@@ -11,7 +11,7 @@ async fn given_no_existing_tasks_when_completing_a_task_then_no_task_should_be_c
     assert!(res.is_err());
     assert_eq!(
         res.unwrap_err().to_string(),
-        "Task starting with id 'randomID' was not found (db error: No tasks found) (complete error: Database error)"
+        "Task starting with id 'randomID' was not found (db error: No tasks found) (complete error: No tasks found)"
     );
 }
 
@@ -68,3 +68,15 @@ async fn given_existing_tasks_when_completing_a_task_then_the_other_parts_of_the
         "This is a description of the task"
     );
 }
+
+#[tokio::test]
+async fn given_in_progress_task_when_completing_then_the_task_should_move_to_done() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let task = Task::builder().state(TaskState::InProgress).build();
+    let new_task: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = complete::run(&db, id).await.unwrap();
+    assert_eq!(res.result().state(), TaskState::Done);
+    assert_eq!(res.result().state_history().len(), 1);
+}
@@ -0,0 +1,69 @@
+use super::super::super::{
+    cli::model::CompletionShell,
+    db,
+    model::{Task, TaskState},
+};
+use super::super::completions::{self, id_commands, script};
+use surrealdb::sql::Datetime;
+
+#[test]
+fn given_cli_when_finding_id_commands_then_only_commands_taking_an_id_should_be_returned() {
+    let commands = id_commands();
+    assert!(commands.contains(&"complete".to_string()));
+    assert!(commands.contains(&"view".to_string()));
+    assert!(commands.contains(&"start-branch".to_string()));
+    assert!(!commands.contains(&"list".to_string()));
+    assert!(!commands.contains(&"add".to_string()));
+}
+
+#[test]
+fn given_each_shell_when_generating_script_then_ids_should_be_completed_from_the_database() {
+    for shell in [
+        CompletionShell::Bash,
+        CompletionShell::Zsh,
+        CompletionShell::Fish,
+        CompletionShell::Powershell,
+    ] {
+        let script = script(&shell).unwrap();
+        assert!(
+            script.contains("tmgr __complete-ids"),
+            "{shell:?} script should complete ids"
+        );
+        assert!(script.contains("start-branch"));
+    }
+
+    let zsh = script(&CompletionShell::Zsh).unwrap();
+    assert!(!zsh.contains("(can be partial):_default'"));
+    assert!(zsh.contains("':id -- The id of the task to view (can be partial):_tmgr_ids'"));
+    assert!(zsh.trim_end().ends_with("fi"));
+}
+
+#[tokio::test]
+async fn given_tasks_when_completing_ids_then_in_progress_ids_with_names_should_be_printed() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    for (name, completed) in [("Open\ttask", false), ("Done task", true)] {
+        let mut builder = Task::builder()
+            .name(name.to_string())
+            .state(TaskState::Todo);
+        if completed {
+            builder = builder.completed_at(Datetime::default());
+        }
+        let _: Vec<Task> = db
+            .client
+            .insert("task")
+            .content(builder.build())
+            .await
+            .unwrap();
+    }
+
+    let res = completions::ids(&db, None).await.unwrap();
+    assert_eq!(res.result().len(), 1);
+    let id = res.result()[0].id().unwrap();
+    assert_eq!(res.message(), format!("{id}\tOpen task"));
+
+    let prefix = id[..3].to_uppercase();
+    let res = completions::ids(&db, Some(prefix)).await.unwrap();
+    assert_eq!(res.result().len(), 1);
+    let res = completions::ids(&db, Some("-".to_string())).await.unwrap();
+    assert!(res.message().is_empty());
+}
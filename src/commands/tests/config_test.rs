@@ -0,0 +1,52 @@
+use super::super::config::set_in;
+use std::fs::{read_to_string, write};
+use tempfile::tempdir;
+
+#[test]
+fn given_valid_value_when_setting_then_only_chosen_file_should_change() {
+    let dir = tempdir().expect("Failed to create temporary directory");
+    let global = dir.path().join("config").join("config.toml");
+    let project = dir.path().join(".tmgr.toml");
+
+    let file = set_in(&global, &project, false, "list.sort", "name").unwrap();
+    assert_eq!(file, global);
+    assert_eq!(
+        read_to_string(&global).unwrap(),
+        "[list]\nsort = \"name\"\n"
+    );
+    assert!(!project.exists());
+
+    set_in(&global, &project, true, "display.color", "false").unwrap();
+    assert_eq!(
+        read_to_string(&project).unwrap(),
+        "[display]\ncolor = false\n"
+    );
+}
+
+#[test]
+fn given_invalid_value_when_setting_then_file_should_be_untouched() {
+    let dir = tempdir().expect("Failed to create temporary directory");
+    let global = dir.path().join("config.toml");
+    let project = dir.path().join(".tmgr.toml");
+    write(&global, "[priorities]\nblocker = 35\n").unwrap();
+
+    // the priority is defined in the other file
+    set_in(&global, &project, true, "add.priority", "blocker").unwrap();
+
+    let res = set_in(&global, &project, true, "add.priority", "urgent");
+    assert!(
+        res.unwrap_err()
+            .to_string()
+            .contains("Invalid key 'add.priority'")
+    );
+    assert!(set_in(&global, &project, false, "list.sorting", "name").is_err());
+    assert!(set_in(&global, &project, false, "list.columns", "[\"owner\"]").is_err());
+    assert_eq!(
+        read_to_string(&project).unwrap(),
+        "[add]\npriority = \"blocker\"\n"
+    );
+    assert_eq!(
+        read_to_string(&global).unwrap(),
+        "[priorities]\nblocker = 35\n"
+    );
+}
@@ -9,7 +9,7 @@ async fn given_no_existing_tasks_when_deleting_a_task_then_no_task_should_be_del
     assert!(res.is_err());
     assert_eq!(
         res.unwrap_err().to_string(),
-        "Task starting with id 'randomID' was not found (db error: No tasks found) (delete error: Database error)"
+        "Task starting with id 'randomID' was not found (db error: No tasks found) (delete error: No tasks found)"
     );
 }
 
@@ -0,0 +1,167 @@
+use super::super::super::{
+    db,
+    model::{Task, TaskPriority},
+};
+use super::super::doctor::{self, DoctorIssueKind};
+use std::fs::File;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn given_consistent_store_when_diagnosing_then_no_issues_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let notes_dir = tempdir().expect("Failed to create temporary directory");
+    let note_path = notes_dir.path().join("note.md");
+    File::create(&note_path).expect("Failed to create note");
+    let task = Task::builder()
+        .name("healthy")
+        .work_note_path(note_path.to_string_lossy())
+        .build();
+    let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+
+    let issues = doctor::diagnose(&db, notes_dir.path()).await.unwrap();
+    assert!(issues.is_empty());
+}
+
+#[tokio::test]
+async fn given_task_with_missing_note_file_when_repairing_then_note_path_should_be_cleared() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let notes_dir = tempdir().expect("Failed to create temporary directory");
+    let task = Task::builder().work_note_path("/does/not/exist.md").build();
+    let inserted: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    let id = inserted[0].id().unwrap();
+
+    let mut issues = doctor::diagnose(&db, notes_dir.path()).await.unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(*issues[0].kind(), DoctorIssueKind::DanglingNotePath);
+    assert_eq!(issues[0].subject(), id);
+
+    doctor::repair(&db, notes_dir.path(), &mut issues)
+        .await
+        .unwrap();
+    assert!(issues[0].fixed().is_some());
+    let task: Option<Task> = db.client.select(("task", id)).await.unwrap();
+    assert!(task.unwrap().work_note_path().is_none());
+}
+
+#[tokio::test]
+async fn given_note_without_task_when_repairing_then_note_should_be_quarantined() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let notes_dir = tempdir().expect("Failed to create temporary directory");
+    let note_path = notes_dir.path().join("orphan.md");
+    File::create(&note_path).expect("Failed to create note");
+
+    let mut issues = doctor::diagnose(&db, notes_dir.path()).await.unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(*issues[0].kind(), DoctorIssueKind::OrphanedNote);
+
+    doctor::repair(&db, notes_dir.path(), &mut issues)
+        .await
+        .unwrap();
+    assert!(!note_path.exists());
+    assert!(
+        notes_dir
+            .path()
+            .join("quarantine")
+            .join("orphan.md")
+            .exists()
+    );
+}
+
+#[tokio::test]
+async fn given_task_with_invalid_priority_when_repairing_then_priority_should_be_reset() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let notes_dir = tempdir().expect("Failed to create temporary directory");
+    db.client
+        .query("CREATE task:bad SET name = 'bad', priority = 'urgent', created_at = time::now()")
+        .await
+        .unwrap();
+
+    let mut issues = doctor::diagnose(&db, notes_dir.path()).await.unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(*issues[0].kind(), DoctorIssueKind::InvalidPriority);
+    assert_eq!(issues[0].subject(), "bad");
+
+    doctor::repair(&db, notes_dir.path(), &mut issues)
+        .await
+        .unwrap();
+    let task: Option<Task> = db.client.select(("task", "bad")).await.unwrap();
+    assert_eq!(*task.unwrap().priority(), TaskPriority::default());
+}
+
+#[tokio::test]
+async fn given_undeserializable_task_when_repairing_then_record_should_be_quarantined() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let notes_dir = tempdir().expect("Failed to create temporary directory");
+    db.client
+        .query("CREATE task:broken SET name = 42, priority = 'Low', created_at = time::now()")
+        .await
+        .unwrap();
+
+    let mut issues = doctor::diagnose(&db, notes_dir.path()).await.unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(*issues[0].kind(), DoctorIssueKind::UndeserializableRecord);
+
+    doctor::repair(&db, notes_dir.path(), &mut issues)
+        .await
+        .unwrap();
+    let remaining: Vec<String> = db
+        .client
+        .query("SELECT VALUE <string> id FROM task")
+        .await
+        .unwrap()
+        .take(0)
+        .unwrap();
+    assert!(remaining.is_empty());
+    let quarantined: Vec<String> = db
+        .client
+        .query("SELECT VALUE original_id FROM quarantine")
+        .await
+        .unwrap()
+        .take(0)
+        .unwrap();
+    assert_eq!(quarantined, vec!["task:broken".to_string()]);
+}
+
+#[tokio::test]
+async fn given_tasks_with_non_string_ids_when_repairing_then_records_should_be_quarantined() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let notes_dir = tempdir().expect("Failed to create temporary directory");
+    db.client
+        .query("CREATE task:1 SET name = 'numeric', priority = 'Low', created_at = time::now()")
+        .query(
+            "CREATE task:[1, 2] SET name = 'complex', priority = 'Low', created_at = time::now()",
+        )
+        .await
+        .unwrap();
+
+    let mut issues = doctor::diagnose(&db, notes_dir.path()).await.unwrap();
+    assert_eq!(issues.len(), 2);
+    assert!(
+        issues
+            .iter()
+            .all(|issue| *issue.kind() == DoctorIssueKind::BadTaskId)
+    );
+    let mut subjects: Vec<&str> = issues.iter().map(|issue| issue.subject()).collect();
+    subjects.sort();
+    assert_eq!(subjects, vec!["task:1", "task:[1, 2]"]);
+
+    doctor::repair(&db, notes_dir.path(), &mut issues)
+        .await
+        .unwrap();
+    let remaining: Vec<String> = db
+        .client
+        .query("SELECT VALUE <string> id FROM task")
+        .await
+        .unwrap()
+        .take(0)
+        .unwrap();
+    assert!(remaining.is_empty());
+    let quarantined: Vec<String> = db
+        .client
+        .query("SELECT VALUE original_id FROM quarantine")
+        .await
+        .unwrap()
+        .take(0)
+        .unwrap();
+    assert_eq!(quarantined.len(), 2);
+}
@@ -0,0 +1,207 @@
+use super::super::super::{
+    db,
+    model::{Task, TaskState},
+};
+use super::super::{
+    git::{branch_name, closing_references, commits_referencing, git, mentions},
+    git_hook, start_branch,
+};
+use std::{
+    fs::{read_to_string, write},
+    path::Path,
+};
+use tempfile::{TempDir, tempdir};
+
+/// A temporary git repository with a single empty commit.
+fn repository() -> TempDir {
+    let repo = tempdir().expect("Failed to create temporary directory");
+    git(repo.path(), &["init", "--quiet", "--initial-branch=main"]).unwrap();
+    commit(repo.path(), "Initial commit");
+    repo
+}
+
+fn commit(dir: &Path, message: &str) {
+    git(
+        dir,
+        &[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "--message",
+            message,
+        ],
+    )
+    .unwrap();
+}
+
+async fn insert_task(db: &db::DB, name: &str) -> String {
+    let task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(
+            Task::builder()
+                .name(name.to_string())
+                .state(TaskState::Todo)
+                .build(),
+        )
+        .await
+        .unwrap();
+    task[0].id().unwrap()
+}
+
+#[test]
+fn given_task_names_when_naming_a_branch_then_name_should_be_a_slug_after_the_id() {
+    assert_eq!(
+        branch_name("1w08w2", "Fix the login page!"),
+        "1w08w2-fix-the-login-page"
+    );
+    assert_eq!(
+        branch_name("1w08w2", "  Ünïcode & co  "),
+        "1w08w2-n-code-co"
+    );
+    assert_eq!(branch_name("1w08w2", "???"), "1w08w2");
+    assert!(branch_name("1w08w2", &"long name ".repeat(20)).len() <= "1w08w2-".len() + 40);
+}
+
+#[test]
+fn given_commit_messages_when_checking_mentions_then_only_whole_id_prefixes_should_match() {
+    let id = "1w08w2abc9";
+    assert!(mentions("Work on 1w08w2abc9", id, 6));
+    assert!(mentions("Refs #1w08w2", id, 6));
+    assert!(!mentions("Refs 1w08w", id, 6));
+    assert!(!mentions("Refs 1w08w2x", id, 6));
+}
+
+#[test]
+fn given_commit_messages_when_finding_closing_references_then_closed_ids_should_be_returned() {
+    assert_eq!(
+        closing_references("Add login\n\nCloses 1w08w2, fixes: 9x8y7z\nrelated to abc123"),
+        vec!["1w08w2", "9x8y7z"]
+    );
+    assert_eq!(
+        closing_references("Resolved 1w08w2 and closed 1w08w2"),
+        vec!["1w08w2"]
+    );
+    assert!(closing_references("# closes 1w08w2\nNo references").is_empty());
+    assert!(closing_references("Fixes a typo, closes the bug").is_empty());
+}
+
+#[test]
+fn given_commits_referencing_a_task_when_listing_then_only_those_commits_should_be_returned() {
+    let repo = repository();
+    commit(repo.path(), "Start the login page\n\nPart of 1w08w2abc9");
+    commit(repo.path(), "Unrelated change to 1w08w2x");
+    commit(repo.path(), "Closes 1W08W2ABC9");
+
+    let commits = commits_referencing(repo.path(), "1w08w2abc9", 6).unwrap();
+    let subjects: Vec<&str> = commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects, vec!["Closes 1W08W2ABC9", "Start the login page"]);
+    assert_eq!(commits[0].author, "Test");
+    assert!(commits[0].to_string().starts_with(&format!(
+        "{} Closes 1W08W2ABC9 (Test, ",
+        &commits[0].hash[..8]
+    )));
+}
+
+#[tokio::test]
+async fn given_task_in_todo_when_starting_a_branch_then_branch_should_be_created_and_task_started()
+{
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let repo = repository();
+    let id = insert_task(&db, "Fix the login page").await;
+
+    let res = start_branch::run_in(&db, repo.path(), id.clone())
+        .await
+        .unwrap();
+
+    let branch = git(repo.path(), &["branch", "--show-current"]).unwrap();
+    assert_eq!(branch, format!("{id}-fix-the-login-page"));
+    assert_eq!(res.result().state(), TaskState::InProgress);
+    assert!(start_branch::run_in(&db, repo.path(), id).await.is_err());
+}
+
+#[tokio::test]
+async fn given_directory_outside_git_when_starting_a_branch_then_error_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let dir = tempdir().expect("Failed to create temporary directory");
+    let id = insert_task(&db, "Fix the login page").await;
+
+    assert!(start_branch::run_in(&db, dir.path(), id).await.is_err());
+}
+
+#[test]
+fn given_repository_when_installing_hook_then_existing_foreign_hook_should_need_force() {
+    let repo = repository();
+    let tmgr = Path::new("/usr/local/bin/tmgr");
+
+    let hook = git_hook::install_in(repo.path(), tmgr, false).unwrap();
+    let script = read_to_string(&hook).unwrap();
+    assert!(script.starts_with("#!/bin/sh\n"));
+    assert!(script.contains("'/usr/local/bin/tmgr' git-hook commit-msg \"$1\""));
+    // reinstalling over our own hook is fine
+    git_hook::install_in(repo.path(), tmgr, false).unwrap();
+
+    write(&hook, "#!/bin/sh\necho custom\n").unwrap();
+    assert!(git_hook::install_in(repo.path(), tmgr, false).is_err());
+    git_hook::install_in(repo.path(), tmgr, true).unwrap();
+    assert!(
+        read_to_string(&hook)
+            .unwrap()
+            .contains("git-hook commit-msg")
+    );
+}
+
+#[tokio::test]
+async fn given_commit_message_closing_tasks_when_running_hook_then_tasks_should_be_completed() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let dir = tempdir().expect("Failed to create temporary directory");
+    let closed = insert_task(&db, "Closed task").await;
+    let open = insert_task(&db, "Open task").await;
+    let message_file = dir.path().join("COMMIT_EDITMSG");
+    write(
+        &message_file,
+        format!("Finish the work\n\nCloses {closed}, fixes zzzzzzzz\nRefs {open}\n"),
+    )
+    .unwrap();
+
+    let res = git_hook::commit_msg(&db, message_file.to_string_lossy().to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(res.result().len(), 1);
+    assert_eq!(res.result()[0].state(), TaskState::Done);
+    assert!(res.message().contains("skipped 'zzzzzzzz'"));
+    let open_task = db.select_task_by_partial_id(&open).await.unwrap();
+    assert_eq!(open_task.state(), TaskState::Todo);
+}
+
+#[tokio::test]
+async fn given_short_word_after_closing_word_when_running_hook_then_no_task_should_be_completed() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let dir = tempdir().expect("Failed to create temporary directory");
+    let task = Task::builder()
+        .name("Task starting with a".to_string())
+        .state(TaskState::Todo)
+        .build();
+    db.client
+        .query("CREATE task:abcdef123456 CONTENT $task")
+        .bind(("task", task))
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+    let message_file = dir.path().join("COMMIT_EDITMSG");
+    write(&message_file, "Fixes a typo\n").unwrap();
+
+    let res = git_hook::commit_msg(&db, message_file.to_string_lossy().to_string())
+        .await
+        .unwrap();
+
+    assert!(res.result().is_empty());
+    let task = db.select_task_by_partial_id("a").await.unwrap();
+    assert_eq!(task.state(), TaskState::Todo);
+}
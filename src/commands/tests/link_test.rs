@@ -0,0 +1,170 @@
+use super::super::super::{
+    cli::model::{ListFilter, OutputFormat},
+    db,
+    model::{CodeLink, Task},
+};
+use super::super::{
+    doctor::{self, DoctorIssueKind},
+    link::{self, LinkStatus, check_content, parse_location},
+    list, view,
+};
+use std::fs::{create_dir, write};
+use tempfile::{TempDir, tempdir};
+
+/// A temporary git repository holding `src/lib.rs` with five numbered lines.
+fn repository() -> TempDir {
+    let repo = tempdir().expect("Failed to create temporary directory");
+    create_dir(repo.path().join(".git")).unwrap();
+    create_dir(repo.path().join("src")).unwrap();
+    write(
+        repo.path().join("src/lib.rs"),
+        "line 1\nline 2\nline 3\nline 4\nline 5\n",
+    )
+    .unwrap();
+    repo
+}
+
+fn code_link(start: usize, end: usize, lines: &[&str]) -> CodeLink {
+    CodeLink::new(
+        "/repo".to_string(),
+        "src/lib.rs".to_string(),
+        start,
+        end,
+        lines.iter().map(|l| l.to_string()).collect(),
+    )
+}
+
+#[test]
+fn given_locations_when_parsing_then_path_and_line_range_should_be_returned() {
+    assert_eq!(
+        parse_location("src/main.rs:120").unwrap(),
+        ("src/main.rs".to_string(), 120, 120)
+    );
+    assert_eq!(
+        parse_location("C:/code/main.rs:120-140").unwrap(),
+        ("C:/code/main.rs".to_string(), 120, 140)
+    );
+    assert!(parse_location("src/main.rs").is_err());
+    assert!(parse_location("src/main.rs:0").is_err());
+    assert!(parse_location("src/main.rs:12-3").is_err());
+}
+
+#[test]
+fn given_file_content_when_checking_a_link_then_staleness_should_be_reported() {
+    let link = code_link(2, 3, &["line 2", "line 3"]);
+    assert_eq!(
+        check_content(&link, "line 1\nline 2\nline 3\n"),
+        LinkStatus::Current
+    );
+    assert_eq!(
+        check_content(&link, "new\nline 1\nline 2\nline 3\n"),
+        LinkStatus::Moved(3)
+    );
+    assert_eq!(
+        check_content(&link, "line 1\nline two\nline 3\n"),
+        LinkStatus::Changed
+    );
+    assert_eq!(check_content(&link, "line 1\n"), LinkStatus::MissingLines);
+}
+
+#[test]
+fn given_broken_link_when_checking_it_then_it_should_be_invalid() {
+    let content = "line 1\nline 2\n";
+    assert_eq!(
+        check_content(&code_link(0, 1, &["line 1"]), content),
+        LinkStatus::Invalid
+    );
+    assert_eq!(
+        check_content(&code_link(2, 1, &["line 2"]), content),
+        LinkStatus::Invalid
+    );
+    assert_eq!(
+        check_content(&code_link(1, 1, &[]), content),
+        LinkStatus::Invalid
+    );
+}
+
+#[tokio::test]
+async fn given_file_in_git_repository_when_linking_then_task_should_be_found_by_file_and_viewed_with_excerpt()
+ {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let repo = repository();
+    let file = repo.path().join("src/lib.rs");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::builder().name("linked").build())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = link::run(&db, id.clone(), format!("{}:2-3", file.display()))
+        .await
+        .unwrap();
+    assert_eq!(
+        res.message(),
+        format!("Successfully linked 'src/lib.rs:2-3' to task '{id}'")
+    );
+    let link = &res.result().code_links()[0];
+    assert_eq!(link.path(), "src/lib.rs");
+    assert_eq!(
+        link.lines(),
+        &vec!["line 2".to_string(), "line 3".to_string()]
+    );
+
+    let listed = list::run(
+        &db,
+        ListFilter {
+            file: Some(file.to_string_lossy().to_string()),
+            ..Default::default()
+        },
+        None,
+        OutputFormat::Table,
+    )
+    .await
+    .unwrap();
+    assert_eq!(listed.result().len(), 1);
+
+    let viewed = view::run(&db, id.clone(), false, OutputFormat::Table)
+        .await
+        .unwrap();
+    assert!(
+        viewed
+            .message()
+            .ends_with("src/lib.rs:2-3\n  2 | line 2\n  3 | line 3")
+    );
+}
+
+#[tokio::test]
+async fn given_linked_lines_that_changed_when_running_doctor_then_link_should_be_reported_and_refreshed()
+ {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let notes_dir = tempdir().expect("Failed to create temporary directory");
+    let repo = repository();
+    let file = repo.path().join("src/lib.rs");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+    link::run(&db, id.clone(), format!("{}:4", file.display()))
+        .await
+        .unwrap();
+    write(&file, "line 1\nline 2\nline 3\nline four\nline 5\n").unwrap();
+
+    let mut issues = doctor::diagnose(&db, notes_dir.path()).await.unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(*issues[0].kind(), DoctorIssueKind::StaleCodeLink);
+    doctor::repair(&db, notes_dir.path(), &mut issues)
+        .await
+        .unwrap();
+
+    assert!(
+        doctor::diagnose(&db, notes_dir.path())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -1,10 +1,23 @@
-use super::super::super::{db, model::Task};
+use super::super::super::{
+    cli::model::{ListFilter, ListSort, OutputFormat},
+    db,
+    model::{Task, TaskPriority, TaskState},
+};
 use super::super::list;
 
 #[tokio::test]
 async fn given_no_existing_tasks_when_listing_all_tasks_then_no_tasks_should_be_returned() {
     let db = db::DB::new_test().await.expect("Failed to create db");
-    let res = list::run(&db, true).await;
+    let res = list::run(
+        &db,
+        ListFilter {
+            all: true,
+            ..Default::default()
+        },
+        None,
+        OutputFormat::Table,
+    )
+    .await;
     assert!(res.is_ok());
     assert_eq!(res.unwrap().message().contains("task:"), false);
 }
@@ -12,7 +25,7 @@ async fn given_no_existing_tasks_when_listing_all_tasks_then_no_tasks_should_be_
 #[tokio::test]
 async fn given_no_existing_tasks_when_listing_in_progress_tasks_then_no_tasks_should_be_returned() {
     let db = db::DB::new_test().await.expect("Failed to create db");
-    let res = list::run(&db, false).await;
+    let res = list::run(&db, ListFilter::default(), None, OutputFormat::Table).await;
     assert!(res.is_ok());
     assert_eq!(res.unwrap().message().contains("task:"), false);
 }
@@ -26,7 +39,16 @@ async fn given_existing_tasks_when_listing_all_tasks_then_all_tasks_should_be_re
         .content(Task::default())
         .await
         .unwrap();
-    let res = list::run(&db, true).await;
+    let res = list::run(
+        &db,
+        ListFilter {
+            all: true,
+            ..Default::default()
+        },
+        None,
+        OutputFormat::Table,
+    )
+    .await;
     assert!(res.is_ok());
     assert!(res.unwrap().message().contains("Low"));
 }
@@ -43,9 +65,94 @@ async fn given_existing_tasks_when_listing_in_progress_tasks_then_only_in_progre
     let _: Vec<Task> = db.client.insert("task").content(task1).await.unwrap();
     let _: Vec<Task> = db.client.insert("task").content(task2).await.unwrap();
 
-    let res = list::run(&db, false).await;
+    let res = list::run(&db, ListFilter::default(), None, OutputFormat::Table).await;
     assert!(res.is_ok());
     let res = res.unwrap().message().to_lowercase();
     assert!(&res.contains("in progress task"));
     assert_eq!(res.contains("Completed task"), false);
 }
+
+#[tokio::test]
+async fn given_tasks_with_different_priorities_when_listing_then_highest_priority_should_be_first()
+{
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    for (name, priority) in [
+        ("low task", TaskPriority::Low),
+        ("critical task", TaskPriority::Critical),
+        ("medium task", TaskPriority::Medium),
+    ] {
+        let task = Task::builder().name(name).priority(priority).build();
+        let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    }
+
+    let res = list::run(&db, ListFilter::default(), None, OutputFormat::Table)
+        .await
+        .unwrap();
+    let names: Vec<&str> = res.result().iter().map(|t| t.name()).collect();
+    assert_eq!(names, vec!["critical task", "medium task", "low task"]);
+}
+
+#[tokio::test]
+async fn given_tasks_in_different_states_when_listing_by_state_then_only_matching_tasks_should_be_returned()
+ {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    for (name, state) in [
+        ("blocked task", TaskState::Blocked),
+        ("todo task", TaskState::Todo),
+        ("cancelled task", TaskState::Cancelled),
+    ] {
+        let task = Task::builder().name(name).state(state).build();
+        let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    }
+
+    let res = list::run(
+        &db,
+        ListFilter {
+            sort: Some(ListSort::Name),
+            state: vec![TaskState::Blocked, TaskState::Cancelled],
+            ..Default::default()
+        },
+        None,
+        OutputFormat::Table,
+    )
+    .await
+    .unwrap();
+    let names: Vec<&str> = res.result().iter().map(|t| t.name()).collect();
+    assert_eq!(names, vec!["blocked task", "cancelled task"]);
+}
+
+#[tokio::test]
+async fn given_columns_and_json_output_when_listing_then_only_those_fields_should_be_printed() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let task = Task::builder().name("json task".to_string()).build();
+    let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+
+    let res = list::run(
+        &db,
+        ListFilter {
+            all: true,
+            ..Default::default()
+        },
+        Some(vec!["name".to_string(), "priority".to_string()]),
+        OutputFormat::Json,
+    )
+    .await
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(res.message()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([{"name": "json task", "priority": res.result()[0].priority().to_string()}])
+    );
+
+    let unknown = list::run(
+        &db,
+        ListFilter {
+            all: true,
+            ..Default::default()
+        },
+        Some(vec!["owner".to_string()]),
+        OutputFormat::Table,
+    )
+    .await;
+    assert!(unknown.is_err());
+}
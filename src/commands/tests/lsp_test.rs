@@ -0,0 +1,204 @@
+use super::super::super::{
+    db,
+    model::{CodeLink, Task, TaskPriority},
+};
+use super::super::{
+    complete,
+    lsp::{
+        self, CREATE_TASK_COMMAND, TaskReference, code_actions, diagnostics, find_references,
+        hover_text, reference_at, workspace_symbols,
+    },
+};
+use std::fs::write;
+use tower_lsp::lsp_types::{CodeActionOrCommand, DiagnosticSeverity, Position, Range, Url};
+
+async fn insert_task(db: &db::DB, task: Task) -> String {
+    let task: Option<Task> = db.client.create("task").content(task).await.unwrap();
+    task.unwrap().id().unwrap()
+}
+
+#[test]
+fn given_references_when_finding_them_then_ids_and_ranges_should_be_returned() {
+    let text = "fn main() {}\n// see tmgr:abc123 and tmgr:def456, not tmgr:ab or xtmgr:abcd\n";
+    assert_eq!(
+        find_references(text),
+        vec![
+            TaskReference {
+                id: "abc123".to_string(),
+                range: Range::new(Position::new(1, 7), Position::new(1, 18)),
+            },
+            TaskReference {
+                id: "def456".to_string(),
+                range: Range::new(Position::new(1, 23), Position::new(1, 34)),
+            },
+        ]
+    );
+}
+
+#[test]
+fn given_non_ascii_text_before_reference_when_finding_it_then_columns_should_be_in_utf16_units() {
+    let references = find_references("// café 🎉 tmgr:abc123");
+    assert_eq!(references[0].range.start, Position::new(0, 11));
+    assert_eq!(references[0].range.end, Position::new(0, 22));
+}
+
+#[test]
+fn given_position_when_finding_reference_at_it_then_only_reference_under_it_should_be_returned() {
+    let text = "// tmgr:abc123 and more";
+    assert_eq!(
+        reference_at(text, Position::new(0, 10)).map(|r| r.id),
+        Some("abc123".to_string())
+    );
+    assert_eq!(reference_at(text, Position::new(0, 18)), None);
+    assert_eq!(reference_at(text, Position::new(1, 5)), None);
+}
+
+#[tokio::test]
+async fn given_references_to_done_and_missing_tasks_when_diagnosing_then_they_should_be_flagged() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let open = insert_task(&db, Task::builder().name("Open task").build()).await;
+    let done = insert_task(&db, Task::builder().name("Done task").build()).await;
+    complete::run(&db, done.clone()).await.unwrap();
+
+    let text = format!("// tmgr:{open}\n// tmgr:{done}\n// tmgr:zzzz9999\n");
+    let diagnostics = diagnostics(&db, &text).await;
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].range.start.line, 1);
+    assert_eq!(
+        diagnostics[0].severity,
+        Some(DiagnosticSeverity::INFORMATION)
+    );
+    assert_eq!(
+        diagnostics[0].message,
+        format!("Task '{done}' is done: Done task")
+    );
+    assert_eq!(diagnostics[1].range.start.line, 2);
+    assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(
+        diagnostics[1].message,
+        "Task 'zzzz9999' does not exist, it may have been deleted"
+    );
+}
+
+#[tokio::test]
+async fn given_referenced_task_when_hovering_then_task_should_be_described() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let id = insert_task(
+        &db,
+        Task::builder()
+            .name("Write docs")
+            .priority(TaskPriority::High)
+            .description("For the LSP".to_string())
+            .build(),
+    )
+    .await;
+    let text = format!("// tmgr:{}", &id[..6]);
+
+    let hover = lsp::hover(&db, &text, Position::new(0, 5)).await;
+    assert!(hover.is_some());
+    let task = db.select_task_by_partial_id(&id).await.unwrap();
+    assert_eq!(
+        hover_text(&task),
+        format!("**Write docs**\n\n`{id}` · in-progress · High priority\n\nFor the LSP")
+    );
+    assert!(lsp::hover(&db, &text, Position::new(0, 1)).await.is_none());
+}
+
+#[tokio::test]
+async fn given_todo_comment_when_creating_task_then_it_should_be_created_once_and_linked() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("main.rs");
+    let text = "fn main() {\n    // TODO: handle errors\n}\n";
+    write(&path, text).unwrap();
+    let uri = Url::from_file_path(&path).unwrap();
+    let line_range = Range::new(Position::new(1, 0), Position::new(1, 0));
+
+    let actions = code_actions(&db, &uri, &path, text, line_range)
+        .await
+        .unwrap();
+    assert_eq!(actions.len(), 1);
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("Expected a code action");
+    };
+    assert_eq!(action.title, "Create a tmgr task from this TODO");
+    assert_eq!(
+        action.command.as_ref().unwrap().command,
+        CREATE_TASK_COMMAND
+    );
+
+    let task = lsp::create_task(&db, &path, text, 2).await.unwrap();
+    assert_eq!(task.name(), "handle errors");
+    assert!(task.comment_hash().is_some());
+    assert_eq!(task.code_links()[0].path(), "main.rs");
+    assert_eq!(task.code_links()[0].start(), 2);
+
+    let again = lsp::create_task(&db, &path, text, 2).await;
+    assert!(
+        again
+            .unwrap_err()
+            .to_string()
+            .contains("was already created")
+    );
+    let actions = code_actions(&db, &uri, &path, text, line_range)
+        .await
+        .unwrap();
+    assert!(actions.is_empty());
+}
+
+#[tokio::test]
+async fn given_line_without_comment_when_creating_task_then_error_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("main.rs");
+    let res = lsp::create_task(&db, &path, "fn main() {}\n", 1).await;
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "There is no TODO, FIXME or HACK comment on line 1 (lsp error: No comment)"
+    );
+}
+
+#[tokio::test]
+async fn given_in_progress_tasks_when_listing_workspace_symbols_then_linked_matching_tasks_should_be_returned()
+ {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let link = CodeLink::new(
+        "/repo".to_string(),
+        "src/main.rs".to_string(),
+        3,
+        4,
+        vec!["a".to_string(), "b".to_string()],
+    );
+    let linked = insert_task(
+        &db,
+        Task::builder()
+            .name("Fix the parser")
+            .code_links(vec![link.clone()])
+            .build(),
+    )
+    .await;
+    insert_task(&db, Task::builder().name("Fix without link").build()).await;
+    let done = insert_task(
+        &db,
+        Task::builder()
+            .name("Fix the lexer")
+            .code_links(vec![link])
+            .build(),
+    )
+    .await;
+    complete::run(&db, done).await.unwrap();
+
+    let symbols = workspace_symbols(&db, "fix").await.unwrap();
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].name, "Fix the parser");
+    assert_eq!(symbols[0].container_name, Some(format!("tmgr:{linked}")));
+    assert_eq!(
+        symbols[0].location.uri,
+        Url::from_file_path("/repo/src/main.rs").unwrap()
+    );
+    assert_eq!(
+        symbols[0].location.range,
+        Range::new(Position::new(2, 0), Position::new(3, 0))
+    );
+    assert!(workspace_symbols(&db, "lexer").await.unwrap().is_empty());
+}
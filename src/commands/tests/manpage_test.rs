@@ -0,0 +1,57 @@
+use super::super::super::cli::model::Cli;
+use super::super::manpage::{markdown_reference, visible_subcommands, write_pages_in};
+use clap::CommandFactory;
+use std::{fs::read_to_string, path::Path};
+use tempfile::tempdir;
+
+fn repository_file(path: &str) -> String {
+    read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(path))
+        .unwrap_or_else(|e| panic!("Failed to read {path}: {e}"))
+}
+
+#[test]
+fn given_cli_when_generating_markdown_reference_then_it_should_match_docs() {
+    assert_eq!(
+        repository_file("docs/reference.md"),
+        format!("{}\n", markdown_reference()),
+        "docs/reference.md is out of date, run `cargo run -- manpage --markdown > docs/reference.md`"
+    );
+}
+
+#[test]
+fn given_cli_when_reading_readme_then_command_table_should_list_every_command() {
+    let readme = repository_file("README.md");
+    let cli = Cli::command();
+    for command in visible_subcommands(&cli) {
+        let row = format!("| {:<13}|", command.get_name());
+        assert!(
+            readme.contains(&row),
+            "README command table is missing '{}'",
+            command.get_name()
+        );
+    }
+}
+
+#[test]
+fn given_directory_when_writing_man_pages_then_every_command_should_have_a_page() {
+    let dir = tempdir().expect("Failed to create temporary directory");
+
+    let pages = write_pages_in(dir.path()).unwrap();
+
+    for page in &pages {
+        assert!(page.is_file(), "{} should exist", page.display());
+    }
+    let names: Vec<String> = pages
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert!(names.contains(&"tmgr.1".to_string()));
+    assert!(names.contains(&"tmgr-migrate.1".to_string()));
+    assert!(names.contains(&"tmgr-config-set.1".to_string()));
+    assert!(!names.iter().any(|n| n.contains("complete-ids")));
+    assert!(
+        read_to_string(dir.path().join("tmgr-list.1"))
+            .unwrap()
+            .contains("\\-\\-sort")
+    );
+}
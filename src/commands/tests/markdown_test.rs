@@ -0,0 +1,41 @@
+use super::super::markdown::render;
+
+#[test]
+fn given_markdown_when_rendering_without_style_then_markup_should_be_removed() {
+    let markdown = "<!-- tmgr:header:start -->\n# Title\n\nSome **bold** and `code` with a [link](https://example.com).\n\n- first\n1. second\n> quoted\n\n```\nlet x = 1;\n```\n";
+    assert_eq!(
+        render(markdown, None, false),
+        "Title\n\nSome bold and code with a link (https://example.com).\n\n• first\n1. second\n| quoted\n\n    let x = 1;"
+    );
+}
+
+#[test]
+fn given_width_when_rendering_then_list_items_should_wrap_with_a_hanging_indent() {
+    assert_eq!(
+        render("- one two three four five six", Some(16), false),
+        "• one two three\n  four five six"
+    );
+}
+
+#[test]
+fn given_code_block_when_rendering_then_lines_should_not_be_wrapped_or_parsed() {
+    assert_eq!(
+        render("```\nfn **not_bold**() { a_b_c }\n```", Some(10), false),
+        "    fn **not_bold**() { a_b_c }"
+    );
+}
+
+#[test]
+fn given_underscores_inside_words_when_rendering_then_they_should_not_be_emphasis() {
+    assert_eq!(
+        render("a snake_case_name", None, false),
+        "a snake_case_name"
+    );
+}
+
+#[test]
+fn given_markdown_when_rendering_with_style_then_escape_codes_should_be_added() {
+    let rendered = render("# Title", None, true);
+    assert!(rendered.contains("\u{1b}["));
+    assert!(rendered.contains("Title"));
+}
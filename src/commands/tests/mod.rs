@@ -1,16 +1,49 @@
 #[cfg(test)]
 mod add_test;
+#[cfg(test)]
+mod attach_test;
+#[cfg(test)]
+mod board_test;
 
 #[cfg(test)]
 mod complete_test;
+#[cfg(test)]
+mod completions_test;
+#[cfg(test)]
+mod config_test;
 
 #[cfg(test)]
 mod delete_test;
 #[cfg(test)]
+mod doctor_test;
+#[cfg(test)]
+mod git_test;
+#[cfg(test)]
+mod link_test;
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod list_test;
 #[cfg(test)]
+mod lsp_test;
+#[cfg(test)]
+mod manpage_test;
+#[cfg(test)]
+mod markdown_test;
+#[cfg(test)]
+mod move_test;
+#[cfg(test)]
+mod open_test;
+#[cfg(test)]
+mod query_test;
+#[cfg(test)]
+mod scan_test;
+#[cfg(test)]
 mod status_test;
 #[cfg(test)]
+mod task_editor_test;
+#[cfg(test)]
+mod tui_test;
+#[cfg(test)]
 mod update_test;
 #[cfg(test)]
 mod upgrade_test;
@@ -20,4 +53,7 @@ mod view_test;
 #[cfg(test)]
 mod migrate_test;
 #[cfg(test)]
+#[allow(clippy::expect_fun_call)]
 mod note_test;
+#[cfg(test)]
+mod serve_test;
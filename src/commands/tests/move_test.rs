@@ -0,0 +1,100 @@
+use super::super::super::{
+    db,
+    model::{Task, TaskState},
+};
+use super::super::move_task;
+
+#[tokio::test]
+async fn given_todo_task_when_moving_to_in_progress_then_transition_should_be_recorded() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let task = Task::builder().state(TaskState::Todo).build();
+    let new_task: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = move_task::run(
+        &db,
+        id.clone(),
+        TaskState::InProgress,
+        &TaskState::default_transitions(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        res.message(),
+        format!("Successfully moved task '{id}' from 'todo' to 'in-progress'")
+    );
+
+    let task = res.result();
+    assert_eq!(task.state(), TaskState::InProgress);
+    assert_eq!(task.state_history().len(), 1);
+    assert_eq!(*task.state_history()[0].from(), TaskState::Todo);
+    assert_eq!(*task.state_history()[0].to(), TaskState::InProgress);
+    assert!(task.completed_at().is_none());
+}
+
+#[tokio::test]
+async fn given_task_when_moving_to_done_and_back_then_completed_at_should_be_set_and_cleared() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let task = Task::builder().state(TaskState::InProgress).build();
+    let new_task: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    let id = new_task[0].id().unwrap();
+    let transitions = TaskState::default_transitions();
+
+    let done = move_task::run(&db, id.clone(), TaskState::Done, &transitions)
+        .await
+        .unwrap();
+    assert!(done.result().completed_at().is_some());
+
+    let reopened = move_task::run(&db, id.clone(), TaskState::InProgress, &transitions)
+        .await
+        .unwrap();
+    assert!(reopened.result().completed_at().is_none());
+    assert_eq!(reopened.result().state_history().len(), 2);
+}
+
+#[tokio::test]
+async fn given_disallowed_transition_when_moving_then_error_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let task = Task::builder().state(TaskState::Cancelled).build();
+    let new_task: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = move_task::run(
+        &db,
+        id.clone(),
+        TaskState::Done,
+        &TaskState::default_transitions(),
+    )
+    .await;
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        format!(
+            "Task '{id}' cannot move from 'cancelled' to 'done', allowed states are: todo (move error: Transition not allowed)"
+        )
+    );
+}
+
+#[tokio::test]
+async fn given_task_when_moving_to_current_state_then_error_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let task = Task::builder().state(TaskState::Blocked).build();
+    let new_task: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = move_task::run(
+        &db,
+        id.clone(),
+        TaskState::Blocked,
+        &TaskState::default_transitions(),
+    )
+    .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn given_task_without_state_when_reading_state_then_state_should_be_inferred() {
+    let in_progress = Task::builder().build();
+    let completed = Task::builder().completed_at(Default::default()).build();
+    assert_eq!(in_progress.state(), TaskState::InProgress);
+    assert_eq!(completed.state(), TaskState::Done);
+}
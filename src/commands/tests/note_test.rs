@@ -1,7 +1,14 @@
-use super::super::super::{db, model::Task};
-use super::super::note::{self, path_from_id};
+use super::super::super::{
+    db,
+    model::{Task, TaskPriority, TaskState},
+};
+use super::super::note::{
+    self, append_entry, load_template_from, path_from_id, render_template, replace_header,
+};
+use super::super::{complete, update};
 use std::{
-    fs::{File, read_to_string, remove_file},
+    env::temp_dir,
+    fs::{File, create_dir_all, read_to_string, remove_dir_all, remove_file, write},
     io::{self, BufRead, Write},
     path::Path,
 };
@@ -17,7 +24,7 @@ async fn given_an_existing_task_without_note_when_calling_note_should_create_md_
         .await
         .unwrap();
     let id = new_task[0].id().unwrap();
-    let res = note::run(&db, id.clone(), false)
+    let res = note::run(&db, id.clone(), false, None)
         .await
         .expect("Error creating note")
         .message()
@@ -44,7 +51,7 @@ async fn when_creating_note_should_write_correct_header() {
         .await
         .unwrap();
     let id = new_task[0].id().unwrap();
-    let res = note::run(&db, id.clone(), false)
+    let res = note::run(&db, id.clone(), false, None)
         .await
         .expect("Error creating note")
         .message()
@@ -54,10 +61,14 @@ async fn when_creating_note_should_write_correct_header() {
     let reader = io::BufReader::new(file);
     let lines: Vec<String> = reader.lines().collect::<io::Result<Vec<String>>>().unwrap();
 
-    assert_eq!(lines[0], format!("# Task {id} - "));
-    assert_eq!(lines[1], "");
-    assert_eq!(lines[2], "## Notes");
-    assert_eq!(lines[3], "");
+    assert_eq!(lines[0], "<!-- tmgr:header:start -->");
+    assert_eq!(lines[1], format!("# Task {id} - "));
+    assert_eq!(lines[2], "");
+    assert_eq!(lines[3], "**State:** `in-progress` | **Priority:** Low");
+    assert_eq!(lines[4], "<!-- tmgr:header:end -->");
+    assert_eq!(lines[5], "");
+    assert_eq!(lines[6], "## Notes");
+    assert_eq!(lines[7], "");
 
     // Clean up by removing the note file
     remove_file(&res).expect(format!("Failed to delete the note file at path: {res}").as_str());
@@ -77,7 +88,7 @@ async fn when_creating_note_with_description_should_write_correct_header() {
         .await
         .unwrap();
     let id = new_task[0].id().unwrap();
-    let res = note::run(&db, id.clone(), false)
+    let res = note::run(&db, id.clone(), false, None)
         .await
         .expect("Error creating note")
         .message()
@@ -87,12 +98,16 @@ async fn when_creating_note_with_description_should_write_correct_header() {
     let reader = io::BufReader::new(file);
     let lines: Vec<String> = reader.lines().collect::<io::Result<Vec<String>>>().unwrap();
 
-    assert_eq!(lines[0], format!("# Task {id} - New task"));
-    assert_eq!(lines[1], "");
-    assert_eq!(lines[2], "Some description");
-    assert_eq!(lines[3], "");
-    assert_eq!(lines[4], "## Notes");
-    assert_eq!(lines[5], "");
+    assert_eq!(lines[0], "<!-- tmgr:header:start -->");
+    assert_eq!(lines[1], format!("# Task {id} - New task"));
+    assert_eq!(lines[2], "");
+    assert_eq!(lines[3], "**State:** `in-progress` | **Priority:** Low");
+    assert_eq!(lines[4], "");
+    assert_eq!(lines[5], "Some description");
+    assert_eq!(lines[6], "<!-- tmgr:header:end -->");
+    assert_eq!(lines[7], "");
+    assert_eq!(lines[8], "## Notes");
+    assert_eq!(lines[9], "");
 
     // Clean up by removing the note file
     remove_file(&res).expect(format!("Failed to delete the note file at path: {res}").as_str());
@@ -114,7 +129,7 @@ async fn given_an_existing_task_with_note_when_calling_note_should_not_create_md
     let task: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
 
     let id = task[0].id().unwrap();
-    let res = note::run(&db, id.clone(), false)
+    let res = note::run(&db, id.clone(), false, None)
         .await
         .expect("Error creating note")
         .message()
@@ -138,3 +153,221 @@ fn should_return_filename_with_id_and_md_extension() {
             .ends_with(format!("{}.md", id).as_str())
     );
 }
+
+#[test]
+fn given_template_with_placeholders_when_rendering_then_task_fields_should_be_filled_in() {
+    let task = Task::builder()
+        .name("Write report".to_string())
+        .priority(TaskPriority::High)
+        .state(TaskState::Todo)
+        .build();
+    let rendered = render_template(
+        "# {{name}} ({{ priority }})\nState: {{state}}\nDone: {{completed_at}}\n",
+        &task,
+    )
+    .unwrap();
+    assert_eq!(rendered, "# Write report (High)\nState: todo\nDone: \n");
+}
+
+#[test]
+fn given_line_with_only_an_empty_placeholder_when_rendering_then_line_should_be_left_out() {
+    let task = Task::builder().name("No description".to_string()).build();
+    let rendered = render_template("# {{name}}\n\n{{description}}\n\n## Notes\n", &task).unwrap();
+    assert_eq!(rendered, "# No description\n\n## Notes\n");
+}
+
+#[test]
+fn given_unknown_placeholder_when_rendering_then_error_should_be_returned() {
+    let res = render_template("{{assignee}}\n", &Task::default());
+    assert!(res.is_err());
+    assert!(
+        res.unwrap_err()
+            .to_string()
+            .starts_with("Unknown placeholder '{{assignee}}', available placeholders are: ")
+    );
+}
+
+#[test]
+fn given_template_directory_when_loading_templates_then_files_and_builtin_default_should_be_used() {
+    let dir = temp_dir().join(format!("tmgr-templates-{}", std::process::id()));
+    create_dir_all(&dir).unwrap();
+    write(dir.join("meeting.md"), "# Meeting {{name}}\n").unwrap();
+
+    assert_eq!(
+        load_template_from(&dir, "meeting").unwrap(),
+        "# Meeting {{name}}\n"
+    );
+    assert!(
+        load_template_from(&dir, "default")
+            .unwrap()
+            .contains("## Notes")
+    );
+    let err = load_template_from(&dir, "missing").unwrap_err().to_string();
+    assert!(err.contains("available templates are: default, meeting"));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn given_note_with_header_when_replacing_header_then_only_the_header_should_change() {
+    let task = Task::builder()
+        .name("Renamed".to_string())
+        .state(TaskState::Todo)
+        .build();
+    let note = "<!-- tmgr:header:start -->\n# Task x - Old name\n<!-- tmgr:header:end -->\n\n## Notes\n\nkeep me\n";
+    let updated = replace_header(note, &task).unwrap();
+    assert!(updated.contains("- Renamed\n"));
+    assert!(!updated.contains("Old name"));
+    assert!(updated.ends_with("<!-- tmgr:header:end -->\n\n## Notes\n\nkeep me\n"));
+}
+
+#[test]
+fn given_note_without_header_markers_when_replacing_header_then_none_should_be_returned() {
+    assert!(replace_header("# My own note\n", &Task::default()).is_none());
+}
+
+#[tokio::test]
+async fn given_task_with_note_when_updating_and_completing_then_note_header_should_be_regenerated()
+{
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::builder().name("Old name".to_string()).build())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+    let path = note::run(&db, id.clone(), false, None)
+        .await
+        .expect("Error creating note")
+        .message()
+        .to_string();
+    let mut f = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    f.write_all(b"my findings\n").unwrap();
+
+    update::run(&db, id.clone(), Some("New name".to_string()), None, None)
+        .await
+        .unwrap();
+    complete::run(&db, id.clone()).await.unwrap();
+
+    let content = read_to_string(&path).unwrap();
+    assert!(content.contains(&format!("# Task {id} - New name\n")));
+    assert!(content.contains("**State:** `done` | **Priority:** Low | **Completed:** "));
+    assert!(content.ends_with("## Notes\n\nmy findings\n"));
+
+    remove_file(&path).unwrap_or_else(|_| panic!("Failed to delete the note file at path: {path}"));
+}
+
+#[test]
+fn given_note_with_notes_section_when_appending_then_entry_should_be_added_at_the_end_of_the_section()
+ {
+    let note = "# Title\n\n## Notes\n\nfirst\n\n## Links\n\n- a\n";
+    assert_eq!(
+        append_entry(note, "2026-01-01 10:00:00 UTC", "second"),
+        "# Title\n\n## Notes\n\nfirst\n\n### 2026-01-01 10:00:00 UTC\n\nsecond\n\n## Links\n\n- a\n"
+    );
+}
+
+#[test]
+fn given_heading_in_code_block_when_appending_then_it_should_not_end_the_notes_section() {
+    let note = "# Title\n\n## Notes\n\n```sh\n# install it\nmake\n```\n\n## Links\n\n- a\n";
+    assert_eq!(
+        append_entry(note, "ts", "entry"),
+        "# Title\n\n## Notes\n\n```sh\n# install it\nmake\n```\n\n### ts\n\nentry\n\n## Links\n\n- a\n"
+    );
+}
+
+#[test]
+fn given_note_without_notes_section_when_appending_then_section_should_be_created() {
+    assert_eq!(
+        append_entry("# Title\n", "ts", "entry"),
+        "# Title\n\n## Notes\n\n### ts\n\nentry\n"
+    );
+}
+
+#[tokio::test]
+async fn given_task_without_note_when_appending_then_note_should_be_created_with_the_entry() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+
+    note::append(&db, id.clone(), "found the bug".to_string(), None)
+        .await
+        .expect("Error appending to note");
+    let res = note::append(&db, id.clone(), "fixed it".to_string(), None)
+        .await
+        .expect("Error appending to note");
+    let path = res.result().work_note_path().clone().unwrap();
+
+    let content = read_to_string(&path).unwrap();
+    let notes = content.split("## Notes\n").nth(1).unwrap();
+    assert!(notes.starts_with("\n### "));
+    assert!(notes.contains(" UTC\n\nfound the bug\n\n### "));
+    assert!(notes.ends_with(" UTC\n\nfixed it\n"));
+
+    remove_file(&path).unwrap_or_else(|_| panic!("Failed to delete the note file at path: {path}"));
+}
+
+#[tokio::test]
+async fn given_empty_entry_when_appending_then_error_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let res = note::append(&db, "any".to_string(), "  ".to_string(), None).await;
+    assert!(res.is_err());
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "Nothing to append, the entry is empty (note error: Empty entry)"
+    );
+}
+
+#[tokio::test]
+async fn given_task_with_note_when_showing_then_rendered_note_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::builder().name("Shown".to_string()).build())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+    let path = note::run(&db, id.clone(), false, None)
+        .await
+        .expect("Error creating note")
+        .message()
+        .to_string();
+
+    let res = note::show(&db, id.clone()).await.unwrap();
+    // stdout is not a terminal in tests, so the note is rendered as plain text
+    assert!(res.message().starts_with(&format!("Task {id} - Shown\n")));
+    assert!(!res.message().contains("<!--"));
+    assert!(res.message().ends_with("Notes"));
+
+    remove_file(&path).unwrap_or_else(|_| panic!("Failed to delete the note file at path: {path}"));
+}
+
+#[tokio::test]
+async fn given_task_without_note_when_showing_then_error_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = note::show(&db, id.clone()).await;
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        format!(
+            "Task '{id}' has no note, create one with 'tmgr note {id}' (note error: Note not found)"
+        )
+    );
+}
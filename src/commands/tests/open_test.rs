@@ -0,0 +1,62 @@
+use super::super::super::{
+    db,
+    model::{Attachment, AttachmentKind, Task},
+};
+use super::super::open::{self, select_attachment};
+
+fn task_with_attachments(count: usize) -> Task {
+    Task::builder()
+        .attachments(
+            (1..=count)
+                .map(|i| Attachment::new(AttachmentKind::Url, format!("https://example.com/{i}")))
+                .collect(),
+        )
+        .build()
+}
+
+#[test]
+fn given_single_attachment_when_no_number_is_given_then_it_should_be_selected() {
+    let task = task_with_attachments(1);
+    assert_eq!(
+        select_attachment(&task, None).unwrap().target(),
+        "https://example.com/1"
+    );
+}
+
+#[test]
+fn given_several_attachments_when_selecting_by_number_then_numbers_should_start_at_one() {
+    let task = task_with_attachments(3);
+    assert_eq!(
+        select_attachment(&task, Some(2)).unwrap().target(),
+        "https://example.com/2"
+    );
+    assert!(select_attachment(&task, Some(0)).is_err());
+    assert!(select_attachment(&task, Some(4)).is_err());
+}
+
+#[test]
+fn given_several_attachments_when_no_number_is_given_then_error_should_list_them() {
+    let task = task_with_attachments(2);
+    assert_eq!(
+        select_attachment(&task, None).unwrap_err().to_string(),
+        "Choose an attachment between 1 and 2:\n1. [url] https://example.com/1\n2. [url] https://example.com/2 (open error: Invalid attachment number)"
+    );
+}
+
+#[tokio::test]
+async fn given_task_without_attachments_when_opening_then_error_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let new_task: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = new_task[0].id().unwrap();
+
+    let res = open::run(&db, id, None).await;
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "The task has no attachments, add one with 'tmgr attach' (open error: No attachments)"
+    );
+}
@@ -0,0 +1,113 @@
+use super::super::super::{
+    cli::model::{ListFilter, ListSort, OutputFormat},
+    config::Config,
+    db,
+    model::{Task, TaskPriority, TaskState},
+};
+use super::super::query::{self, filter_args};
+
+async fn insert_task(db: &db::DB, name: &str, priority: TaskPriority, state: TaskState) {
+    let task = Task::builder()
+        .name(name.to_string())
+        .priority(priority)
+        .state(state)
+        .build();
+    let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+}
+
+fn urgent_filter() -> ListFilter {
+    ListFilter {
+        sort: Some(ListSort::Name),
+        priority: vec![TaskPriority::High, TaskPriority::Critical],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn given_saved_query_when_running_then_tasks_should_be_listed_with_its_filters() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    insert_task(&db, "b urgent", TaskPriority::Critical, TaskState::Todo).await;
+    insert_task(&db, "a urgent", TaskPriority::High, TaskState::Blocked).await;
+    insert_task(&db, "not urgent", TaskPriority::Low, TaskState::Todo).await;
+
+    query::save(&db, "urgent".to_string(), urgent_filter(), false)
+        .await
+        .unwrap();
+    let res = query::run(
+        &db,
+        &Config::default(),
+        "urgent".to_string(),
+        OutputFormat::Table,
+    )
+    .await
+    .unwrap();
+
+    let names: Vec<&str> = res.result().iter().map(|t| t.name()).collect();
+    assert_eq!(names, vec!["a urgent", "b urgent"]);
+}
+
+#[tokio::test]
+async fn given_existing_query_when_saving_again_then_force_should_be_required() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    query::save(&db, "urgent".to_string(), urgent_filter(), false)
+        .await
+        .unwrap();
+
+    let blocked = ListFilter {
+        state: vec![TaskState::Blocked],
+        ..Default::default()
+    };
+    assert!(
+        query::save(&db, "urgent".to_string(), blocked.clone(), false)
+            .await
+            .is_err()
+    );
+    query::save(&db, "urgent".to_string(), blocked.clone(), true)
+        .await
+        .unwrap();
+
+    let queries = query::list(&db).await.unwrap();
+    assert_eq!(queries.result().len(), 1);
+    assert_eq!(queries.result()[0].filter, blocked);
+    assert_eq!(queries.message(), "urgent: tmgr list --state blocked");
+}
+
+#[tokio::test]
+async fn given_unknown_or_invalid_names_when_using_queries_then_errors_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    assert!(
+        query::save(&db, "my query".to_string(), urgent_filter(), false)
+            .await
+            .is_err()
+    );
+    assert!(
+        query::run(
+            &db,
+            &Config::default(),
+            "missing".to_string(),
+            OutputFormat::Table
+        )
+        .await
+        .is_err()
+    );
+    assert!(query::delete(&db, "missing".to_string()).await.is_err());
+
+    query::save(&db, "urgent".to_string(), urgent_filter(), false)
+        .await
+        .unwrap();
+    query::delete(&db, "urgent".to_string()).await.unwrap();
+    assert!(query::list(&db).await.unwrap().result().is_empty());
+}
+
+#[test]
+fn given_filter_when_formatting_then_list_arguments_should_be_returned() {
+    let filter = ListFilter {
+        all: true,
+        file: Some("src/my file.rs".to_string()),
+        ..urgent_filter()
+    };
+    assert_eq!(
+        filter_args(&filter),
+        "--all --sort name --priority high --priority critical --file 'src/my file.rs'"
+    );
+}
@@ -0,0 +1,79 @@
+use super::super::super::{
+    db,
+    model::{TaskPriority, TaskState},
+};
+use super::super::scan::{self, comment_hash, extract_comments};
+use std::fs::{create_dir, write};
+use tempfile::tempdir;
+
+#[test]
+fn given_source_with_comments_when_extracting_then_markers_and_text_should_be_found() {
+    let source = "fn main() {\n    // TODO: handle errors\n    let x = 1; # FIXME(alice) wrong sign\n    /* HACK */\n    let todo = \"// TODO: not a comment\";\n    // TODOS are not markers\n}\n";
+    let comments: Vec<(String, String, usize)> = extract_comments(source)
+        .into_iter()
+        .map(|c| (c.marker, c.text, c.line))
+        .collect();
+    assert_eq!(
+        comments,
+        vec![
+            ("TODO".to_string(), "handle errors".to_string(), 2),
+            ("FIXME".to_string(), "wrong sign".to_string(), 3),
+            ("HACK".to_string(), "".to_string(), 4),
+        ]
+    );
+}
+
+#[test]
+fn given_comment_on_another_line_when_hashing_then_hash_should_not_change() {
+    let first = &extract_comments("// TODO: stable")[0];
+    let moved = &extract_comments("\n\n// TODO: stable")[0];
+    assert_eq!(
+        comment_hash("src/lib.rs", first, 0),
+        comment_hash("src/lib.rs", moved, 0)
+    );
+    assert_ne!(
+        comment_hash("src/lib.rs", first, 0),
+        comment_hash("src/lib.rs", first, 1)
+    );
+    assert_ne!(
+        comment_hash("src/lib.rs", first, 0),
+        comment_hash("src/main.rs", first, 0)
+    );
+}
+
+#[tokio::test]
+async fn given_source_tree_when_scanning_then_tasks_should_be_created_updated_and_completed() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let repo = tempdir().expect("Failed to create temporary directory");
+    create_dir(repo.path().join(".git")).unwrap();
+    write(repo.path().join(".gitignore"), "ignored.rs\n").unwrap();
+    write(repo.path().join("ignored.rs"), "// TODO: ignored\n").unwrap();
+    let file = repo.path().join("lib.rs");
+    write(&file, "// TODO: keep me\n// FIXME: remove me\n").unwrap();
+    let path = repo.path().to_string_lossy().to_string();
+
+    let res = scan::run(&db, path.clone()).await.unwrap();
+    let created = res.result().created();
+    assert_eq!(created.len(), 2);
+    assert_eq!(created[0].name(), "keep me");
+    assert_eq!(created[1].name(), "remove me");
+    assert_eq!(*created[1].priority(), TaskPriority::Medium);
+    assert_eq!(created[0].code_links()[0].to_string(), "lib.rs:1");
+
+    // scanning again changes nothing
+    let res = scan::run(&db, path.clone()).await.unwrap();
+    assert!(res.result().created().is_empty());
+    assert!(res.result().updated().is_empty());
+
+    write(&file, "\n// TODO: keep me\n").unwrap();
+    let res = scan::run(&db, path).await.unwrap();
+    assert!(res.result().created().is_empty());
+    assert_eq!(res.result().updated().len(), 1);
+    assert_eq!(
+        res.result().updated()[0].code_links()[0].to_string(),
+        "lib.rs:2"
+    );
+    assert_eq!(res.result().completed().len(), 1);
+    assert_eq!(res.result().completed()[0].name(), "remove me");
+    assert_eq!(res.result().completed()[0].state(), TaskState::Done);
+}
@@ -0,0 +1,305 @@
+use super::super::super::{config::Config, db};
+use super::super::serve::router;
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+async fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    send_with_token(app, method, uri, body, None).await
+}
+
+async fn send_with_token(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+    token: Option<&str>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+    if method != "GET" {
+        request = request.header(header::CONTENT_TYPE, "application/json");
+    }
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .expect("Failed to build request");
+    let response = app.clone().oneshot(request).await.expect("Request failed");
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read body");
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn test_app(token: Option<&str>) -> Router {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    router(db, Config::default(), token.map(|t| t.to_string()))
+}
+
+async fn add_task(app: &Router, name: &str) -> String {
+    let (status, task) = send(app, "POST", "/tasks", Some(json!({ "name": name }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    task["id"]
+        .as_str()
+        .expect("Task should have an id")
+        .to_string()
+}
+
+#[tokio::test]
+async fn given_new_task_when_posting_then_created_task_should_be_returned() {
+    let app = test_app(None).await;
+    let (status, task) = send(
+        &app,
+        "POST",
+        "/tasks",
+        Some(json!({ "name": "Write docs", "priority": "p1", "description": "For the API" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(task["name"], "Write docs");
+    assert_eq!(task["priority"], "High");
+    assert_eq!(task["description"], "For the API");
+    assert_eq!(task["state"], "todo");
+    assert!(!task["id"].as_str().unwrap().starts_with("task:"));
+}
+
+#[tokio::test]
+async fn given_invalid_body_when_posting_task_then_bad_request_should_be_returned() {
+    let app = test_app(None).await;
+    let (status, body) = send(&app, "POST", "/tasks", Some(json!({ "priority": "high" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].is_string());
+
+    let (status, _) = send(&app, "POST", "/tasks", Some(json!({ "name": "  " }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        "/tasks",
+        Some(json!({ "name": "Task", "priority": "urgent" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["error"]
+            .as_str()
+            .unwrap()
+            .contains("invalid priority 'urgent'")
+    );
+}
+
+#[tokio::test]
+async fn given_tasks_when_listing_then_tasks_matching_filters_should_be_returned() {
+    let app = test_app(None).await;
+    let first = add_task(&app, "First").await;
+    add_task(&app, "Second").await;
+    send(&app, "POST", &format!("/tasks/{first}/complete"), None).await;
+
+    let (status, tasks) = send(&app, "GET", "/tasks", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tasks.as_array().unwrap().len(), 1);
+    assert_eq!(tasks[0]["name"], "Second");
+
+    let (_, tasks) = send(&app, "GET", "/tasks?all=true&sort=name", None).await;
+    let names: Vec<&str> = tasks
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["First", "Second"]);
+
+    let (_, tasks) = send(&app, "GET", "/tasks?state=done,cancelled", None).await;
+    assert_eq!(tasks.as_array().unwrap().len(), 1);
+    assert_eq!(tasks[0]["id"], first.as_str());
+}
+
+#[tokio::test]
+async fn given_invalid_filter_when_listing_then_bad_request_should_be_returned() {
+    let app = test_app(None).await;
+    let (status, _) = send(&app, "GET", "/tasks?state=someday", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "GET", "/tasks?all=maybe", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn given_partial_id_when_viewing_updating_and_deleting_then_task_should_be_changed() {
+    let app = test_app(None).await;
+    let id = add_task(&app, "Task").await;
+    let partial = &id[..4];
+
+    let (status, task) = send(&app, "GET", &format!("/tasks/{partial}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["id"], id.as_str());
+
+    let (status, task) = send(
+        &app,
+        "PATCH",
+        &format!("/tasks/{partial}"),
+        Some(json!({ "name": "Renamed", "priority": "critical" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["name"], "Renamed");
+    assert_eq!(task["priority"], "Critical");
+
+    let (status, task) = send(&app, "DELETE", &format!("/tasks/{partial}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["name"], "Renamed");
+    let (status, _) = send(&app, "GET", &format!("/tasks/{id}"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn given_unknown_id_when_viewing_then_not_found_should_be_returned() {
+    let app = test_app(None).await;
+    let (status, body) = send(&app, "GET", "/tasks/randomID", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        body["error"],
+        "Task starting with id 'randomID' was not found (db error: No tasks found)"
+    );
+}
+
+#[tokio::test]
+async fn given_id_matching_several_tasks_when_completing_then_conflict_should_be_returned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    db.client
+        .query("CREATE task:abc1 SET name = 'First', priority = 'Low', created_at = time::now()")
+        .query("CREATE task:abc2 SET name = 'Second', priority = 'Low', created_at = time::now()")
+        .await
+        .expect("Failed to create tasks");
+    let app = router(db, Config::default(), None);
+
+    let (status, body) = send(&app, "POST", "/tasks/abc/complete", None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["error"],
+        "Multiple tasks found, provide more characters of the id (db error: Multiple tasks found)"
+    );
+    let (status, _) = send(&app, "POST", "/tasks/abc2/complete", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn given_update_without_fields_when_patching_then_bad_request_should_be_returned() {
+    let app = test_app(None).await;
+    let id = add_task(&app, "Task").await;
+    let (status, body) = send(&app, "PATCH", &format!("/tasks/{id}"), Some(json!({}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["error"]
+            .as_str()
+            .unwrap()
+            .contains("No fields to update")
+    );
+}
+
+#[tokio::test]
+async fn given_task_when_moving_then_state_should_change_or_conflict_should_be_returned() {
+    let app = test_app(None).await;
+    let id = add_task(&app, "Task").await;
+    let uri = format!("/tasks/{id}/move");
+
+    let (status, task) = send(&app, "POST", &uri, Some(json!({ "state": "in-progress" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["state"], "in-progress");
+
+    let (status, _) = send(&app, "POST", &uri, Some(json!({ "state": "in-progress" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = send(&app, "POST", &uri, Some(json!({ "state": "someday" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn given_task_without_note_when_reading_note_then_not_found_should_be_returned() {
+    let app = test_app(None).await;
+    let id = add_task(&app, "Task").await;
+    let (status, _) = send(&app, "GET", &format!("/tasks/{id}/note"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/tasks/{id}/note"),
+        Some(json!({ "text": "-" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn given_tasks_when_getting_status_then_task_counts_should_be_returned() {
+    let app = test_app(None).await;
+    let id = add_task(&app, "First").await;
+    add_task(&app, "Second").await;
+    send(&app, "POST", &format!("/tasks/{id}/complete"), None).await;
+
+    let (status, body) = send(&app, "GET", "/status", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["task_status"],
+        json!({ "completed": 1, "in_progress": 1, "total": 2 })
+    );
+}
+
+#[tokio::test]
+async fn given_token_when_requesting_without_or_with_wrong_token_then_unauthorized_should_be_returned()
+ {
+    let app = test_app(Some("secret")).await;
+    let (status, _) = send(&app, "GET", "/tasks", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_with_token(&app, "GET", "/tasks", None, Some("wrong")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_with_token(&app, "GET", "/tasks", None, Some("secret")).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn given_form_post_when_completing_task_then_it_should_be_refused() {
+    let app = test_app(None).await;
+    let id = add_task(&app, "Task").await;
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/tasks/{id}/complete"))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from("x=1"))
+        .expect("Failed to build request");
+    let response = app.clone().oneshot(request).await.expect("Request failed");
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let (_, task) = send(&app, "GET", &format!("/tasks/{id}"), None).await;
+    assert_eq!(task["state"], "todo");
+}
+
+#[tokio::test]
+async fn given_unknown_endpoint_when_requesting_then_not_found_should_be_returned() {
+    let app = test_app(None).await;
+    let (status, body) = send(&app, "GET", "/projects", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "No such endpoint");
+}
+
+#[tokio::test]
+async fn given_id_with_query_syntax_when_deleting_then_it_should_only_be_matched_as_a_prefix() {
+    let app = test_app(None).await;
+    add_task(&app, "Task").await;
+    let (status, _) = send(&app, "DELETE", "/tasks/%22)%20OR%20true%20OR%20(%22", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, tasks) = send(&app, "GET", "/tasks", None).await;
+    assert_eq!(tasks.as_array().unwrap().len(), 1);
+}
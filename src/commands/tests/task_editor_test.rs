@@ -0,0 +1,75 @@
+use super::super::super::model::TaskPriority;
+use super::super::task_editor::{TaskFields, edit_task_with, parse, render};
+use std::fs::{read_to_string, write};
+use tempfile::tempdir;
+
+#[test]
+fn given_rendered_fields_when_parsing_then_same_fields_should_be_returned() {
+    let fields = TaskFields {
+        name: "Write docs".to_string(),
+        priority: TaskPriority::High,
+        description: Some("# Context\n\nMulti-line\ndescription".to_string()),
+    };
+    assert_eq!(parse(&render(&fields)), Ok(fields));
+}
+
+#[test]
+fn given_document_without_description_when_parsing_then_description_should_be_none() {
+    let fields = parse("---\nname: Task\npriority: p0\n---\n\n").unwrap();
+    assert_eq!(fields.name, "Task");
+    assert_eq!(fields.priority, TaskPriority::Critical);
+    assert!(fields.description.is_none());
+}
+
+#[test]
+fn given_invalid_document_when_parsing_then_all_errors_should_be_returned() {
+    let errors = parse("---\nname:\npriority: urgent\ncolor: red\n---\n").unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert!(errors.contains(&"unknown field 'color'".to_string()));
+    assert!(errors.contains(&"name is required".to_string()));
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.starts_with("invalid priority 'urgent'"))
+    );
+}
+
+#[test]
+fn given_document_without_front_matter_when_parsing_then_error_should_be_returned() {
+    assert!(parse("name: Task\n").is_err());
+}
+
+#[test]
+fn given_invalid_edit_when_editing_then_editor_should_reopen_with_error_comments() {
+    let dir = tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("task.md");
+    let mut opened = 0;
+    let mut reopened_document = String::new();
+
+    let fields = edit_task_with(&TaskFields::default(), &path, |path| {
+        opened += 1;
+        if opened == 1 {
+            write(path, "---\nname:\n---\n").unwrap();
+        } else {
+            reopened_document = read_to_string(path).unwrap();
+            write(path, "---\nname: Fixed\n---\n").unwrap();
+        }
+        Ok(())
+    })
+    .expect("Second edit should be valid");
+
+    assert_eq!(opened, 2);
+    assert!(reopened_document.starts_with("# ERROR: name is required\n"));
+    assert_eq!(fields.name, "Fixed");
+}
+
+#[test]
+fn given_emptied_document_when_editing_then_edit_should_be_cancelled() {
+    let dir = tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("task.md");
+    let res = edit_task_with(&TaskFields::default(), &path, |path| {
+        write(path, "").unwrap();
+        Ok(())
+    });
+    assert!(res.is_err());
+}
@@ -0,0 +1,115 @@
+use super::super::super::model::{Task, TaskPriority, TaskState};
+use super::super::tui::{Action, App, Mode};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn tasks() -> Vec<Task> {
+    vec![
+        Task::builder()
+            .id("task:write")
+            .name("Write docs")
+            .priority(TaskPriority::High)
+            .state(TaskState::InProgress)
+            .build(),
+        Task::builder()
+            .id("task:review")
+            .name("Review PR")
+            .state(TaskState::InReview)
+            .build(),
+        Task::builder()
+            .id("task:ship")
+            .name("Ship release")
+            .state(TaskState::Done)
+            .completed_at(Default::default())
+            .build(),
+    ]
+}
+
+#[test]
+fn given_tasks_when_grouping_by_completion_then_completed_tasks_should_be_in_second_column() {
+    let app = App::new(tasks());
+    let columns = app.columns();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[0].title(), "In Progress");
+    assert_eq!(columns[0].tasks().len(), 2);
+    assert_eq!(columns[1].tasks()[0].name(), "Ship release");
+}
+
+#[test]
+fn given_tasks_when_switching_grouping_then_columns_should_be_per_state() {
+    let mut app = App::new(tasks());
+    app.handle_key(key(KeyCode::Char('g')));
+    let columns = app.columns();
+    assert_eq!(columns.len(), 6);
+    assert_eq!(columns[2].title(), "in-review");
+    assert_eq!(columns[2].tasks()[0].name(), "Review PR");
+}
+
+#[test]
+fn given_filter_when_typing_then_columns_should_only_contain_matching_tasks() {
+    let mut app = App::new(tasks());
+    app.handle_key(key(KeyCode::Char('/')));
+    assert_eq!(*app.mode(), Mode::Filter);
+    for c in "review".chars() {
+        app.handle_key(key(KeyCode::Char(c)));
+    }
+    let columns = app.columns();
+    assert_eq!(columns[0].tasks().len(), 1);
+    assert_eq!(columns[0].tasks()[0].name(), "Review PR");
+    assert!(columns[1].tasks().is_empty());
+}
+
+#[test]
+fn given_selected_task_when_pressing_complete_then_complete_action_should_be_returned() {
+    let mut app = App::new(tasks());
+    app.handle_key(key(KeyCode::Down));
+    let action = app.handle_key(key(KeyCode::Char('c')));
+    assert_eq!(action, Some(Action::Complete("review".to_string())));
+}
+
+#[test]
+fn given_selected_task_when_deleting_then_confirmation_should_be_required() {
+    let mut app = App::new(tasks());
+    assert_eq!(app.handle_key(key(KeyCode::Char('d'))), None);
+    assert_eq!(*app.mode(), Mode::ConfirmDelete);
+    let action = app.handle_key(key(KeyCode::Char('y')));
+    assert_eq!(action, Some(Action::Delete("write".to_string())));
+
+    app.handle_key(key(KeyCode::Char('d')));
+    assert_eq!(app.handle_key(key(KeyCode::Char('n'))), None);
+    assert_eq!(*app.mode(), Mode::Normal);
+}
+
+#[test]
+fn given_add_mode_when_entering_name_then_add_action_should_be_returned() {
+    let mut app = App::new(vec![]);
+    app.handle_key(key(KeyCode::Char('a')));
+    for c in "New".chars() {
+        app.handle_key(key(KeyCode::Char(c)));
+    }
+    let action = app.handle_key(key(KeyCode::Enter));
+    assert_eq!(action, Some(Action::Add("New".to_string())));
+}
+
+#[test]
+fn given_selected_task_when_lowering_priority_then_next_lower_level_should_be_returned() {
+    let mut app = App::new(tasks());
+    let action = app.handle_key(key(KeyCode::Char('-')));
+    assert_eq!(
+        action,
+        Some(Action::SetPriority(
+            "write".to_string(),
+            TaskPriority::Medium
+        ))
+    );
+}
+
+#[test]
+fn when_pressing_q_then_app_should_quit() {
+    let mut app = App::new(tasks());
+    app.handle_key(key(KeyCode::Char('q')));
+    assert!(app.quit());
+}
@@ -122,6 +122,28 @@ async fn given_existing_tasks_when_updating_the_name_then_only_that_field_should
     assert_eq!(res[0].description().as_ref().unwrap(), "some description");
 }
 
+#[tokio::test]
+async fn given_task_with_description_when_updating_to_empty_description_then_it_should_be_cleared()
+{
+    let db = db::DB::new_test().await.expect("Failed to create db");
+
+    let task = Task::builder()
+        .name("test".to_string())
+        .description("some description".to_string())
+        .build();
+
+    let db_res: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
+    let id = db_res[0].id().unwrap();
+
+    let res = update::run(&db, id, None, None, Some(String::new())).await;
+    assert!(res.is_ok());
+    assert!(res.unwrap().result().description().is_none());
+
+    let res: Vec<Task> = db.client.select("task").await.unwrap();
+    assert_eq!(res[0].name(), "test");
+    assert!(res[0].description().is_none());
+}
+
 // -- END Basic update 1 param tests --
 
 // -- Update multiple params tests --
@@ -1,43 +1,386 @@
-use super::super::upgrade::{delete_existing_binary, latest_release_url, move_new_binary};
-use std::{fs::File, io::Write};
-use tempfile::tempdir;
+use super::super::super::cli::model::ReleaseChannel;
+use super::super::upgrade::{
+    TARGET, UpdateError, UpdateInfo, asset_name, check_for_updates, default_release_api_url,
+    install_release, previous_binary_path, replace_binary, rollback_binary, verify_checksum,
+};
+use mockito::{Matcher, Server, ServerGuard};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{read, read_dir, write},
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+use tempfile::{TempDir, tempdir};
 
-// TODO: Repurpose this test file to test calling the run::upgrade function instead and checking output
 // TODO: move existing tests into upgrade.rs file as these test functionality of functions that should not be public
 #[test]
-fn latest_release_url_test() {
-    let expected = "https://api.github.com/repos/charliekarafotias/tmgr/releases/latest";
-    let actual = latest_release_url();
+fn default_release_api_url_test() {
+    let expected = "https://api.github.com/repos/charliekarafotias/tmgr";
+    let actual = default_release_api_url();
     assert_eq!(
         expected, actual,
-        "latest_release_url() should return the correct URL"
+        "default_release_api_url() should return the correct URL"
     );
 }
 
+const NEW_BINARY: &[u8] = b"the new tmgr executable";
+
+fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// A temporary directory holding a fake `tmgr` executable.
+fn executable() -> (TempDir, PathBuf) {
+    let dir = tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("tmgr");
+    write(&path, "the old tmgr executable").expect("Failed to write to temporary file");
+    (dir, path)
+}
+
+/// A release served by the mock release API.
+struct MockRelease<'a> {
+    tag: &'a str,
+    prerelease: bool,
+    body: &'a str,
+    assets: &'a [(&'a str, &'a [u8])],
+}
+
+/// A mock of the GitHub release API serving the given releases, with their assets served at
+/// `/download/<tag>/<name>`.
+async fn releases_server(releases: &[MockRelease<'_>]) -> ServerGuard {
+    let mut server = Server::new_async().await;
+    let url = server.url();
+    let mut releases_json = Vec::new();
+    for release in releases {
+        let tag = release.tag;
+        let mut assets_json = Vec::new();
+        for (name, body) in release.assets {
+            assets_json.push(format!(
+                r#"{{"name": "{name}", "browser_download_url": "{url}/download/{tag}/{name}"}}"#
+            ));
+            server
+                .mock("GET", format!("/download/{tag}/{name}").as_str())
+                .with_body(body)
+                .create_async()
+                .await;
+        }
+        releases_json.push(format!(
+            r#"{{"tag_name": "{tag}", "prerelease": {}, "draft": false, "body": "{}", "assets": [{}]}}"#,
+            release.prerelease,
+            release.body,
+            assets_json.join(", ")
+        ));
+    }
+    server
+        .mock("GET", "/releases")
+        .match_query(Matcher::Any)
+        .with_body(format!("[{}]", releases_json.join(", ")))
+        .create_async()
+        .await;
+    server
+}
+
+/// A mock of the GitHub release API serving a single stable release.
+async fn release_server(tag: &str, assets: &[(&str, &[u8])]) -> ServerGuard {
+    releases_server(&[MockRelease {
+        tag,
+        prerelease: false,
+        body: "",
+        assets,
+    }])
+    .await
+}
+
+/// Upgrades to the newest stable release of the mock release API, as `tmgr upgrade` does.
+async fn install_latest(
+    server: &ServerGuard,
+    exe: &Path,
+    public_key: Option<&str>,
+) -> Result<UpdateInfo, UpdateError> {
+    let info = check_for_updates(&server.url(), None, &ReleaseChannel::Stable).await?;
+    if info.needs_update() {
+        install_release(info.release(), exe, public_key).await?;
+    }
+    Ok(info)
+}
+
 #[test]
-fn delete_existing_binary_test() {
-    let temp_dir = tempdir().expect("Failed to create temporary directory");
-    let file_path = temp_dir.path().join("fake_file");
-    let mut file = File::create(&file_path).expect("Failed to create temporary file");
-    file.write_all(b"This is a fake file")
-        .expect("Failed to write to temporary file");
+fn given_checksum_files_when_verifying_then_only_matching_sha256_should_pass() {
+    let digest = sha256(NEW_BINARY);
+    verify_checksum(NEW_BINARY, &digest).unwrap();
+    verify_checksum(NEW_BINARY, &format!("{}  tmgr\n", digest.to_uppercase())).unwrap();
+    assert!(verify_checksum(b"tampered", &digest).is_err());
+    assert!(verify_checksum(NEW_BINARY, "not a checksum").is_err());
+}
+
+#[tokio::test]
+async fn given_newer_release_when_upgrading_then_asset_for_target_should_replace_executable() {
+    let (_dir, exe) = executable();
+    let name = asset_name();
+    let checksum = sha256(NEW_BINARY);
+    let server = release_server(
+        "v99.0.0",
+        &[
+            ("tmgr-some-other-target", b"wrong binary"),
+            (&name, NEW_BINARY),
+            (&format!("{name}.sha256"), checksum.as_bytes()),
+        ],
+    )
+    .await;
+
+    let info = install_latest(&server, &exe, None).await.unwrap();
+
+    assert!(info.needs_update());
+    assert!(name.ends_with(TARGET));
+    assert_eq!(read(&exe).unwrap(), NEW_BINARY);
+    assert_eq!(
+        read(previous_binary_path(&exe)).unwrap(),
+        b"the old tmgr executable"
+    );
+}
+
+#[tokio::test]
+async fn given_checksum_mismatch_when_upgrading_then_executable_should_be_untouched() {
+    let (dir, exe) = executable();
+    let name = asset_name();
+    let checksum = sha256(b"a different binary");
+    let server = release_server(
+        "v99.0.0",
+        &[
+            (&name, NEW_BINARY),
+            (&format!("{name}.sha256"), checksum.as_bytes()),
+        ],
+    )
+    .await;
+
+    let res = install_latest(&server, &exe, None).await;
+
+    assert!(res.is_err());
+    assert_eq!(read(&exe).unwrap(), b"the old tmgr executable");
+    // nothing is left behind next to the executable
+    assert_eq!(read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn given_release_without_checksum_or_target_asset_when_upgrading_then_error_should_be_returned()
+ {
+    let (_dir, exe) = executable();
+    let name = asset_name();
+    let without_checksum = release_server("v99.0.0", &[(&name, NEW_BINARY)]).await;
+    let without_target = release_server("v99.0.0", &[("tmgr-some-other-target", NEW_BINARY)]).await;
 
-    delete_existing_binary(&file_path).expect("delete_existing_binary should return Ok");
-    assert!(!file_path.try_exists().expect("file should be deleted"));
+    for server in [without_checksum, without_target] {
+        let res = install_latest(&server, &exe, None).await;
+        assert!(res.is_err());
+        assert_eq!(read(&exe).unwrap(), b"the old tmgr executable");
+    }
+}
+
+#[tokio::test]
+async fn given_current_release_when_upgrading_then_nothing_should_be_downloaded() {
+    let (_dir, exe) = executable();
+    let name = asset_name();
+    let checksum = sha256(NEW_BINARY);
+    let server = release_server(
+        &format!("v{}", env!("CARGO_PKG_VERSION")),
+        &[
+            (&name, NEW_BINARY),
+            (&format!("{name}.sha256"), checksum.as_bytes()),
+        ],
+    )
+    .await;
+
+    let info = install_latest(&server, &exe, None).await.unwrap();
+
+    assert!(!info.needs_update());
+    assert_eq!(read(&exe).unwrap(), b"the old tmgr executable");
+    assert!(!previous_binary_path(&exe).exists());
+}
+
+#[tokio::test]
+async fn given_public_key_when_upgrading_then_signature_should_be_required_and_valid() {
+    let key_pair = minisign::KeyPair::generate_unencrypted_keypair().unwrap();
+    let public_key = key_pair.pk.to_base64();
+    let signature = minisign::sign(
+        Some(&key_pair.pk),
+        &key_pair.sk,
+        Cursor::new(NEW_BINARY),
+        None,
+        None,
+    )
+    .unwrap()
+    .to_string();
+    let bad_signature = minisign::sign(
+        Some(&key_pair.pk),
+        &key_pair.sk,
+        Cursor::new(b"another binary"),
+        None,
+        None,
+    )
+    .unwrap()
+    .to_string();
+    let name = asset_name();
+    let checksum = sha256(NEW_BINARY);
+
+    let unsigned = release_server(
+        "v99.0.0",
+        &[
+            (&name, NEW_BINARY),
+            (&format!("{name}.sha256"), checksum.as_bytes()),
+        ],
+    )
+    .await;
+    let badly_signed = release_server(
+        "v99.0.0",
+        &[
+            (&name, NEW_BINARY),
+            (&format!("{name}.sha256"), checksum.as_bytes()),
+            (&format!("{name}.minisig"), bad_signature.as_bytes()),
+        ],
+    )
+    .await;
+    for server in [unsigned, badly_signed] {
+        let (_dir, exe) = executable();
+        assert!(
+            install_latest(&server, &exe, Some(&public_key))
+                .await
+                .is_err()
+        );
+        assert_eq!(read(&exe).unwrap(), b"the old tmgr executable");
+    }
+
+    let signed = release_server(
+        "v99.0.0",
+        &[
+            (&name, NEW_BINARY),
+            (&format!("{name}.sha256"), checksum.as_bytes()),
+            (&format!("{name}.minisig"), signature.as_bytes()),
+        ],
+    )
+    .await;
+    let (_dir, exe) = executable();
+    install_latest(&signed, &exe, Some(&public_key))
+        .await
+        .unwrap();
+    assert_eq!(read(&exe).unwrap(), NEW_BINARY);
 }
 
 #[test]
-fn move_new_binary_test() {
-    let temp_dir_old = tempdir().expect("Failed to create temporary directory");
-    let temp_dir_new = tempdir().expect("Failed to create temporary directory");
-    let file_path_old = temp_dir_old.path().join("fake_old");
-    let file_path_new = temp_dir_new.path().join("fake_new");
-    let mut file = File::create(&file_path_old).expect("Failed to create temporary file");
-    file.write_all(b"This is a fake file")
-        .expect("Failed to write to temporary file");
-
-    move_new_binary(file_path_old.clone(), file_path_new.clone())
-        .expect("move_new_binary should return Ok");
-    assert!(!file_path_old.try_exists().expect("file should be deleted"));
-    assert!(file_path_new.try_exists().expect("should exist"));
+fn given_upgraded_executable_when_rolling_back_then_versions_should_be_swapped() {
+    let (_dir, exe) = executable();
+    assert!(rollback_binary(&exe).is_err());
+
+    replace_binary(&exe, NEW_BINARY).unwrap();
+    let previous = rollback_binary(&exe).unwrap();
+
+    assert_eq!(read(&exe).unwrap(), b"the old tmgr executable");
+    assert_eq!(read(&previous).unwrap(), NEW_BINARY);
+    // rolling back again undoes the rollback
+    rollback_binary(&exe).unwrap();
+    assert_eq!(read(&exe).unwrap(), NEW_BINARY);
+}
+
+#[tokio::test]
+async fn given_releases_in_channels_when_checking_then_newest_of_channel_should_be_picked_with_notes()
+ {
+    let server = releases_server(&[
+        MockRelease {
+            tag: "v98.0.0",
+            prerelease: false,
+            body: "Added boards",
+            assets: &[],
+        },
+        MockRelease {
+            tag: "v99.0.0-beta.1",
+            prerelease: true,
+            body: "Trying a new database",
+            assets: &[],
+        },
+        MockRelease {
+            tag: "v97.1.0",
+            prerelease: false,
+            body: "",
+            assets: &[],
+        },
+        MockRelease {
+            tag: "not-a-version",
+            prerelease: false,
+            body: "",
+            assets: &[],
+        },
+    ])
+    .await;
+
+    let stable = check_for_updates(&server.url(), None, &ReleaseChannel::Stable)
+        .await
+        .unwrap();
+    assert!(stable.needs_update());
+    assert_eq!(stable.release().version().to_string(), "98.0.0");
+    assert_eq!(
+        stable.release_notes(),
+        "## v97.1.0\n\n## v98.0.0\n\nAdded boards"
+    );
+
+    let prerelease = check_for_updates(&server.url(), None, &ReleaseChannel::Prerelease)
+        .await
+        .unwrap();
+    assert_eq!(prerelease.release().version().to_string(), "99.0.0-beta.1");
+    assert!(
+        prerelease
+            .release_notes()
+            .ends_with("Trying a new database")
+    );
+}
+
+#[tokio::test]
+async fn given_specific_version_when_checking_then_that_release_should_be_picked() {
+    let server = releases_server(&[
+        MockRelease {
+            tag: "v98.0.0",
+            prerelease: false,
+            body: "",
+            assets: &[],
+        },
+        MockRelease {
+            tag: "v97.1.0",
+            prerelease: false,
+            body: "",
+            assets: &[],
+        },
+        MockRelease {
+            tag: "v1.0.0",
+            prerelease: false,
+            body: "",
+            assets: &[],
+        },
+    ])
+    .await;
+    let url = server.url();
+
+    let specific = check_for_updates(&url, Some("97.1.0"), &ReleaseChannel::Stable)
+        .await
+        .unwrap();
+    assert!(specific.needs_update());
+    assert_eq!(specific.release().version().to_string(), "97.1.0");
+
+    // older versions can be installed too, without release notes
+    let older = check_for_updates(&url, Some("v1.0.0"), &ReleaseChannel::Stable)
+        .await
+        .unwrap();
+    assert!(older.needs_update());
+    assert!(older.release_notes().is_empty());
+
+    assert!(
+        check_for_updates(&url, Some("2.0.0"), &ReleaseChannel::Stable)
+            .await
+            .is_err()
+    );
+    assert!(
+        check_for_updates(&url, Some("latest"), &ReleaseChannel::Stable)
+            .await
+            .is_err()
+    );
 }
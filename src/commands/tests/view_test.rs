@@ -1,4 +1,5 @@
 use super::super::super::{
+    cli::model::OutputFormat,
     db,
     model::{Task, TaskPriority},
 };
@@ -9,12 +10,12 @@ use surrealdb::sql::Datetime;
 #[tokio::test]
 async fn given_no_existing_task_when_viewing_a_task_then_error_should_be_returned() {
     let db = db::DB::new_test().await.expect("Failed to create db");
-    let res = view::run(&db, "randomID".to_string()).await;
+    let res = view::run(&db, "randomID".to_string(), false, OutputFormat::Table).await;
     assert!(res.is_err());
     let res_str = res.unwrap_err().to_string();
     assert_eq!(
         res_str,
-        "Task starting with id 'randomID' was not found (db error: No tasks found) (view error: Database error)"
+        "Task starting with id 'randomID' was not found (db error: No tasks found) (view error: No tasks found)"
     );
 }
 
@@ -30,12 +31,18 @@ async fn given_existing_task_when_wrong_id_is_passed_then_error_should_be_return
 
     let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
 
-    let res = view::run(&db, "DefinitelyNotTheID".to_string()).await;
+    let res = view::run(
+        &db,
+        "DefinitelyNotTheID".to_string(),
+        false,
+        OutputFormat::Table,
+    )
+    .await;
     assert!(res.is_err());
     let res_str = res.unwrap_err().to_string();
     assert_eq!(
         res_str,
-        "Task starting with id 'DefinitelyNotTheID' was not found (db error: No tasks found) (view error: Database error)"
+        "Task starting with id 'DefinitelyNotTheID' was not found (db error: No tasks found) (view error: No tasks found)"
     );
 }
 
@@ -57,12 +64,12 @@ async fn given_existing_tasks_when_unspecific_id_is_passed_then_error_should_be_
     let _: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
     let _: Vec<Task> = db.client.insert("task").content(task2).await.unwrap();
 
-    let res = view::run(&db, "".to_string()).await;
+    let res = view::run(&db, "".to_string(), false, OutputFormat::Table).await;
     assert!(res.is_err());
     let res_str = res.unwrap_err().to_string();
     assert_eq!(
         res_str,
-        "Multiple tasks found, provide more characters of the id (db error: Multiple tasks found) (view error: Database error)"
+        "Multiple tasks found, provide more characters of the id (db error: Multiple tasks found) (view error: Multiple tasks found)"
     );
 }
 
@@ -82,7 +89,7 @@ async fn given_existing_task_when_viewing_a_task_then_all_fields_should_be_retur
     let db_res: Vec<Task> = db.client.insert("task").content(task).await.unwrap();
     let id = db_res[0].id().unwrap();
 
-    let res = view::run(&db, id.clone()).await;
+    let res = view::run(&db, id.clone(), false, OutputFormat::Table).await;
     let res_str = res.unwrap().message().to_string();
     assert!(res_str.contains("test"));
     assert!(res_str.contains("Medium"));
@@ -90,3 +97,18 @@ async fn given_existing_task_when_viewing_a_task_then_all_fields_should_be_retur
     assert!(res_str.contains("1970-01-01T00:00:00Z"));
     assert!(res_str.contains("In Progress"));
 }
+
+#[tokio::test]
+async fn given_task_without_note_when_viewing_with_note_then_missing_note_should_be_mentioned() {
+    let db = db::DB::new_test().await.expect("Failed to create db");
+    let db_res: Vec<Task> = db
+        .client
+        .insert("task")
+        .content(Task::default())
+        .await
+        .unwrap();
+    let id = db_res[0].id().unwrap();
+
+    let res = view::run(&db, id, true, OutputFormat::Table).await.unwrap();
+    assert!(res.message().ends_with("\n\nThis task has no note"));
+}
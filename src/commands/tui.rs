@@ -0,0 +1,528 @@
+use super::super::{
+    cli::model::ListSort,
+    config::Config,
+    db::{DB, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TaskPriority, TaskState,
+        TmgrError, TmgrErrorKind,
+    },
+};
+use super::{add, complete, delete, list::sort_tasks, note, update};
+use clap::ValueEnum;
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use std::fmt;
+
+const HELP: &str = "←↓↑→ move  g group  / filter  a add  e edit  +/- priority  c complete  d delete  n note  q quit";
+
+pub(crate) async fn run(db: &DB, config: &Config) -> Result<CommandResult<()>, TuiError> {
+    let mut app = App::new(load_tasks(db).await?);
+    let mut terminal = ratatui::try_init().map_err(|e| TuiError {
+        kind: TuiErrorKind::TerminalError,
+        message: e.to_string(),
+    })?;
+    let res = event_loop(db, config, &mut terminal, &mut app).await;
+    // always give the terminal back, even if the event loop failed
+    ratatui::try_restore().map_err(|e| TuiError {
+        kind: TuiErrorKind::TerminalError,
+        message: e.to_string(),
+    })?;
+    res?;
+
+    Ok(CommandResult::new(String::new(), ()))
+}
+
+async fn event_loop(
+    db: &DB,
+    config: &Config,
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+) -> Result<(), TuiError> {
+    while !app.quit() {
+        terminal
+            .draw(|frame| draw(frame, app))
+            .map_err(|e| TuiError {
+                kind: TuiErrorKind::TerminalError,
+                message: e.to_string(),
+            })?;
+        let event = event::read().map_err(|e| TuiError {
+            kind: TuiErrorKind::TerminalError,
+            message: e.to_string(),
+        })?;
+        if let Event::Key(key) = event
+            && key.kind == KeyEventKind::Press
+            && let Some(action) = app.handle_key(key)
+        {
+            app.status = perform(db, config, terminal, action).await?;
+            app.set_tasks(load_tasks(db).await?);
+        }
+    }
+    Ok(())
+}
+
+/// Runs the action through the matching command, returning the message to show in the
+/// status line. Command errors are shown to the user rather than ending the session.
+///
+/// New tasks get the default priority and notes the default template of the config, as on
+/// the command line.
+async fn perform(
+    db: &DB,
+    config: &Config,
+    terminal: &mut DefaultTerminal,
+    action: Action,
+) -> Result<String, TuiError> {
+    let res: Result<String, TmgrError> = match action {
+        Action::Add(name) => add::run(db, name, config.default_priority(), None)
+            .await
+            .map(|r| r.message().to_string())
+            .map_err(TmgrError::from),
+        Action::Rename(id, name) => update::run(db, id, Some(name), None, None)
+            .await
+            .map(|r| r.message().to_string())
+            .map_err(TmgrError::from),
+        Action::SetPriority(id, priority) => update::run(db, id, None, Some(priority), None)
+            .await
+            .map(|r| r.message().to_string())
+            .map_err(TmgrError::from),
+        Action::Complete(id) => complete::run(db, id)
+            .await
+            .map(|r| r.message().to_string())
+            .map_err(TmgrError::from),
+        Action::Delete(id) => delete::run(db, id)
+            .await
+            .map(|r| r.message().to_string())
+            .map_err(TmgrError::from),
+        Action::OpenNote(id) => {
+            // hand the terminal over to the editor while it is open
+            ratatui::try_restore().map_err(|e| TuiError {
+                kind: TuiErrorKind::TerminalError,
+                message: e.to_string(),
+            })?;
+            let res = note::run(db, id, true, config.default_template()).await;
+            *terminal = ratatui::try_init().map_err(|e| TuiError {
+                kind: TuiErrorKind::TerminalError,
+                message: e.to_string(),
+            })?;
+            res.map(|r| format!("Note: {}", r.message()))
+                .map_err(TmgrError::from)
+        }
+    };
+    Ok(res.unwrap_or_else(|e| e.to_string()))
+}
+
+async fn load_tasks(db: &DB) -> Result<Vec<Task>, TuiError> {
+    let mut tasks: Vec<Task> = db
+        .client
+        .select("task")
+        .logged("SELECT * FROM task")
+        .await
+        .map_err(|e| TuiError {
+            kind: TuiErrorKind::DatabaseError(Some(e.into())),
+            message: "Failed to get all tasks".to_string(),
+        })?;
+    sort_tasks(&mut tasks, &ListSort::Priority);
+    Ok(tasks)
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let [board_area, status_area, help_area] = Layout::vertical([
+        Constraint::Min(3),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let columns = app.columns();
+    let column_areas =
+        Layout::horizontal(columns.iter().map(|_| Constraint::Fill(1))).split(board_area);
+    for (i, (column, area)) in columns.iter().zip(column_areas.iter()).enumerate() {
+        let active = i == app.column;
+        let items: Vec<ListItem> = column
+            .tasks()
+            .iter()
+            .map(|t| ListItem::new(format!("{} [{}]", t.name(), t.priority())))
+            .collect();
+        let border_style = if active {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(format!("{} ({})", column.title(), column.tasks().len())),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default();
+        if active && !column.tasks().is_empty() {
+            state.select(Some(app.row));
+        }
+        frame.render_stateful_widget(list, *area, &mut state);
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(app.status_line())), status_area);
+    frame.render_widget(
+        Paragraph::new(Line::from(HELP)).style(Style::default().fg(Color::DarkGray)),
+        help_area,
+    );
+}
+
+// --- App State ---
+/// How tasks are split into columns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Grouping {
+    /// In progress tasks and completed tasks
+    Completion,
+    /// One column per workflow state
+    State,
+    /// One column per priority level, for tasks that are not completed
+    Priority,
+}
+
+impl Grouping {
+    fn next(self) -> Self {
+        match self {
+            Grouping::Completion => Grouping::State,
+            Grouping::State => Grouping::Priority,
+            Grouping::Priority => Grouping::Completion,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum Mode {
+    Normal,
+    Filter,
+    Add(String),
+    Rename(String),
+    ConfirmDelete,
+}
+
+/// A change requested by the user, carried out by the matching command.
+#[derive(Debug, PartialEq)]
+pub(super) enum Action {
+    Add(String),
+    Rename(String, String),
+    SetPriority(String, TaskPriority),
+    Complete(String),
+    Delete(String),
+    OpenNote(String),
+}
+
+pub(super) struct Column<'a> {
+    title: String,
+    tasks: Vec<&'a Task>,
+}
+
+impl Column<'_> {
+    pub(super) fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub(super) fn tasks(&self) -> &[&Task] {
+        &self.tasks
+    }
+}
+
+pub(super) struct App {
+    tasks: Vec<Task>,
+    grouping: Grouping,
+    filter: String,
+    mode: Mode,
+    column: usize,
+    row: usize,
+    status: String,
+    quit: bool,
+}
+
+impl App {
+    pub(super) fn new(tasks: Vec<Task>) -> Self {
+        Self {
+            tasks,
+            grouping: Grouping::Completion,
+            filter: String::new(),
+            mode: Mode::Normal,
+            column: 0,
+            row: 0,
+            status: String::new(),
+            quit: false,
+        }
+    }
+
+    pub(super) fn set_tasks(&mut self, tasks: Vec<Task>) {
+        self.tasks = tasks;
+        self.clamp_selection();
+    }
+
+    pub(super) fn mode(&self) -> &Mode {
+        &self.mode
+    }
+
+    pub(super) fn quit(&self) -> bool {
+        self.quit
+    }
+
+    /// The tasks matching the filter, split into columns by the current grouping.
+    pub(super) fn columns(&self) -> Vec<Column<'_>> {
+        let filter = self.filter.to_lowercase();
+        let visible: Vec<&Task> = self
+            .tasks
+            .iter()
+            .filter(|t| {
+                filter.is_empty()
+                    || t.name().to_lowercase().contains(&filter)
+                    || t.description()
+                        .as_ref()
+                        .is_some_and(|d| d.to_lowercase().contains(&filter))
+                    || t.id().is_ok_and(|id| id.contains(&filter))
+            })
+            .collect();
+        let column = |title: String, keep: &dyn Fn(&Task) -> bool| Column {
+            title,
+            tasks: visible.iter().copied().filter(|t| keep(t)).collect(),
+        };
+
+        match self.grouping {
+            Grouping::Completion => vec![
+                column("In Progress".to_string(), &|t| t.completed_at().is_none()),
+                column("Completed".to_string(), &|t| t.completed_at().is_some()),
+            ],
+            Grouping::State => TaskState::value_variants()
+                .iter()
+                .map(|state| column(state.to_string(), &|t| t.state() == *state))
+                .collect(),
+            Grouping::Priority => TaskPriority::known_levels()
+                .iter()
+                .rev()
+                .map(|priority| {
+                    column(priority.to_string(), &|t| {
+                        t.completed_at().is_none() && t.priority() == priority
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    pub(super) fn selected(&self) -> Option<&Task> {
+        self.columns()
+            .get(self.column)
+            .and_then(|c| c.tasks.get(self.row).copied())
+    }
+
+    fn selected_id(&self) -> Option<String> {
+        self.selected().and_then(|t| t.id().ok())
+    }
+
+    fn clamp_selection(&mut self) {
+        let (column, rows) = {
+            let columns = self.columns();
+            let column = self.column.min(columns.len().saturating_sub(1));
+            (
+                column,
+                columns.get(column).map(|c| c.tasks.len()).unwrap_or(0),
+            )
+        };
+        self.column = column;
+        self.row = self.row.min(rows.saturating_sub(1));
+    }
+
+    fn status_line(&self) -> String {
+        match self.mode() {
+            Mode::Filter => format!("/{}", self.filter),
+            Mode::Add(buffer) => format!("New task: {buffer}"),
+            Mode::Rename(buffer) => format!("Rename: {buffer}"),
+            Mode::ConfirmDelete => format!(
+                "Delete '{}'? (y/n)",
+                self.selected().map(|t| t.name()).unwrap_or_default()
+            ),
+            Mode::Normal if !self.filter.is_empty() => {
+                format!("filter: {}  {}", self.filter, self.status)
+            }
+            Mode::Normal => self.status.clone(),
+        }
+    }
+
+    /// Updates the state for a key press, returning an action if the key requests a change
+    /// to a task.
+    pub(super) fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match &mut self.mode {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Filter => {
+                match key.code {
+                    KeyCode::Enter => self.mode = Mode::Normal,
+                    KeyCode::Esc => {
+                        self.filter.clear();
+                        self.mode = Mode::Normal;
+                    }
+                    KeyCode::Backspace => {
+                        self.filter.pop();
+                    }
+                    KeyCode::Char(c) => self.filter.push(c),
+                    _ => (),
+                }
+                self.clamp_selection();
+                None
+            }
+            Mode::Add(buffer) | Mode::Rename(buffer) => match key.code {
+                KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    None
+                }
+                KeyCode::Backspace => {
+                    buffer.pop();
+                    None
+                }
+                KeyCode::Char(c) => {
+                    buffer.push(c);
+                    None
+                }
+                KeyCode::Enter => {
+                    let mode = std::mem::replace(&mut self.mode, Mode::Normal);
+                    match mode {
+                        Mode::Add(name) if !name.trim().is_empty() => Some(Action::Add(name)),
+                        Mode::Rename(name) if !name.trim().is_empty() => {
+                            self.selected_id().map(|id| Action::Rename(id, name))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            Mode::ConfirmDelete => {
+                self.mode = Mode::Normal;
+                match key.code {
+                    KeyCode::Char('y') => self.selected_id().map(Action::Delete),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.clamp_selection();
+            }
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.column = self.column.saturating_sub(1);
+                self.clamp_selection();
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.column += 1;
+                self.clamp_selection();
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.row = self.row.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.row += 1;
+                self.clamp_selection();
+            }
+            KeyCode::Char('g') => {
+                self.grouping = self.grouping.next();
+                self.column = 0;
+                self.row = 0;
+            }
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Char('a') => self.mode = Mode::Add(String::new()),
+            KeyCode::Char('e') => {
+                if let Some(task) = self.selected() {
+                    self.mode = Mode::Rename(task.name().to_string());
+                }
+            }
+            KeyCode::Char('d') if self.selected().is_some() => self.mode = Mode::ConfirmDelete,
+            KeyCode::Char('c') => return self.selected_id().map(Action::Complete),
+            KeyCode::Char('n') | KeyCode::Enter => return self.selected_id().map(Action::OpenNote),
+            KeyCode::Char('+') | KeyCode::Char('-') => {
+                let task = self.selected()?;
+                let levels = TaskPriority::known_levels();
+                let current = levels.iter().position(|p| p == task.priority())?;
+                let next = if key.code == KeyCode::Char('+') {
+                    levels.get(current + 1)
+                } else {
+                    current.checked_sub(1).and_then(|i| levels.get(i))
+                }?;
+                return Some(Action::SetPriority(task.id().ok()?, next.clone()));
+            }
+            _ => (),
+        }
+        None
+    }
+}
+
+// --- Tui Errors ---
+#[derive(Debug)]
+pub enum TuiErrorKind {
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    TerminalError,
+}
+
+#[derive(Debug)]
+pub struct TuiError {
+    kind: TuiErrorKind,
+    message: String,
+}
+
+impl fmt::Display for TuiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (tui error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for TuiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            TuiErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TuiErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TuiErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            TuiErrorKind::TerminalError => write!(f, "Terminal error"),
+        }
+    }
+}
+
+impl ErrorCode for TuiErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            TuiErrorKind::DatabaseError(_) => "E-TUI-001",
+            TuiErrorKind::TerminalError => "E-TUI-002",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            TuiErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+            TuiErrorKind::TerminalError => ErrorCategory::IO,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            TuiErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            _ => None,
+        }
+    }
+}
+
+impl From<TuiError> for TmgrError {
+    fn from(err: TuiError) -> Self {
+        TmgrError::new(TmgrErrorKind::TuiCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
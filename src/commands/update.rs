@@ -1,10 +1,16 @@
 use super::super::{
-    db::DB,
-    model::{CommandResult, Task, TaskPriority, TmgrError, TmgrErrorKind},
+    db::{DB, DBError, Logged},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, Task, TaskPriority, TmgrError,
+        TmgrErrorKind,
+    },
 };
+use super::note::sync_header;
+use super::task_editor::{TaskFields, edit_task};
 use std::{collections::BTreeMap, fmt, iter::FromIterator};
 
-pub(crate) async fn run(
+/// Changes the given fields of the task whose id starts with `id`.
+pub async fn run(
     db: &DB,
     id: String,
     name: Option<String>,
@@ -22,23 +28,27 @@ pub(crate) async fn run(
         .select_task_by_partial_id(&id)
         .await
         .map_err(|e| UpdateError {
-            kind: UpdateErrorKind::DatabaseError,
             message: e.to_string(),
+            kind: UpdateErrorKind::TaskLookup(e),
         })?;
     let task_id = task.id().map_err(|e| UpdateError {
         kind: UpdateErrorKind::BadTaskId,
         message: e.to_string(),
     })?;
 
-    let update_map: BTreeMap<&str, String> = FromIterator::from_iter(
+    // An empty description clears it, as a task without one has no description field
+    let update_map: BTreeMap<&str, Option<String>> = FromIterator::from_iter(
         [
-            name.as_ref().map(|name| ("name", name.to_string())),
+            name.as_ref().map(|name| ("name", Some(name.to_string()))),
             priority
                 .as_ref()
-                .map(|priority| ("priority", priority.into())),
-            description
-                .as_ref()
-                .map(|description| ("description", description.to_string())),
+                .map(|priority| ("priority", Some(priority.into()))),
+            description.as_ref().map(|description| {
+                (
+                    "description",
+                    (!description.is_empty()).then(|| description.to_string()),
+                )
+            }),
         ]
         .into_iter()
         .flatten(),
@@ -49,27 +59,76 @@ pub(crate) async fn run(
         .client
         .update(("task", &task_id))
         .merge(update_map)
+        .logged(&format!("UPDATE task:{task_id} MERGE $data"))
         .await
-        .map_err(|_| UpdateError {
-            kind: UpdateErrorKind::DatabaseError,
+        .map_err(|e| UpdateError {
+            kind: UpdateErrorKind::DatabaseError(Some(e.into())),
             message: "Failed to update task".to_string(),
         })?
         .ok_or_else(|| UpdateError {
-            kind: UpdateErrorKind::DatabaseError,
+            kind: UpdateErrorKind::DatabaseError(None),
             message: "Failed to update task".to_string(),
         })?;
 
+    sync_header(&updated_task).map_err(|e| UpdateError {
+        kind: UpdateErrorKind::NoteError,
+        message: format!("Task '{task_id}' was updated, but its note was not: {e}"),
+    })?;
+
     Ok(CommandResult::new(
         format!("Successfully updated task '{task_id}'"),
         updated_task,
     ))
 }
 
+/// Updates a task after letting the user edit it with `$EDITOR`.
+///
+/// The document opened in the editor is pre-filled with the current values of the task,
+/// overridden by any values given. Only the fields that differ from the task are updated.
+pub(crate) async fn run_in_editor(
+    db: &DB,
+    id: String,
+    name: Option<String>,
+    priority: Option<TaskPriority>,
+    description: Option<String>,
+) -> Result<CommandResult<Task>, UpdateError> {
+    let task = db
+        .select_task_by_partial_id(&id)
+        .await
+        .map_err(|e| UpdateError {
+            message: e.to_string(),
+            kind: UpdateErrorKind::TaskLookup(e),
+        })?;
+    let task_id = task.id().map_err(|e| UpdateError {
+        kind: UpdateErrorKind::BadTaskId,
+        message: e.to_string(),
+    })?;
+
+    let fields = edit_task(&TaskFields {
+        name: name.unwrap_or(task.name().to_string()),
+        priority: priority.unwrap_or(task.priority().clone()),
+        description: description.or(task.description().clone()),
+    })
+    .map_err(|e| UpdateError {
+        kind: UpdateErrorKind::EditorError,
+        message: e.to_string(),
+    })?;
+
+    let name = (fields.name != task.name()).then_some(fields.name);
+    let priority = (fields.priority != *task.priority()).then_some(fields.priority);
+    let description =
+        (fields.description != *task.description()).then(|| fields.description.unwrap_or_default());
+    run(db, task_id, name, priority, description).await
+}
+
 #[derive(Debug)]
 pub enum UpdateErrorKind {
     BadTaskId,
-    DatabaseError,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    EditorError,
     NoFieldsToUpdate,
+    NoteError,
+    TaskLookup(DBError),
 }
 
 #[derive(Debug)]
@@ -84,12 +143,63 @@ impl fmt::Display for UpdateError {
     }
 }
 
+impl std::error::Error for UpdateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            UpdateErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            UpdateErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for UpdateErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             UpdateErrorKind::BadTaskId => write!(f, "Bad task id"),
-            UpdateErrorKind::DatabaseError => write!(f, "Database error"),
+            UpdateErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            UpdateErrorKind::EditorError => write!(f, "Editor error"),
             UpdateErrorKind::NoFieldsToUpdate => write!(f, "No fields to update"),
+            UpdateErrorKind::NoteError => write!(f, "Note error"),
+            UpdateErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+        }
+    }
+}
+
+impl ErrorCode for UpdateErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            UpdateErrorKind::BadTaskId => "E-UPDATE-001",
+            UpdateErrorKind::DatabaseError(_) => "E-UPDATE-002",
+            UpdateErrorKind::EditorError => "E-UPDATE-003",
+            UpdateErrorKind::NoFieldsToUpdate => "E-UPDATE-004",
+            UpdateErrorKind::NoteError => "E-UPDATE-005",
+            UpdateErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            UpdateErrorKind::BadTaskId | UpdateErrorKind::DatabaseError(_) => {
+                ErrorCategory::Internal
+            }
+            UpdateErrorKind::EditorError | UpdateErrorKind::NoteError => ErrorCategory::IO,
+            UpdateErrorKind::NoFieldsToUpdate => ErrorCategory::Validation,
+            UpdateErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            UpdateErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            UpdateErrorKind::EditorError => Some(
+                "Set the editor setting of the config file, or the $EDITOR environment variable",
+            ),
+            UpdateErrorKind::NoFieldsToUpdate => {
+                Some("Pass --name, --priority or --description, or --editor to edit the task")
+            }
+            UpdateErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
         }
     }
 }
@@ -97,5 +207,7 @@ impl fmt::Display for UpdateErrorKind {
 impl From<UpdateError> for TmgrError {
     fn from(err: UpdateError) -> Self {
         TmgrError::new(TmgrErrorKind::UpdateCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
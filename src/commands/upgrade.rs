@@ -1,130 +1,303 @@
 use super::super::{
-    cli::model::TmgrVersion,
+    cli::model::{ReleaseChannel, TmgrVersion},
     db::DB,
-    model::{CommandResult, TmgrError, TmgrErrorKind},
+    model::{CommandResult, ErrorCategory, ErrorCode, TmgrError, TmgrErrorKind},
 };
-use super::migrate;
-use directories::UserDirs;
+use super::{markdown::render_for_terminal, migrate};
+use minisign_verify::{PublicKey, Signature};
 use reqwest::header::USER_AGENT;
 use semver::Version;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use std::{
     env::current_exe,
     fmt, fs,
     fs::{File, Permissions},
     io::Write,
-    num::ParseIntError,
     os::unix::fs::PermissionsExt,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
+use tracing::info;
 
-pub(crate) async fn run(db: &DB) -> Result<CommandResult<bool>, UpdateError> {
+/// The target triple tmgr was built for, which selects the release asset to download.
+pub(super) const TARGET: &str = env!("TMGR_TARGET");
+
+/// The minisign public key release assets are signed with. When set at build time, upgrades
+/// require a valid signature.
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("TMGR_RELEASE_PUBLIC_KEY");
+
+pub(crate) async fn run(
+    db: &DB,
+    check: bool,
+    version: Option<String>,
+    channel: ReleaseChannel,
+    api_url: Option<String>,
+) -> Result<CommandResult<bool>, UpdateError> {
+    let api_url = api_url.unwrap_or_else(default_release_api_url);
     println!("Checking repository for updates...");
-    let update_info = check_for_updates().await?;
+    let update_info = check_for_updates(&api_url, version.as_deref(), &channel).await?;
+    let current = update_info.current_version();
+    let target = update_info.release().version();
 
-    if update_info.needs_update() {
-        let new_binary_download_path =
-            download_binary_to_downloads_folder(update_info.binary_download_url()).await?;
-        let path_to_existing_executable = current_exe().map_err(|e| UpdateError {
-            message: e.to_string(),
-            kind: UpdateErrorKind::UnableToDetermineTmgrExecutablePath,
-        })?;
-        delete_existing_binary(&path_to_existing_executable)?;
-        // move new binary from download folder to bin of current executable
-        move_new_binary(new_binary_download_path, path_to_existing_executable)?;
-        migrate_database_if_major_version_changed(&update_info, db).await?;
-        Ok(CommandResult::new(
+    if !update_info.needs_update() {
+        let message = match version {
+            Some(_) => format!("Already on v{current}"),
+            None => "Already on latest version".to_string(),
+        };
+        return Ok(CommandResult::new(message, false));
+    }
+    let notes = update_info.release_notes();
+    let notes = if notes.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\n\n{}",
+            render_for_terminal(notes, colored::control::SHOULD_COLORIZE.should_colorize())
+        )
+    };
+    if check {
+        return Ok(CommandResult::new(
             format!(
-                "Update complete: v{} -> v{}",
-                update_info.current_version(),
-                update_info.latest_version()
+                "Update available: v{current} -> v{target} ({channel} channel), run 'tmgr upgrade' to install it{notes}"
             ),
             true,
-        ))
-    } else {
-        Ok(CommandResult::new(
-            "Already on latest version".to_string(),
-            true,
-        ))
+        ));
     }
+
+    let path_to_existing_executable = current_exe().map_err(|e| UpdateError {
+        message: e.to_string(),
+        kind: UpdateErrorKind::UnableToDetermineTmgrExecutablePath,
+    })?;
+    info!(
+        "The executable is '{}'",
+        path_to_existing_executable.display()
+    );
+    install_release(
+        update_info.release(),
+        &path_to_existing_executable,
+        RELEASE_PUBLIC_KEY,
+    )
+    .await?;
+    migrate_database_if_major_version_changed(&update_info, db).await?;
+    Ok(CommandResult::new(
+        format!(
+            "Update complete: v{current} -> v{target} (run 'tmgr upgrade --rollback' to go back){notes}"
+        ),
+        true,
+    ))
 }
 
-async fn check_for_updates() -> Result<UpdateInfo, UpdateError> {
-    // get latest release from GitHub
+/// Swaps the executable with the one replaced by the last upgrade.
+pub(crate) fn rollback() -> Result<CommandResult<bool>, UpdateError> {
+    let path_to_existing_executable = current_exe().map_err(|e| UpdateError {
+        message: e.to_string(),
+        kind: UpdateErrorKind::UnableToDetermineTmgrExecutablePath,
+    })?;
+    info!(
+        "The executable is '{}'",
+        path_to_existing_executable.display()
+    );
+    let previous = rollback_binary(&path_to_existing_executable)?;
+    Ok(CommandResult::new(
+        format!(
+            "Rolled back to the previous version, the replaced version was kept as '{}'",
+            previous.display()
+        ),
+        true,
+    ))
+}
+
+/// Finds the release to upgrade to: the given version, or else the newest release of the
+/// channel. The release notes of the releases in between are gathered along the way.
+pub(super) async fn check_for_updates(
+    api_url: &str,
+    version: Option<&str>,
+    channel: &ReleaseChannel,
+) -> Result<UpdateInfo, UpdateError> {
+    let releases = fetch_releases(api_url).await?;
+
+    // parse current version
+    let current_version = Version::parse(env!("CARGO_PKG_VERSION")).map_err(|e| UpdateError {
+        message: e.to_string(),
+        kind: UpdateErrorKind::NoCurrentVersion,
+    })?;
+
+    let release = match version {
+        Some(version) => {
+            let version =
+                Version::parse(version.trim_start_matches('v')).map_err(|e| UpdateError {
+                    message: format!("'{version}' is not a version like 1.2.3: {e}"),
+                    kind: UpdateErrorKind::InvalidVersion,
+                })?;
+            releases
+                .iter()
+                .find(|r| r.version == version)
+                .ok_or_else(|| UpdateError {
+                    message: format!("no release found for v{version}"),
+                    kind: UpdateErrorKind::NoLatestVersion,
+                })?
+        }
+        None => releases
+            .iter()
+            .filter(|r| channel.includes(r))
+            .max_by(|a, b| a.version.cmp(&b.version))
+            .ok_or_else(|| UpdateError {
+                message: format!("no release found in the {channel} channel"),
+                kind: UpdateErrorKind::NoLatestVersion,
+            })?,
+    };
+    let needs_update = match version {
+        Some(_) => release.version != current_version,
+        None => release.version > current_version,
+    };
+    let release_notes = release_notes(&releases, &current_version, &release.version);
+
+    Ok(UpdateInfo {
+        current_version,
+        needs_update,
+        release: release.clone(),
+        release_notes,
+    })
+}
+
+/// The published releases of the release API, skipping drafts and tags that are not versions.
+async fn fetch_releases(api_url: &str) -> Result<Vec<Release>, UpdateError> {
     let client = reqwest::Client::new();
     let res = client
-        .get(latest_release_url())
+        .get(format!(
+            "{}/releases?per_page=100",
+            api_url.trim_end_matches('/')
+        ))
         .header(USER_AGENT, "tmgr-rust")
         .send()
         .await
+        .and_then(|res| res.error_for_status())
         .map_err(|e| UpdateError {
             message: e.to_string(),
             kind: UpdateErrorKind::RepoCheckFail,
         })?;
-    let latest_release = res
-        .json::<GithubReleaseResponse>()
+    let releases = res
+        .json::<Vec<GithubReleaseResponse>>()
         .await
         .map_err(|e| UpdateError {
             message: e.to_string(),
             kind: UpdateErrorKind::GitHibResponseToRustStructConversionFail,
         })?;
+    Ok(releases
+        .into_iter()
+        .filter(|r| !r.draft)
+        .filter_map(|r| {
+            let version = Version::parse(r.tag_name.trim_start_matches('v')).ok()?;
+            Some(Release {
+                prerelease: r.prerelease || !version.pre.is_empty(),
+                version,
+                notes: r.body.unwrap_or_default(),
+                assets: r.assets,
+            })
+        })
+        .collect())
+}
 
-    // parse latest version
-    let latest_version_github =
-        Version::parse(&latest_release.tag_name.replace('v', "")).map_err(|e| UpdateError {
-            message: e.to_string(),
-            kind: UpdateErrorKind::NoLatestVersion,
-        })?;
+/// The notes of the releases after `current` up to and including `target`, oldest first.
+pub(super) fn release_notes(releases: &[Release], current: &Version, target: &Version) -> String {
+    let mut between: Vec<&Release> = releases
+        .iter()
+        .filter(|r| &r.version > current && &r.version <= target)
+        .collect();
+    between.sort_by(|a, b| a.version.cmp(&b.version));
+    between
+        .iter()
+        .map(|r| {
+            let notes = r.notes.trim();
+            if notes.is_empty() {
+                format!("## v{}", r.version)
+            } else {
+                format!("## v{}\n\n{notes}", r.version)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
 
-    // parse current version
-    let current_version = Version::parse(env!("CARGO_PKG_VERSION")).map_err(|e| UpdateError {
-        message: e.to_string(),
-        kind: UpdateErrorKind::NoCurrentVersion,
+/// Replaces the executable with the asset of a release for this target, once its checksum
+/// (and signature, with a public key) is verified.
+pub(super) async fn install_release(
+    release: &Release,
+    executable: &Path,
+    public_key: Option<&str>,
+) -> Result<(), UpdateError> {
+    if release.assets.is_empty() {
+        return Err(UpdateError {
+            message: format!("no assets found for v{}", release.version),
+            kind: UpdateErrorKind::NoDownloadLinkFromGitHub,
+        });
+    }
+    let name = asset_name();
+    let find_asset = |asset_name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name == asset_name)
+            .map(|a| a.browser_download_url.as_str())
+    };
+    let binary_download_url = find_asset(&name).ok_or_else(|| UpdateError {
+        message: format!(
+            "no asset named '{name}' in v{}, available assets: {}",
+            release.version,
+            release
+                .assets
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ),
+        kind: UpdateErrorKind::NoAssetForTarget,
     })?;
+    let checksum_download_url =
+        find_asset(&format!("{name}.sha256")).ok_or_else(|| UpdateError {
+            message: format!("no '{name}.sha256' asset in v{}", release.version),
+            kind: UpdateErrorKind::MissingChecksum,
+        })?;
 
-    if !latest_release.assets.is_empty() {
-        Ok(UpdateInfo {
-            current_version: current_version.to_string(),
-            needs_update: latest_version_github > current_version,
-            binary_download_url: latest_release.assets[0].browser_download_url.clone(),
-            latest_version: latest_version_github.to_string(),
-        })
-    } else {
-        Err(UpdateError {
-            message: "no assets found".to_string(),
-            kind: UpdateErrorKind::NoDownloadLinkFromGitHub,
-        })
+    let binary = download(binary_download_url).await?;
+    let checksum = download(checksum_download_url).await?;
+    verify_checksum(&binary, &String::from_utf8_lossy(&checksum))?;
+    if let Some(public_key) = public_key {
+        let signature_url = find_asset(&format!("{name}.minisig")).ok_or(UpdateError {
+            message: format!("no signature found for '{name}'"),
+            kind: UpdateErrorKind::MissingSignature,
+        })?;
+        let signature = download(signature_url).await?;
+        verify_signature(&binary, &String::from_utf8_lossy(&signature), public_key)?;
     }
+    replace_binary(executable, &binary)
 }
 
-pub(super) fn latest_release_url() -> String {
+/// The release API of the repository tmgr is published from, which lists its releases at
+/// `/releases`.
+pub(super) fn default_release_api_url() -> String {
     let repo_link = env!("CARGO_PKG_REPOSITORY");
     let url: Vec<&str> = repo_link.split('/').rev().collect();
     let repo = url[0];
     let github_account = url[1];
-    format!("https://api.github.com/repos/{github_account}/{repo}/releases/latest",)
+    format!("https://api.github.com/repos/{github_account}/{repo}")
 }
 
-async fn download_binary_to_downloads_folder(
-    binary_download_url: &str,
-) -> Result<PathBuf, UpdateError> {
-    // get download directory of the current system
-    let system_file_structure = UserDirs::new().ok_or(UpdateError {
-        message: "Unable to determine system's file structure".to_string(),
-        kind: UpdateErrorKind::UnableToDetermineFileStructure,
-    })?;
-    let download_dir_path = system_file_structure.download_dir().ok_or(UpdateError {
-        message: "Unable to determine download directory".to_string(),
-        kind: UpdateErrorKind::UnableToDetermineFileStructure,
-    })?;
+/// The name of the release asset holding the executable for this target, as in
+/// `tmgr-aarch64-apple-darwin`. Its checksum is in the asset of the same name ending with
+/// `.sha256`, and its signature in the one ending with `.minisig`.
+pub(super) fn asset_name() -> String {
+    format!("tmgr-{TARGET}")
+}
 
-    // download binary from GitHUb
+async fn download(url: &str) -> Result<Vec<u8>, UpdateError> {
     let client = reqwest::Client::new();
     let res = client
-        .get(binary_download_url)
+        .get(url)
         .header(USER_AGENT, "tmgr-rust")
         .send()
         .await
+        .and_then(|res| res.error_for_status())
         .map_err(|e| UpdateError {
             message: e.to_string(),
             kind: UpdateErrorKind::BinaryDownloadFail,
@@ -135,52 +308,152 @@ async fn download_binary_to_downloads_folder(
         message: e.to_string(),
         kind: UpdateErrorKind::CorruptedBinaryDownload,
     })?;
+    Ok(bytes.to_vec())
+}
 
-    // write bytes to new file called tmgr_new in Downloads folder
-    let full_path = PathBuf::from(download_dir_path).join("tmgr_new");
-    let mut f = File::create(&full_path).map_err(|e| UpdateError {
+/// Checks the SHA-256 digest of a download against a checksum file in the format written by
+/// `sha256sum`, where the hex digest comes first.
+pub(super) fn verify_checksum(bytes: &[u8], checksum_file: &str) -> Result<(), UpdateError> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .filter(|digest| digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or(UpdateError {
+            message: "the checksum asset does not hold a SHA-256 digest".to_string(),
+            kind: UpdateErrorKind::MissingChecksum,
+        })?;
+    let actual: String = Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    if actual != expected.to_lowercase() {
+        return Err(UpdateError {
+            message: format!("expected SHA-256 {expected}, downloaded {actual}"),
+            kind: UpdateErrorKind::ChecksumMismatch,
+        });
+    }
+    Ok(())
+}
+
+/// Checks the minisign signature of a download with the given base64 public key.
+pub(super) fn verify_signature(
+    bytes: &[u8],
+    signature: &str,
+    public_key: &str,
+) -> Result<(), UpdateError> {
+    let invalid = |e: minisign_verify::Error| UpdateError {
         message: e.to_string(),
-        kind: UpdateErrorKind::CreateFileFail,
+        kind: UpdateErrorKind::InvalidSignature,
+    };
+    let public_key = PublicKey::from_base64(public_key).map_err(invalid)?;
+    let signature = Signature::decode(signature).map_err(invalid)?;
+    public_key.verify(bytes, &signature, false).map_err(invalid)
+}
+
+/// The path the previous executable is kept at after an upgrade, as in `tmgr.old`.
+pub(super) fn previous_binary_path(executable: &Path) -> PathBuf {
+    let name = executable
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or("tmgr".to_string());
+    executable.with_file_name(format!("{name}.old"))
+}
+
+/// Replaces the executable with a new binary, keeping the current one as `tmgr.old`.
+///
+/// The binary is written next to the executable first and then renamed over it, so the
+/// executable is never missing, even if the upgrade fails half way.
+pub(super) fn replace_binary(executable: &Path, binary: &[u8]) -> Result<(), UpdateError> {
+    let staged = staging_path(executable, "new");
+    write_executable(&staged, binary).inspect_err(|_| {
+        let _ = fs::remove_file(&staged);
     })?;
-    f.write_all(&bytes).map_err(|e| UpdateError {
-        message: e.to_string(),
-        kind: UpdateErrorKind::CreateFileFail,
+    keep_copy(executable, &previous_binary_path(executable)).inspect_err(|_| {
+        let _ = fs::remove_file(&staged);
     })?;
-
-    // make new file executable
-    f.set_permissions(Permissions::from_mode(0o751))
-        .map_err(|e| UpdateError {
+    fs::rename(&staged, executable).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        UpdateError {
             message: e.to_string(),
-            kind: UpdateErrorKind::CreateFileFail,
+            kind: UpdateErrorKind::UnableToReplaceBinary,
+        }
+    })
+}
+
+/// Swaps the executable with the one kept by the last upgrade, so running it again undoes
+/// the rollback. Returns the path the replaced executable is kept at.
+pub(super) fn rollback_binary(executable: &Path) -> Result<PathBuf, UpdateError> {
+    let previous = previous_binary_path(executable);
+    if !previous.is_file() {
+        return Err(UpdateError {
+            message: format!("'{}' does not exist", previous.display()),
+            kind: UpdateErrorKind::NoPreviousVersion,
+        });
+    }
+    let staged = staging_path(executable, "rollback");
+    keep_copy(executable, &staged)?;
+    fs::rename(&previous, executable)
+        .and_then(|_| fs::rename(&staged, &previous))
+        .map_err(|e| {
+            let _ = fs::remove_file(&staged);
+            UpdateError {
+                message: e.to_string(),
+                kind: UpdateErrorKind::UnableToReplaceBinary,
+            }
         })?;
-    Ok(full_path)
+    Ok(previous)
 }
 
-pub(super) fn delete_existing_binary(existing_binary_path: &PathBuf) -> Result<(), UpdateError> {
-    fs::remove_file(existing_binary_path).map_err(|e| UpdateError {
-        message: e.to_string(),
-        kind: UpdateErrorKind::UnableToDeleteExistingBinary,
-    })
+fn staging_path(executable: &Path, suffix: &str) -> PathBuf {
+    let name = executable
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or("tmgr".to_string());
+    executable.with_file_name(format!(".{name}.{suffix}"))
 }
 
-pub(super) fn move_new_binary(
-    existing_binary_path: PathBuf,
-    new_binary_path: PathBuf,
-) -> Result<(), UpdateError> {
-    fs::rename(existing_binary_path, new_binary_path).map_err(|e| UpdateError {
-        message: e.to_string(),
-        kind: UpdateErrorKind::UnableToMoveBinary,
-    })
+fn write_executable(path: &Path, bytes: &[u8]) -> Result<(), UpdateError> {
+    let create_fail = |e: std::io::Error| UpdateError {
+        message: format!("{}: {e}", path.display()),
+        kind: UpdateErrorKind::CreateFileFail,
+    };
+    let mut f = File::create(path).map_err(create_fail)?;
+    f.write_all(bytes).map_err(create_fail)?;
+    // make new file executable
+    f.set_permissions(Permissions::from_mode(0o751))
+        .map_err(create_fail)?;
+    f.sync_all().map_err(create_fail)
+}
+
+/// Keeps a copy of `from` at `to`, replacing it. A hard link is used where possible, as it
+/// is cheap and keeps the file as it is.
+fn keep_copy(from: &Path, to: &Path) -> Result<(), UpdateError> {
+    if to.exists() {
+        fs::remove_file(to).map_err(|e| UpdateError {
+            message: format!("{}: {e}", to.display()),
+            kind: UpdateErrorKind::UnableToKeepPreviousBinary,
+        })?;
+    }
+    fs::hard_link(from, to)
+        .or_else(|_| fs::copy(from, to).map(|_| ()))
+        .map_err(|e| UpdateError {
+            message: format!("{}: {e}", to.display()),
+            kind: UpdateErrorKind::UnableToKeepPreviousBinary,
+        })
 }
 
 async fn migrate_database_if_major_version_changed(
     update_info: &UpdateInfo,
     db: &DB,
 ) -> Result<(), UpdateError> {
-    let current_version = get_major_version(update_info.current_version())?;
-    let latest_version = get_major_version(update_info.latest_version())?;
+    let current_version = update_info.current_version().major;
+    let latest_version = update_info.release().version().major;
     if latest_version > current_version {
         println!("Migrating database...");
+        let current_version = u32::try_from(current_version).map_err(|e| UpdateError {
+            message: e.to_string(),
+            kind: UpdateErrorKind::NoCurrentVersion,
+        })?;
         let res = migrate::run(db, TmgrVersion::from(current_version))
             .await
             .map_err(|e| UpdateError {
@@ -196,59 +469,74 @@ async fn migrate_database_if_major_version_changed(
     Ok(())
 }
 
-fn get_major_version(version: &str) -> Result<u32, UpdateError> {
-    version
-        .split('.')
-        .next()
-        .ok_or_else(|| UpdateError {
-            message: "Unable to determine major version of current tmgr install".to_string(),
-            kind: UpdateErrorKind::NoCurrentVersion,
-        })?
-        .parse()
-        .map_err(|e: ParseIntError| UpdateError {
-            message: e.to_string(),
-            kind: UpdateErrorKind::NoCurrentVersion,
-        })
+/// A published release of tmgr.
+#[derive(Clone, Debug)]
+pub(super) struct Release {
+    version: Version,
+    prerelease: bool,
+    notes: String,
+    assets: Vec<Asset>,
 }
 
-struct UpdateInfo {
-    binary_download_url: String,
-    latest_version: String,
-    needs_update: bool,
-    current_version: String,
+impl Release {
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
 }
 
-impl UpdateInfo {
-    pub fn binary_download_url(&self) -> &str {
-        &self.binary_download_url
+impl ReleaseChannel {
+    fn includes(&self, release: &Release) -> bool {
+        match self {
+            ReleaseChannel::Stable => !release.prerelease,
+            ReleaseChannel::Prerelease => true,
+        }
     }
+}
 
-    pub fn latest_version(&self) -> &str {
-        &self.latest_version
+pub(super) struct UpdateInfo {
+    current_version: Version,
+    needs_update: bool,
+    release: Release,
+    release_notes: String,
+}
+
+impl UpdateInfo {
+    pub fn current_version(&self) -> &Version {
+        &self.current_version
     }
 
     pub fn needs_update(&self) -> bool {
         self.needs_update
     }
 
-    pub fn current_version(&self) -> &str {
-        &self.current_version
+    pub fn release(&self) -> &Release {
+        &self.release
+    }
+
+    pub fn release_notes(&self) -> &str {
+        &self.release_notes
     }
 }
 
 #[derive(Debug)]
 enum UpdateErrorKind {
     RepoCheckFail,
+    InvalidVersion,
     NoDownloadLinkFromGitHub,
     NoCurrentVersion,
     NoLatestVersion,
     GitHibResponseToRustStructConversionFail,
-    UnableToDetermineFileStructure,
+    NoAssetForTarget,
     BinaryDownloadFail,
     CorruptedBinaryDownload,
+    MissingChecksum,
+    ChecksumMismatch,
+    MissingSignature,
+    InvalidSignature,
     CreateFileFail,
-    UnableToDeleteExistingBinary,
-    UnableToMoveBinary,
+    UnableToKeepPreviousBinary,
+    UnableToReplaceBinary,
+    NoPreviousVersion,
     UnableToDetermineTmgrExecutablePath,
     UnableToMigrateDatabase,
 }
@@ -263,6 +551,8 @@ pub struct UpdateError {
 impl From<UpdateError> for TmgrError {
     fn from(err: UpdateError) -> Self {
         TmgrError::new(TmgrErrorKind::UpgradeCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
 
@@ -273,6 +563,7 @@ impl fmt::Display for UpdateErrorKind {
                 f,
                 "Unable to retrieve fetch tmgr GitHub repo, try again later"
             ),
+            UpdateErrorKind::InvalidVersion => write!(f, "Invalid version"),
             UpdateErrorKind::NoDownloadLinkFromGitHub => {
                 write!(f, "No download url for the tmgr executable found on GitHub")
             }
@@ -280,14 +571,15 @@ impl fmt::Display for UpdateErrorKind {
                 write!(f, "Unable to determine current version of tmgr")
             }
             UpdateErrorKind::NoLatestVersion => {
-                write!(f, "Unable to determine latest version of tmgr from GitHub")
+                write!(f, "Unable to find the requested version of tmgr on GitHub")
             }
             UpdateErrorKind::GitHibResponseToRustStructConversionFail => {
                 write!(f, "Unable to convert GitHub response to Rust struct")
             }
-            UpdateErrorKind::UnableToDetermineFileStructure => {
-                write!(f, "Unable to determine system's file structure")
-            }
+            UpdateErrorKind::NoAssetForTarget => write!(
+                f,
+                "No tmgr executable for this platform found in the latest release"
+            ),
             UpdateErrorKind::BinaryDownloadFail => write!(
                 f,
                 "Unable to retrieve fetch tmgr the latest executable from GitHub repo, try again later"
@@ -295,15 +587,34 @@ impl fmt::Display for UpdateErrorKind {
             UpdateErrorKind::CorruptedBinaryDownload => {
                 write!(f, "Unable to convert downloaded executable to bytes")
             }
+            UpdateErrorKind::MissingChecksum => {
+                write!(f, "No SHA-256 checksum found for the downloaded executable")
+            }
+            UpdateErrorKind::ChecksumMismatch => write!(
+                f,
+                "Downloaded executable does not match its SHA-256 checksum"
+            ),
+            UpdateErrorKind::MissingSignature => {
+                write!(f, "No signature found for the downloaded executable")
+            }
+            UpdateErrorKind::InvalidSignature => {
+                write!(f, "Downloaded executable has an invalid signature")
+            }
             UpdateErrorKind::CreateFileFail => {
-                write!(f, "Unable to create file in downloads folder")
+                write!(
+                    f,
+                    "Unable to write downloaded executable next to current executable"
+                )
             }
-            UpdateErrorKind::UnableToDeleteExistingBinary => {
-                write!(f, "Unable to delete existing executable")
+            UpdateErrorKind::UnableToKeepPreviousBinary => {
+                write!(f, "Unable to keep a copy of the existing executable")
             }
-            UpdateErrorKind::UnableToMoveBinary => write!(
+            UpdateErrorKind::UnableToReplaceBinary => {
+                write!(f, "Unable to replace the existing executable")
+            }
+            UpdateErrorKind::NoPreviousVersion => write!(
                 f,
-                "Unable to move downloaded executable to bin of current executable"
+                "No previous version of tmgr to roll back to, one is kept after an upgrade"
             ),
             UpdateErrorKind::UnableToDetermineTmgrExecutablePath => {
                 write!(f, "Unable to determine path to existing tmgr executable")
@@ -315,24 +626,106 @@ impl fmt::Display for UpdateErrorKind {
     }
 }
 
+impl ErrorCode for UpdateErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            UpdateErrorKind::RepoCheckFail => "E-UPGRADE-001",
+            UpdateErrorKind::InvalidVersion => "E-UPGRADE-002",
+            UpdateErrorKind::NoDownloadLinkFromGitHub => "E-UPGRADE-003",
+            UpdateErrorKind::NoCurrentVersion => "E-UPGRADE-004",
+            UpdateErrorKind::NoLatestVersion => "E-UPGRADE-005",
+            UpdateErrorKind::GitHibResponseToRustStructConversionFail => "E-UPGRADE-006",
+            UpdateErrorKind::NoAssetForTarget => "E-UPGRADE-007",
+            UpdateErrorKind::BinaryDownloadFail => "E-UPGRADE-008",
+            UpdateErrorKind::CorruptedBinaryDownload => "E-UPGRADE-009",
+            UpdateErrorKind::MissingChecksum => "E-UPGRADE-010",
+            UpdateErrorKind::ChecksumMismatch => "E-UPGRADE-011",
+            UpdateErrorKind::MissingSignature => "E-UPGRADE-012",
+            UpdateErrorKind::InvalidSignature => "E-UPGRADE-013",
+            UpdateErrorKind::CreateFileFail => "E-UPGRADE-014",
+            UpdateErrorKind::UnableToKeepPreviousBinary => "E-UPGRADE-015",
+            UpdateErrorKind::UnableToReplaceBinary => "E-UPGRADE-016",
+            UpdateErrorKind::NoPreviousVersion => "E-UPGRADE-017",
+            UpdateErrorKind::UnableToDetermineTmgrExecutablePath => "E-UPGRADE-018",
+            UpdateErrorKind::UnableToMigrateDatabase => "E-UPGRADE-019",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            UpdateErrorKind::RepoCheckFail
+            | UpdateErrorKind::NoDownloadLinkFromGitHub
+            | UpdateErrorKind::NoLatestVersion
+            | UpdateErrorKind::GitHibResponseToRustStructConversionFail
+            | UpdateErrorKind::BinaryDownloadFail
+            | UpdateErrorKind::CorruptedBinaryDownload
+            | UpdateErrorKind::ChecksumMismatch => ErrorCategory::Network,
+            UpdateErrorKind::InvalidVersion | UpdateErrorKind::InvalidSignature => {
+                ErrorCategory::Validation
+            }
+            UpdateErrorKind::NoCurrentVersion | UpdateErrorKind::UnableToMigrateDatabase => {
+                ErrorCategory::Internal
+            }
+            UpdateErrorKind::NoAssetForTarget
+            | UpdateErrorKind::MissingChecksum
+            | UpdateErrorKind::MissingSignature
+            | UpdateErrorKind::NoPreviousVersion => ErrorCategory::NotFound,
+            UpdateErrorKind::CreateFileFail
+            | UpdateErrorKind::UnableToKeepPreviousBinary
+            | UpdateErrorKind::UnableToReplaceBinary
+            | UpdateErrorKind::UnableToDetermineTmgrExecutablePath => ErrorCategory::IO,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            UpdateErrorKind::RepoCheckFail
+            | UpdateErrorKind::NoLatestVersion
+            | UpdateErrorKind::BinaryDownloadFail => {
+                Some("Check your internet connection, or try again later")
+            }
+            UpdateErrorKind::NoAssetForTarget => Some("Build tmgr from source for this platform"),
+            UpdateErrorKind::CorruptedBinaryDownload | UpdateErrorKind::ChecksumMismatch => {
+                Some("Try the upgrade again")
+            }
+            UpdateErrorKind::UnableToReplaceBinary => {
+                Some("Check that you can write to the directory of the tmgr executable")
+            }
+            UpdateErrorKind::UnableToMigrateDatabase => {
+                Some("Run 'tmgr migrate' to migrate the database")
+            }
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for UpdateError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} (update error: {})", self.message, self.kind)
     }
 }
 
+impl std::error::Error for UpdateError {}
+
 // -- Request Models --
 #[derive(Default, Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GithubReleaseResponse {
     #[serde(rename = "tag_name")]
     tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    body: Option<String>,
     assets: Vec<Asset>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct Asset {
+    name: String,
     #[serde(rename = "browser_download_url")]
     browser_download_url: String,
 }
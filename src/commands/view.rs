@@ -1,39 +1,89 @@
 use super::super::{
-    db::DB,
-    model::{CommandResult, TableRow, Task, TmgrError, TmgrErrorKind},
+    cli::model::OutputFormat,
+    db::{DB, DBError},
+    model::{
+        CommandResult, DATABASE_HINT, ErrorCategory, ErrorCode, TableRow, Task, TmgrError,
+        TmgrErrorKind,
+    },
 };
-use comfy_table::{ContentArrangement::Dynamic, Table};
-use std::fmt;
+use super::{
+    git::{MIN_COMMIT_ID_PREFIX, commits_referencing},
+    link::excerpt,
+    list::{json_object, styled_table},
+    note::render_note,
+};
+use comfy_table::ContentArrangement::Dynamic;
+use std::{env::current_dir, fmt};
 
-pub(crate) async fn run(db: &DB, id: String) -> Result<CommandResult<Task>, ViewError> {
+/// Shows the task whose id starts with `id`, with its note if `with_note` is set.
+pub async fn run(
+    db: &DB,
+    id: String,
+    with_note: bool,
+    output: OutputFormat,
+) -> Result<CommandResult<Task>, ViewError> {
     let t = db
         .select_task_by_partial_id(&id)
         .await
         .map_err(|e| ViewError {
-            kind: ViewErrorKind::DatabaseError,
             message: e.to_string(),
+            kind: ViewErrorKind::TaskLookup(e),
         })?;
 
-    let _ = t.id().map_err(|e| ViewError {
+    let task_id = t.id().map_err(|e| ViewError {
         kind: ViewErrorKind::BadTaskId,
         message: e.to_string(),
     })?;
 
-    let mut table = Table::new();
+    if output == OutputFormat::Json {
+        let json =
+            serde_json::to_string_pretty(&json_object(t.to_table_rows())).unwrap_or_default();
+        return Ok(CommandResult::new(json, t));
+    }
+
+    let mut table = styled_table();
     table.set_content_arrangement(Dynamic);
     table.set_header(vec!["Key", "Value"]);
     t.to_table_rows().iter().for_each(|(k, v)| {
         table.add_row(vec![k, v]);
     });
 
-    Ok(CommandResult::new(table.to_string(), t))
+    let mut output = table.to_string();
+    for link in t.code_links() {
+        output.push_str(&format!("\n\n{}", excerpt(link)));
+    }
+    // commits are only listed when running inside a git repository
+    let commits = current_dir()
+        .ok()
+        .and_then(|dir| commits_referencing(&dir, &task_id, MIN_COMMIT_ID_PREFIX).ok())
+        .unwrap_or_default();
+    if !commits.is_empty() {
+        output.push_str("\n\nCommits:");
+        for commit in commits {
+            output.push_str(&format!("\n  {commit}"));
+        }
+    }
+    if with_note {
+        let note = match t.work_note_path() {
+            Some(_) => render_note(&t).map_err(|e| ViewError {
+                kind: ViewErrorKind::NoteError,
+                message: e.to_string(),
+            })?,
+            None => "This task has no note".to_string(),
+        };
+        output.push_str(&format!("\n\n{note}"));
+    }
+
+    Ok(CommandResult::new(output, t))
 }
 
 // -- View Errors ---
 #[derive(Debug)]
 pub enum ViewErrorKind {
     BadTaskId,
-    DatabaseError,
+    DatabaseError(Option<Box<dyn std::error::Error + Send + Sync>>),
+    NoteError,
+    TaskLookup(DBError),
 }
 
 #[derive(Debug)]
@@ -42,17 +92,62 @@ pub struct ViewError {
     message: String,
 }
 
+impl ViewError {
+    pub fn kind(&self) -> &ViewErrorKind {
+        &self.kind
+    }
+}
+
 impl fmt::Display for ViewError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} (view error: {})", self.message, self.kind)
     }
 }
 
+impl std::error::Error for ViewError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ViewErrorKind::DatabaseError(Some(e)) => Some(e.as_ref()),
+            ViewErrorKind::TaskLookup(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for ViewErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             ViewErrorKind::BadTaskId => write!(f, "Bad task id"),
-            ViewErrorKind::DatabaseError => write!(f, "Database error"),
+            ViewErrorKind::DatabaseError(_) => write!(f, "Database error"),
+            ViewErrorKind::NoteError => write!(f, "Note error"),
+            ViewErrorKind::TaskLookup(e) => write!(f, "{}", e.kind()),
+        }
+    }
+}
+
+impl ErrorCode for ViewErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ViewErrorKind::BadTaskId => "E-VIEW-001",
+            ViewErrorKind::DatabaseError(_) => "E-VIEW-002",
+            ViewErrorKind::NoteError => "E-VIEW-003",
+            ViewErrorKind::TaskLookup(e) => e.kind().code(),
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ViewErrorKind::BadTaskId | ViewErrorKind::DatabaseError(_) => ErrorCategory::Internal,
+            ViewErrorKind::NoteError => ErrorCategory::IO,
+            ViewErrorKind::TaskLookup(e) => e.kind().category(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            ViewErrorKind::DatabaseError(_) => Some(DATABASE_HINT),
+            ViewErrorKind::TaskLookup(e) => e.kind().hint(),
+            _ => None,
         }
     }
 }
@@ -60,5 +155,7 @@ impl fmt::Display for ViewErrorKind {
 impl From<ViewError> for TmgrError {
     fn from(err: ViewError) -> Self {
         TmgrError::new(TmgrErrorKind::ViewCommand, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
@@ -0,0 +1,766 @@
+use super::{
+    cli::model::{Cli, ListFilter, ListSort, OutputFormat, ReleaseChannel},
+    model::{
+        ErrorCategory, ErrorCode, TableRow, Task, TaskPriority, TaskState, TmgrError,
+        TmgrErrorKind, register_date_format,
+    },
+};
+use clap::CommandFactory;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env::current_dir,
+    fmt::{self, Formatter, Write},
+    fs::read_to_string,
+    path::{Component, Path, PathBuf},
+    sync::RwLock,
+};
+use surrealdb::sql::Datetime;
+use toml::{Table, Value};
+
+/// The name of the per-project config file, looked up from the current directory upwards.
+pub(super) const PROJECT_CONFIG_FILE: &str = ".tmgr.toml";
+
+/// The settings a project file may not set, as they run a command or download the executable.
+/// A repository could otherwise run any command on the machines it is cloned to.
+const GLOBAL_ONLY_KEYS: [&str; 2] = ["editor", "upgrade"];
+
+/// The settings of the config file, as dotted keys. `<name>` stands for any name.
+pub(super) const KEYS: [&str; 16] = [
+    "editor",
+    "aliases.<name>",
+    "add.priority",
+    "display.color",
+    "display.date_format",
+    "display.output",
+    "list.all",
+    "list.columns",
+    "list.sort",
+    "list.states",
+    "notes.default_template",
+    "notes.dir",
+    "priorities.<name>",
+    "transitions.<state>",
+    "upgrade.api_url",
+    "upgrade.channel",
+];
+
+/// The config loaded at startup, for the settings read deep inside commands.
+static ACTIVE: RwLock<Option<Config>> = RwLock::new(None);
+
+/// User configuration, read from `config.toml` in the tmgr config directory, and from a
+/// `.tmgr.toml` project file whose settings take precedence.
+///
+/// Every setting is optional; a missing config file behaves like an empty one.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The command used to edit notes and tasks, instead of `$EDITOR`.
+    editor: Option<String>,
+    /// Command aliases, mapping the alias to the arguments it stands for, as in
+    /// `today = "list --priority high"`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    aliases: BTreeMap<String, String>,
+    /// Custom priority levels, mapping the level name to its numeric weight.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    priorities: BTreeMap<String, u32>,
+    /// Allowed state transitions, mapping a state to the states a task may move to.
+    /// States that are not listed keep their default transitions.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    transitions: BTreeMap<TaskState, Vec<TaskState>>,
+    /// Settings for the `add` command.
+    add: AddConfig,
+    /// Settings for the `list` command.
+    list: ListConfig,
+    /// How dates, colors and results are displayed.
+    display: DisplayConfig,
+    /// Settings for the `note` command.
+    notes: NotesConfig,
+    /// Settings for the `upgrade` command.
+    upgrade: UpgradeConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct AddConfig {
+    /// The priority of new tasks when `--priority` is not given.
+    priority: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct ListConfig {
+    /// List all tasks, including completed ones, as if `--all` was given.
+    all: bool,
+    /// The task fields shown as columns.
+    columns: Option<Vec<String>>,
+    /// The order to list tasks in when `--sort` is not given.
+    sort: Option<ListSort>,
+    /// The states to list when `--state` is not given.
+    states: Vec<TaskState>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct DisplayConfig {
+    /// Whether output is colored. By default, it is colored when writing to a terminal.
+    color: Option<bool>,
+    /// The strftime format of dates, as in `%Y-%m-%d %H:%M`.
+    date_format: Option<String>,
+    /// The format tasks are printed in by `list` and `view`.
+    output: OutputFormat,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct NotesConfig {
+    /// The template used for new notes when `--template` is not given.
+    default_template: Option<String>,
+    /// The directory notes are created in, instead of `tmgr_notes` next to the executable.
+    /// A relative path is relative to the config file it is set in.
+    dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct UpgradeConfig {
+    /// The releases to upgrade to when `--channel` is not given.
+    channel: ReleaseChannel,
+    /// The release API listing releases at `<api_url>/releases`, to upgrade from a mirror.
+    api_url: Option<String>,
+}
+
+impl Config {
+    /// Loads the global config file and the project config file of the current directory.
+    ///
+    /// Returns the default config if neither file exists.
+    pub fn load() -> Result<Self, ConfigError> {
+        let (global, project) = Self::config_files()?;
+        Self::load_files(&global, project.as_deref())
+    }
+
+    /// Loads the global config file merged with a project config file, whose settings win.
+    pub(super) fn load_files(global: &Path, project: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut sources = vec![(global.to_path_buf(), Self::read_file(global)?)];
+        if let Some(project) = project {
+            sources.push((project.to_path_buf(), Self::read_file(project)?));
+        }
+        Self::from_sources(&sources)
+    }
+
+    /// Parses and validates a config from a TOML string.
+    #[allow(dead_code)]
+    pub(super) fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let table = Self::parse_table(contents)?;
+        let config = Self::from_table(table)?;
+        config
+            .validate()
+            .map_err(|(key, reason)| invalid_key(&key, reason))?;
+        Ok(config)
+    }
+
+    /// Merges config files given as their path and contents, later files taking precedence,
+    /// and validates the result. Errors name the file and the key at fault.
+    ///
+    /// Project files may neither set the global only settings nor a notes directory outside
+    /// of the project.
+    pub(super) fn from_sources(sources: &[(PathBuf, String)]) -> Result<Self, ConfigError> {
+        let mut tables = Vec::new();
+        for (path, contents) in sources {
+            let in_file = |e: ConfigError| ConfigError {
+                kind: e.kind,
+                message: format!("{} in '{}'", e.message, path.display()),
+            };
+            let mut table = Self::parse_table(contents).map_err(in_file)?;
+            Self::from_table(table.clone()).map_err(in_file)?;
+            resolve_notes_dir(&mut table, path);
+            if path
+                .file_name()
+                .is_some_and(|name| name == PROJECT_CONFIG_FILE)
+            {
+                check_project_table(&table, path).map_err(in_file)?;
+            }
+            tables.push((path, table));
+        }
+
+        let mut merged = Table::new();
+        for (_, table) in &tables {
+            merge(&mut merged, table.clone());
+        }
+        let config = Self::from_table(merged)?;
+        config.validate().map_err(|(key, reason)| {
+            let error = invalid_key(&key, reason);
+            // the last file setting the key is the one at fault
+            match tables.iter().rev().find(|(_, t)| lookup(t, &key).is_some()) {
+                Some((path, _)) => ConfigError {
+                    kind: error.kind,
+                    message: format!("{} in '{}'", error.message, path.display()),
+                },
+                None => error,
+            }
+        })?;
+        Ok(config)
+    }
+
+    fn read_file(path: &Path) -> Result<String, ConfigError> {
+        if !path.exists() {
+            return Ok(String::new());
+        }
+        read_to_string(path).map_err(|e| ConfigError {
+            kind: ConfigErrorKind::IOError,
+            message: format!("Failed to read config file '{}': {e}", path.display()),
+        })
+    }
+
+    fn parse_table(contents: &str) -> Result<Table, ConfigError> {
+        contents.parse::<Table>().map_err(|e| ConfigError {
+            kind: ConfigErrorKind::ParseError,
+            message: e.message().to_string(),
+        })
+    }
+
+    fn from_table(table: Table) -> Result<Self, ConfigError> {
+        serde_path_to_error::deserialize(Value::Table(table)).map_err(|e| {
+            let key = e.path().to_string();
+            let reason = e.into_inner().message().to_string();
+            if key == "." {
+                ConfigError {
+                    kind: ConfigErrorKind::ParseError,
+                    message: format!("Invalid config: {reason}"),
+                }
+            } else {
+                ConfigError {
+                    kind: ConfigErrorKind::ParseError,
+                    message: format!("Invalid key '{key}': {reason}"),
+                }
+            }
+        })
+    }
+
+    /// The directory holding the config file and other user customizations.
+    pub(super) fn get_config_dir() -> Result<PathBuf, ConfigError> {
+        ProjectDirs::from("", "", "tmgr")
+            .map(|dirs| dirs.config_dir().to_path_buf())
+            .ok_or(ConfigError {
+                kind: ConfigErrorKind::UnableToDetermineConfigDirectory,
+                message: "Could not determine the home directory of the current user".to_string(),
+            })
+    }
+
+    pub(super) fn get_config_file_path() -> Result<PathBuf, ConfigError> {
+        Ok(Self::get_config_dir()?.join("config.toml"))
+    }
+
+    /// The global config file and, if there is one, the project config file of the current
+    /// directory.
+    pub(super) fn config_files() -> Result<(PathBuf, Option<PathBuf>), ConfigError> {
+        let project = current_dir()
+            .ok()
+            .and_then(|dir| Self::find_project_config_file(&dir));
+        Ok((Self::get_config_file_path()?, project))
+    }
+
+    /// The closest `.tmgr.toml` file in `dir` or the directories above it.
+    pub(super) fn find_project_config_file(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|d| d.join(PROJECT_CONFIG_FILE))
+            .find(|file| file.is_file())
+    }
+
+    /// Makes this config the one used by settings that are read deep inside commands, like
+    /// the editor, the notes directory, the date format and colors.
+    pub fn register(&self) {
+        TaskPriority::register_custom_levels(self.priorities());
+        register_date_format(self.display.date_format.clone());
+        if let Some(color) = self.display.color {
+            colored::control::set_override(color);
+        }
+        if let Ok(mut active) = ACTIVE.write() {
+            *active = Some(self.clone());
+        }
+    }
+
+    /// The registered config, or the default config if none was registered.
+    pub(super) fn active() -> Self {
+        ACTIVE
+            .read()
+            .ok()
+            .and_then(|active| active.clone())
+            .unwrap_or_default()
+    }
+
+    /// The value of a setting given as a dotted key, in TOML, or `None` if it is not set.
+    pub(super) fn get(&self, key: &str) -> Result<Option<String>, ConfigError> {
+        check_key(key)?;
+        let Value::Table(table) = Value::try_from(self).map_err(|e| ConfigError {
+            kind: ConfigErrorKind::ParseError,
+            message: e.to_string(),
+        })?
+        else {
+            return Ok(None);
+        };
+        Ok(lookup(&table, key).map(|value| match value {
+            Value::Table(table) => toml::to_string(table).unwrap_or_default(),
+            value => value.to_string(),
+        }))
+    }
+
+    /// The effective settings, in TOML.
+    pub(super) fn to_toml(&self) -> Result<String, ConfigError> {
+        toml::to_string(self).map_err(|e| ConfigError {
+            kind: ConfigErrorKind::ParseError,
+            message: e.to_string(),
+        })
+    }
+
+    /// Sets a dotted key in the contents of a config file, keeping its comments and layout.
+    ///
+    /// The value is read as TOML, as in `true`, `20` or `["id", "name"]`, and as a string
+    /// otherwise.
+    pub(super) fn set_value(contents: &str, key: &str, value: &str) -> Result<String, ConfigError> {
+        check_key(key)?;
+        let mut document = contents
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| ConfigError {
+                kind: ConfigErrorKind::ParseError,
+                message: e.message().to_string(),
+            })?;
+        let value = value
+            .parse::<toml_edit::Value>()
+            .unwrap_or_else(|_| toml_edit::Value::from(value));
+
+        let segments: Vec<&str> = key.split('.').collect();
+        let (last, tables) = segments.split_last().ok_or_else(|| unknown_key(key))?;
+        let mut table = document.as_table_mut();
+        for segment in tables {
+            let entry = table
+                .entry(segment)
+                .or_insert_with(toml_edit::table)
+                .as_table_mut()
+                .ok_or_else(|| invalid_key(key, format!("'{segment}' is not a table")))?;
+            table = entry;
+        }
+        table.insert(last, toml_edit::value(value));
+        Ok(document.to_string())
+    }
+
+    /// Custom priority levels defined by the user, mapping name to weight.
+    pub(super) fn priorities(&self) -> &BTreeMap<String, u32> {
+        &self.priorities
+    }
+
+    /// The allowed state transitions, with the defaults for states that are not configured.
+    pub fn transitions(&self) -> BTreeMap<TaskState, Vec<TaskState>> {
+        let mut transitions = TaskState::default_transitions();
+        transitions.extend(self.transitions.clone());
+        transitions
+    }
+
+    /// The command used to edit notes and tasks, if it overrides `$EDITOR`.
+    pub(super) fn editor(&self) -> Option<String> {
+        self.editor.clone()
+    }
+
+    /// The priority of new tasks when none is given on the command line.
+    pub(super) fn default_priority(&self) -> Option<TaskPriority> {
+        self.add
+            .priority
+            .as_deref()
+            .and_then(|p| self.priority_level(p))
+    }
+
+    /// The built-in or custom priority level of this config named `name`.
+    fn priority_level(&self, name: &str) -> Option<TaskPriority> {
+        TaskPriority::parse_builtin(name).or_else(|| {
+            self.priorities
+                .keys()
+                .find(|level| level.eq_ignore_ascii_case(name.trim()))
+                .map(|level| TaskPriority::Custom(level.clone()))
+        })
+    }
+
+    /// Replaces an alias in the first argument after the program name with the arguments it
+    /// stands for. Aliases are not expanded within aliases.
+    pub(super) fn expand_alias(&self, args: Vec<String>) -> Result<Vec<String>, ConfigError> {
+        let Some(expansion) = args.get(1).and_then(|arg| self.aliases.get(arg)) else {
+            return Ok(args);
+        };
+        let words = shell_words::split(expansion)
+            .map_err(|e| invalid_key(&format!("aliases.{}", args[1]), e.to_string()))?;
+        let mut expanded = vec![args[0].clone()];
+        expanded.extend(words);
+        expanded.extend(args.into_iter().skip(2));
+        Ok(expanded)
+    }
+
+    /// Completes list filters with the defaults of the `list` settings.
+    pub(super) fn list_filter(&self, filter: ListFilter) -> ListFilter {
+        ListFilter {
+            all: filter.all || self.list_all(),
+            sort: filter.sort.or(self.list_sort()),
+            state: if filter.state.is_empty() {
+                self.list_states()
+            } else {
+                filter.state
+            },
+            ..filter
+        }
+    }
+
+    /// Whether `list` shows completed tasks without `--all`.
+    pub(super) fn list_all(&self) -> bool {
+        self.list.all
+    }
+
+    /// The task fields `list` shows as columns, if they are not the default ones.
+    pub(super) fn list_columns(&self) -> Option<Vec<String>> {
+        self.list.columns.clone()
+    }
+
+    /// The order `list` shows tasks in when none is given on the command line.
+    pub(super) fn list_sort(&self) -> Option<ListSort> {
+        self.list.sort.clone()
+    }
+
+    /// The states `list` shows when none are given on the command line.
+    pub(super) fn list_states(&self) -> Vec<TaskState> {
+        self.list.states.clone()
+    }
+
+    /// Whether output is colored, if it does not depend on writing to a terminal.
+    pub(super) fn color(&self) -> Option<bool> {
+        self.display.color
+    }
+
+    /// The format tasks are printed in by `list` and `view`.
+    pub(super) fn output_format(&self) -> OutputFormat {
+        self.display.output.clone()
+    }
+
+    /// The template used for new notes when none is given on the command line.
+    pub(super) fn default_template(&self) -> Option<String> {
+        self.notes.default_template.clone()
+    }
+
+    /// The directory notes are created in, if it is not the default one.
+    pub(super) fn notes_dir(&self) -> Option<PathBuf> {
+        self.notes.dir.clone()
+    }
+
+    /// The releases to upgrade to when none is given on the command line.
+    pub(super) fn release_channel(&self) -> ReleaseChannel {
+        self.upgrade.channel.clone()
+    }
+
+    /// The release API to upgrade from, if it is not the one of the tmgr repository.
+    pub(super) fn release_api_url(&self) -> Option<String> {
+        self.upgrade.api_url.clone()
+    }
+
+    /// Checks the values that are well-formed but not valid, returning the key at fault and
+    /// the reason.
+    fn validate(&self) -> Result<(), (String, String)> {
+        for name in self.priorities.keys() {
+            if name.trim().is_empty() {
+                return Err((
+                    "priorities".to_string(),
+                    "priority names cannot be empty".to_string(),
+                ));
+            }
+            if TaskPriority::parse_builtin(name).is_some() {
+                return Err((
+                    format!("priorities.{name}"),
+                    format!("'{name}' is a built-in priority level"),
+                ));
+            }
+        }
+        for (from, to) in &self.transitions {
+            if to.contains(from) {
+                return Err((
+                    format!("transitions.{from}"),
+                    "a state cannot transition to itself".to_string(),
+                ));
+            }
+        }
+        for (name, expansion) in &self.aliases {
+            let key = format!("aliases.{name}");
+            if name == "help" || Cli::command().find_subcommand(name).is_some() {
+                return Err((key, format!("'{name}' is a built-in command")));
+            }
+            match shell_words::split(expansion) {
+                Ok(words) if words.is_empty() => {
+                    return Err((key, "an alias cannot be empty".to_string()));
+                }
+                Ok(_) => {}
+                Err(e) => return Err((key, e.to_string())),
+            }
+        }
+        if let Some(editor) = &self.editor
+            && editor.trim().is_empty()
+        {
+            return Err((
+                "editor".to_string(),
+                "the editor cannot be empty".to_string(),
+            ));
+        }
+        if let Some(priority) = &self.add.priority
+            && self.priority_level(priority).is_none()
+        {
+            return Err((
+                "add.priority".to_string(),
+                format!("'{priority}' is not a built-in or custom priority level"),
+            ));
+        }
+        if let Some(columns) = &self.list.columns {
+            if columns.is_empty() {
+                return Err((
+                    "list.columns".to_string(),
+                    "at least one column is required".to_string(),
+                ));
+            }
+            if let Some(column) = columns.iter().find(|c| !Task::FIELDS.contains(&c.as_str())) {
+                return Err((
+                    "list.columns".to_string(),
+                    format!(
+                        "'{column}' is not a task field, expected one of: {}",
+                        Task::FIELDS.join(", ")
+                    ),
+                ));
+            }
+        }
+        if let Some(date_format) = &self.display.date_format {
+            // an invalid format only fails once a date is formatted with it
+            let mut formatted = String::new();
+            if write!(formatted, "{}", Datetime::default().format(date_format)).is_err() {
+                return Err((
+                    "display.date_format".to_string(),
+                    format!("'{date_format}' is not a valid strftime format"),
+                ));
+            }
+        }
+        if let Some(dir) = &self.notes.dir
+            && dir.as_os_str().is_empty()
+        {
+            return Err((
+                "notes.dir".to_string(),
+                "the notes directory cannot be empty".to_string(),
+            ));
+        }
+        if let Some(api_url) = &self.upgrade.api_url
+            && !api_url.starts_with("http://")
+            && !api_url.starts_with("https://")
+        {
+            return Err((
+                "upgrade.api_url".to_string(),
+                format!("'{api_url}' is not an http or https URL"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a dotted key names a setting, or a table of settings.
+fn check_key(key: &str) -> Result<(), ConfigError> {
+    let known = KEYS.iter().any(|known| match known.split_once(".<") {
+        Some((table, _)) => {
+            key == table
+                || key
+                    .strip_prefix(table)
+                    .and_then(|rest| rest.strip_prefix('.'))
+                    .is_some_and(|name| !name.is_empty() && !name.contains('.'))
+        }
+        None => *known == key || known.starts_with(&format!("{key}.")),
+    });
+    if known { Ok(()) } else { Err(unknown_key(key)) }
+}
+
+/// The value at a dotted key of a table.
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut segments = key.split('.');
+    let mut value = table.get(segments.next()?)?;
+    for segment in segments {
+        value = value.as_table()?.get(segment)?;
+    }
+    Some(value)
+}
+
+/// Merges `other` into `table`, the values of `other` winning. Tables are merged key by key.
+fn merge(table: &mut Table, other: Table) {
+    for (key, value) in other {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(value)) => merge(existing, value),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Makes a relative `notes.dir` relative to the directory of the config file it is set in.
+fn resolve_notes_dir(table: &mut Table, config_file: &Path) {
+    let Some(Value::String(dir)) = table
+        .get_mut("notes")
+        .and_then(Value::as_table_mut)
+        .and_then(|notes| notes.get_mut("dir"))
+    else {
+        return;
+    };
+    if Path::new(dir).is_relative()
+        && let Some(parent) = config_file.parent()
+    {
+        *dir = parent.join(&*dir).to_string_lossy().to_string();
+    }
+}
+
+/// Checks that a project file only sets what a repository may set for the people cloning it.
+fn check_project_table(table: &Table, config_file: &Path) -> Result<(), ConfigError> {
+    for key in GLOBAL_ONLY_KEYS {
+        let key = match table.get(key) {
+            Some(Value::Table(settings)) => match settings.keys().next() {
+                Some(setting) => format!("{key}.{setting}"),
+                None => continue,
+            },
+            Some(_) => key.to_string(),
+            None => continue,
+        };
+        return Err(ConfigError {
+            kind: ConfigErrorKind::NotAllowedInProject,
+            message: format!("Invalid key '{key}': a project file may not set it"),
+        });
+    }
+
+    let project_dir = config_file.parent().unwrap_or(Path::new(""));
+    if let Some(dir) = lookup(table, "notes.dir").and_then(Value::as_str)
+        && !normalize(Path::new(dir)).starts_with(normalize(project_dir))
+    {
+        return Err(ConfigError {
+            kind: ConfigErrorKind::NotAllowedInProject,
+            message: format!("Invalid key 'notes.dir': '{dir}' is outside of the project"),
+        });
+    }
+    Ok(())
+}
+
+/// Resolves the `.` and `..` components of a path without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn invalid_key(key: &str, reason: String) -> ConfigError {
+    ConfigError {
+        kind: ConfigErrorKind::InvalidValue,
+        message: format!("Invalid key '{key}': {reason}"),
+    }
+}
+
+fn unknown_key(key: &str) -> ConfigError {
+    ConfigError {
+        kind: ConfigErrorKind::UnknownKey,
+        message: format!("Unknown key '{key}', expected one of: {}", KEYS.join(", ")),
+    }
+}
+
+// --- Config Errors ---
+#[derive(Debug)]
+pub enum ConfigErrorKind {
+    InvalidValue,
+    IOError,
+    NotAllowedInProject,
+    ParseError,
+    UnableToDetermineConfigDirectory,
+    UnknownKey,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    kind: ConfigErrorKind,
+    message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} (config error: {})", self.message, self.kind)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for ConfigErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigErrorKind::InvalidValue => write!(f, "Invalid value"),
+            ConfigErrorKind::IOError => write!(f, "IO error"),
+            ConfigErrorKind::NotAllowedInProject => write!(f, "Not allowed in project"),
+            ConfigErrorKind::ParseError => write!(f, "Parse error"),
+            ConfigErrorKind::UnableToDetermineConfigDirectory => {
+                write!(f, "Unable to determine config directory")
+            }
+            ConfigErrorKind::UnknownKey => write!(f, "Unknown key"),
+        }
+    }
+}
+
+impl ErrorCode for ConfigErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ConfigErrorKind::InvalidValue => "E-CFG-001",
+            ConfigErrorKind::IOError => "E-CFG-002",
+            ConfigErrorKind::ParseError => "E-CFG-003",
+            ConfigErrorKind::UnableToDetermineConfigDirectory => "E-CFG-004",
+            ConfigErrorKind::UnknownKey => "E-CFG-005",
+            ConfigErrorKind::NotAllowedInProject => "E-CFG-006",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ConfigErrorKind::InvalidValue
+            | ConfigErrorKind::NotAllowedInProject
+            | ConfigErrorKind::ParseError
+            | ConfigErrorKind::UnknownKey => ErrorCategory::Validation,
+            ConfigErrorKind::IOError | ConfigErrorKind::UnableToDetermineConfigDirectory => {
+                ErrorCategory::IO
+            }
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            ConfigErrorKind::InvalidValue => {
+                Some("Run 'tmgr config list' to see the settings and their values")
+            }
+            ConfigErrorKind::NotAllowedInProject => {
+                Some("Remove it from the project file, and set it with 'tmgr config set' instead")
+            }
+            ConfigErrorKind::ParseError => {
+                Some("Fix the config file, for example with 'tmgr config edit'")
+            }
+            ConfigErrorKind::UnknownKey => Some("Run 'tmgr config list' to see the known settings"),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for TmgrError {
+    fn from(err: ConfigError) -> Self {
+        TmgrError::new(TmgrErrorKind::Tmgr, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
+    }
+}
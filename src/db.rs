@@ -1,52 +1,75 @@
-use super::model::{Task, TmgrError, TmgrErrorKind};
+use super::model::{
+    DATABASE_HINT, DOCTOR_HINT, ErrorCategory, ErrorCode, Task, TmgrError, TmgrErrorKind, log_error,
+};
 use std::{
     fmt::{self, Formatter},
-    path::PathBuf,
+    future::IntoFuture,
+    path::{Path, PathBuf},
+    pin::Pin,
+    time::Instant,
 };
 use surrealdb::{
     Surreal,
     engine::any::{Any, connect},
 };
+use tracing::{debug, info};
 
 // TODO: should client be private?
-pub(super) struct DB {
+/// A connection to a tmgr task store.
+///
+/// Every command of the library takes a `&DB`; cloning it is cheap and shares the connection.
+#[derive(Clone)]
+pub struct DB {
     pub(super) client: Surreal<Any>,
 }
 
 impl DB {
-    pub(super) async fn new() -> Result<Self, DBError> {
-        let client = connect(format!(
-            "surrealkv://{}",
-            Self::get_db_file_path()?.display()
-        ))
-        .await
-        .map_err(|_| DBError {
-            kind: DBErrorKind::IOError,
-            message: "Could not create/connect to file database".to_string(),
-        })?;
-        client
-            .use_ns("tmgr_ns")
-            .use_db("tmgr_db")
+    /// Opens the database used by the tmgr CLI, stored next to the running executable.
+    pub async fn new() -> Result<Self, DBError> {
+        Self::open(Self::get_db_file_path()?).await
+    }
+
+    /// Opens, or creates, the database stored at `path`.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, DBError> {
+        info!("Opening the database at '{}'", path.as_ref().display());
+        let client = connect(format!("surrealkv://{}", path.as_ref().display()))
             .await
-            .map_err(|_| DBError {
-                kind: DBErrorKind::DatabaseError,
-                message: "Failed to set namespace and database".to_string(),
+            .inspect_err(log_error)
+            .map_err(|e| DBError {
+                kind: DBErrorKind::IOError,
+                message: "Could not create/connect to file database".to_string(),
+                source: Some(e.into()),
             })?;
-        Ok(Self { client })
+        Self::use_tmgr_db(client).await
+    }
+
+    /// Opens an empty database that only lives in memory, for tests and experiments.
+    pub async fn in_memory() -> Result<Self, DBError> {
+        let client = connect("mem://")
+            .await
+            .inspect_err(log_error)
+            .map_err(|e| DBError {
+                kind: DBErrorKind::IOError,
+                message: "Could not connect to memory database".to_string(),
+                source: Some(e.into()),
+            })?;
+        Self::use_tmgr_db(client).await
     }
 
     pub(super) async fn new_test() -> Result<Self, DBError> {
-        let client = connect("mem://").await.map_err(|_| DBError {
-            kind: DBErrorKind::IOError,
-            message: "Could not connect to memory database".to_string(),
-        })?;
+        Self::in_memory().await
+    }
+
+    async fn use_tmgr_db(client: Surreal<Any>) -> Result<Self, DBError> {
         client
             .use_ns("tmgr_ns")
             .use_db("tmgr_db")
             .await
-            .map_err(|_| DBError {
+            .inspect_err(log_error)
+            .map_err(|e| DBError {
                 kind: DBErrorKind::DatabaseError,
                 message: "Failed to set namespace and database".to_string(),
+                source: Some(e.into()),
             })?;
         Ok(Self { client })
     }
@@ -55,10 +78,12 @@ impl DB {
         let exe_path = std::env::current_exe().map_err(|e| DBError {
             kind: DBErrorKind::UnableToDetermineTmgrExecutablePath,
             message: e.to_string(),
+            source: Some(e.into()),
         })?;
         let dir_path = exe_path.parent().ok_or(DBError {
             kind: DBErrorKind::IOError,
             message: "Could not get parent directory of tmgr executable".to_string(),
+            source: None,
         })?;
         Ok(dir_path.join("tmgr_db"))
     }
@@ -69,36 +94,36 @@ impl DB {
     /// Returns an error if no tasks are found, or if multiple tasks are found.
     ///
     /// The id should be a prefix of the full id of the task you want to select.
-    /// The full id of each task is "task:<id>", where <id> is the id you
+    /// The full id of each task is `task:<id>`, where `<id>` is the id you
     /// provided when you added the task.
-    pub(super) async fn select_task_by_partial_id(
-        &self,
-        id: impl Into<String>,
-    ) -> Result<Task, DBError> {
+    pub async fn select_task_by_partial_id(&self, id: impl Into<String>) -> Result<Task, DBError> {
         let id_string = id.into();
-        let query = format!(
-            "SELECT * from task WHERE string::starts_with(<string> id, \"task:{}\")",
-            &id_string
-        );
-
+        // the id is bound rather than formatted into the query, as it can come from a request
+        let query = "SELECT * from task WHERE string::starts_with(<string> id, $prefix)";
         let res: Vec<Task> = self
             .client
             .query(query)
+            .bind(("prefix", format!("task:{id_string}")))
+            .logged(query)
             .await
-            .map_err(|_| DBError {
+            .map_err(|e| DBError {
                 kind: DBErrorKind::DatabaseError,
                 message: "Failed to get tasks".to_string(),
+                source: Some(e.into()),
             })?
             .take(0)
-            .map_err(|_| DBError {
+            .inspect_err(log_error)
+            .map_err(|e| DBError {
                 kind: DBErrorKind::SerializationError,
                 message: "Failed to deserialize tasks".to_string(),
+                source: Some(e.into()),
             })?;
 
         if res.is_empty() {
             return Err(DBError {
                 kind: DBErrorKind::NoTasksFound,
                 message: format!("Task starting with id '{}' was not found", &id_string),
+                source: None,
             });
         }
 
@@ -106,18 +131,46 @@ impl DB {
             return Err(DBError {
                 kind: DBErrorKind::MultipleTasksFound,
                 message: "Multiple tasks found, provide more characters of the id".to_string(),
+                source: None,
             });
         }
 
         let task = res.into_iter().next().ok_or(DBError {
             kind: DBErrorKind::ExpectedOneTask,
             message: "Expected one task".to_string(),
+            source: None,
         })?;
         Ok(task)
     }
 }
 
-#[derive(Debug)]
+/// Logs the SurrealQL statement of a database operation, how long it took and, when it fails,
+/// the error of the database, which the errors of commands only summarize.
+pub(super) trait Logged<T>: IntoFuture<Output = surrealdb::Result<T>> + Sized {
+    fn logged<'a>(
+        self,
+        statement: &'a str,
+    ) -> Pin<Box<dyn Future<Output = surrealdb::Result<T>> + Send + 'a>>
+    where
+        Self::IntoFuture: Send + 'a,
+    {
+        // boxed so that axum handlers do not need to prove `Send` through the SDK builders
+        let operation = self.into_future();
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = operation.await;
+            match &result {
+                Ok(_) => debug!("{statement} ({:.2?})", start.elapsed()),
+                Err(e) => debug!("{statement} failed after {:.2?}: {e:?}", start.elapsed()),
+            }
+            result
+        })
+    }
+}
+
+impl<T, F: IntoFuture<Output = surrealdb::Result<T>>> Logged<T> for F {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DBErrorKind {
     DatabaseError,
     ExpectedOneTask,
@@ -132,6 +185,13 @@ pub enum DBErrorKind {
 pub struct DBError {
     kind: DBErrorKind,
     message: String,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl DBError {
+    pub fn kind(&self) -> &DBErrorKind {
+        &self.kind
+    }
 }
 
 impl fmt::Display for DBError {
@@ -140,6 +200,14 @@ impl fmt::Display for DBError {
     }
 }
 
+impl std::error::Error for DBError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
+
 impl fmt::Display for DBErrorKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -156,8 +224,50 @@ impl fmt::Display for DBErrorKind {
     }
 }
 
+impl ErrorCode for DBErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            DBErrorKind::NoTasksFound => "E-DB-001",
+            DBErrorKind::MultipleTasksFound => "E-DB-002",
+            DBErrorKind::ExpectedOneTask => "E-DB-003",
+            DBErrorKind::DatabaseError => "E-DB-004",
+            DBErrorKind::IOError => "E-DB-005",
+            DBErrorKind::SerializationError => "E-DB-006",
+            DBErrorKind::UnableToDetermineTmgrExecutablePath => "E-DB-007",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            DBErrorKind::NoTasksFound => ErrorCategory::NotFound,
+            DBErrorKind::MultipleTasksFound => ErrorCategory::Ambiguous,
+            DBErrorKind::ExpectedOneTask
+            | DBErrorKind::DatabaseError
+            | DBErrorKind::SerializationError => ErrorCategory::Internal,
+            DBErrorKind::IOError | DBErrorKind::UnableToDetermineTmgrExecutablePath => {
+                ErrorCategory::IO
+            }
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            DBErrorKind::NoTasksFound => Some("Run 'tmgr list --all' to see the ids of all tasks"),
+            DBErrorKind::MultipleTasksFound => Some("Provide more characters of the task id"),
+            DBErrorKind::DatabaseError => Some(DATABASE_HINT),
+            DBErrorKind::IOError => {
+                Some("Check that the directory of the tmgr executable is writable")
+            }
+            DBErrorKind::SerializationError => Some(DOCTOR_HINT),
+            _ => None,
+        }
+    }
+}
+
 impl From<DBError> for TmgrError {
     fn from(err: DBError) -> Self {
         TmgrError::new(TmgrErrorKind::Tmgr, err.to_string())
+            .with_code(&err.kind)
+            .with_source(err)
     }
 }
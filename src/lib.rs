@@ -0,0 +1,51 @@
+//! tmgr manages tasks from the command line, and this library manages the same task store
+//! from other Rust programs.
+//!
+//! Open the store with [`DB::new`], which is the database used by the `tmgr` executable, or
+//! with [`DB::open`] for a database at a given path, then run the functions of the
+//! [`commands`] modules on it. Every command is async, and returns a [`CommandResult`]
+//! holding the message the CLI prints and the typed value behind it, or the error of the
+//! command.
+//!
+//! ```
+//! use tmgr::{DB, ListFilter, OutputFormat, TaskPriority, commands};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let db = DB::in_memory().await?;
+//! let task = commands::add::run(&db, "Write docs".to_string(), Some(TaskPriority::High), None)
+//!     .await?
+//!     .into_result();
+//!
+//! let tasks = commands::list::run(&db, ListFilter::default(), None, OutputFormat::Json)
+//!     .await?
+//!     .into_result();
+//! assert_eq!(tasks.len(), 1);
+//! assert_eq!(tasks[0].id()?, task.id()?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Settings like custom priority levels, the date format and the notes directory are read
+//! from the registered [`Config`]; call [`Config::load`] and [`Config::register`] first to
+//! behave like the `tmgr` executable.
+
+mod cli;
+pub mod commands;
+mod config;
+mod db;
+mod model;
+mod tests;
+
+pub use cli::model::{ListFilter, ListSort, OutputFormat};
+pub use config::{Config, ConfigError, ConfigErrorKind};
+pub use db::{DB, DBError, DBErrorKind};
+pub use model::{
+    Attachment, AttachmentKind, CodeLink, CommandResult, ErrorCategory, ErrorCode, StateTransition,
+    Task, TaskBuilder, TaskError, TaskErrorKind, TaskPriority, TaskState, TmgrError, TmgrErrorKind,
+};
+
+/// Runs the tmgr command line with the arguments of the process, returning its exit code.
+pub fn run_cli() -> i32 {
+    cli::parser::run()
+}
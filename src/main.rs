@@ -1,11 +1,4 @@
-mod cli;
-mod commands;
-mod db;
-mod model;
-
-use cli::parser;
-
 fn main() {
-    let exit_code: i32 = parser::run();
+    let exit_code: i32 = tmgr::run_cli();
     std::process::exit(exit_code);
 }
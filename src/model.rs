@@ -1,7 +1,8 @@
 use clap::ValueEnum;
-use serde::{Deserialize, Deserializer, Serialize};
-use std::{fmt, fmt::Display};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{cmp::Ordering, collections::BTreeMap, fmt, fmt::Display, str::FromStr, sync::RwLock};
 use surrealdb::sql::{Datetime, Thing};
+use tracing::debug;
 
 // -- Task --
 /// Represents a task.
@@ -9,7 +10,7 @@ use surrealdb::sql::{Datetime, Thing};
 /// This is the structure of the data in the database, and must match the
 /// structure of the data in the database.
 #[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
-pub(super) struct Task {
+pub struct Task {
     #[serde(deserialize_with = "thing_to_string")]
     id: Option<String>,
     name: String,
@@ -18,6 +19,16 @@ pub(super) struct Task {
     work_note_path: Option<String>,
     created_at: Datetime,
     completed_at: Option<Datetime>,
+    #[serde(default)]
+    state: Option<TaskState>,
+    #[serde(default)]
+    state_history: Vec<StateTransition>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+    #[serde(default)]
+    code_links: Vec<CodeLink>,
+    #[serde(default)]
+    comment_hash: Option<String>,
     // TODO: impl macros for this: https://stackoverflow.com/questions/37140768/how-to-get-struct-field-names-in-rust
     // NOTE (new field): if new fields are added here, then implement getters and update TableRow implementation
 }
@@ -28,10 +39,10 @@ impl Task {
     /// If the task ID is not set, this function returns an error.
     ///
     /// If the task ID is set, but does not start with "task:", this function returns an error.
-    /// The expected format of task IDs is "task:<id>", where <id> is the id you
+    /// The expected format of task IDs is `task:<id>`, where `<id>` is the id you
     /// provided when you added the task.
     ///
-    pub(super) fn id(&self) -> Result<String, TaskError> {
+    pub fn id(&self) -> Result<String, TaskError> {
         let actual_id = &self.id;
         if let Some(actual_id) = actual_id {
             let id = actual_id.strip_prefix("task:");
@@ -55,14 +66,14 @@ impl Task {
     /// The name of the task.
     ///
     /// This is the short description of the task you provided when you added the task.
-    pub(super) fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
     /// The priority of the task.
     ///
     /// This is the priority of the task you provided when you added the task.
-    pub(super) fn priority(&self) -> &TaskPriority {
+    pub fn priority(&self) -> &TaskPriority {
         &self.priority
     }
 
@@ -70,7 +81,7 @@ impl Task {
     ///
     /// This is the description of the task you provided when you added the task.
     /// This is optional, as you did not have to provide a description when you added the task.
-    pub(super) fn description(&self) -> &Option<String> {
+    pub fn description(&self) -> &Option<String> {
         &self.description
     }
 
@@ -78,14 +89,14 @@ impl Task {
     ///
     /// This is the path you provided when you ran the `note` command.
     /// This is optional, as you did not have to provide a path when you ran the `note` command.
-    pub(super) fn work_note_path(&self) -> &Option<String> {
+    pub fn work_note_path(&self) -> &Option<String> {
         &self.work_note_path
     }
 
     /// The time at which the task was added.
     ///
     /// This is the time at which the task was added to the database.
-    pub(super) fn created_at(&self) -> &Datetime {
+    pub fn created_at(&self) -> &Datetime {
         &self.created_at
     }
 
@@ -93,15 +104,49 @@ impl Task {
     ///
     /// This is the time at which the task was marked as completed.
     /// This is optional, as the task may not have been completed yet.
-    pub(super) fn completed_at(&self) -> &Option<Datetime> {
+    pub fn completed_at(&self) -> &Option<Datetime> {
         &self.completed_at
     }
 
+    /// The workflow state of the task.
+    ///
+    /// Tasks created before states were introduced have no stored state; their state
+    /// is inferred as `Done` if the task was completed, and `InProgress` otherwise.
+    pub fn state(&self) -> TaskState {
+        match (&self.state, &self.completed_at) {
+            (Some(state), _) => state.clone(),
+            (None, Some(_)) => TaskState::Done,
+            (None, None) => TaskState::InProgress,
+        }
+    }
+
+    /// The state transitions of the task, oldest first.
+    ///
+    /// This is the record of every `move` (or `complete`) made on the task.
+    pub fn state_history(&self) -> &Vec<StateTransition> {
+        &self.state_history
+    }
+
+    /// The files and URLs attached to the task, in the order they were attached.
+    pub fn attachments(&self) -> &Vec<Attachment> {
+        &self.attachments
+    }
+
+    /// The source code locations linked to the task, in the order they were linked.
+    pub fn code_links(&self) -> &Vec<CodeLink> {
+        &self.code_links
+    }
+
+    /// The hash of the source comment the task was created from by `scan`, if any.
+    pub fn comment_hash(&self) -> &Option<String> {
+        &self.comment_hash
+    }
+
     /// Constructs a new `TaskBuilder` with all fields set to `None`.
     ///
     /// This is a convenient way to start building a `Task` incrementally by
     /// setting only the desired fields using the builder pattern.
-    pub(super) fn builder() -> TaskBuilder {
+    pub fn builder() -> TaskBuilder {
         TaskBuilder {
             id: None,
             name: None,
@@ -110,6 +155,11 @@ impl Task {
             work_note_path: None,
             created_at: None,
             completed_at: None,
+            state: None,
+            state_history: Vec::new(),
+            attachments: Vec::new(),
+            code_links: Vec::new(),
+            comment_hash: None,
         }
     }
 }
@@ -124,6 +174,30 @@ where
     Ok(Some(t.to_raw()))
 }
 
+/// The strftime format of displayed dates, registered from the config file.
+static DATE_FORMAT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Registers the format dates are displayed in, or the default format for `None`.
+pub(super) fn register_date_format(format: Option<String>) {
+    if let Ok(mut date_format) = DATE_FORMAT.write() {
+        *date_format = format;
+    }
+}
+
+/// Formats a date with the registered date format.
+pub(super) fn format_datetime(datetime: &Datetime) -> String {
+    match DATE_FORMAT.read().ok().and_then(|f| f.clone()) {
+        Some(format) => datetime.format(&format).to_string(),
+        None => datetime.to_string(),
+    }
+}
+
+/// Logs the error behind a failure at the debug level, as the errors of commands only keep a
+/// summary of it.
+pub(super) fn log_error(error: &impl fmt::Debug) {
+    debug!("{error:?}");
+}
+
 pub(super) trait TableRow {
     const FIELDS: &'static [&'static str];
     fn to_table_rows(&self) -> Vec<(String, String)>;
@@ -139,6 +213,11 @@ impl TableRow for Task {
         "description",
         "created_at",
         "completed_at",
+        "state",
+        "state_history",
+        "attachments",
+        "code_links",
+        "comment_hash",
         "work_note_path",
     ];
 
@@ -180,14 +259,45 @@ impl TableRow for Task {
                         .unwrap_or(&"None".to_string())
                         .to_string(),
                 ),
-                "created_at" => (f.to_string(), self.created_at().to_string()),
+                "created_at" => (f.to_string(), format_datetime(self.created_at())),
                 "completed_at" => (
                     f.to_string(),
                     self.completed_at()
                         .as_ref()
-                        .map(|s| s.to_string())
+                        .map(format_datetime)
                         .unwrap_or("In Progress".to_string()),
                 ),
+                "state" => (f.to_string(), self.state().to_string()),
+                "state_history" => (
+                    f.to_string(),
+                    self.state_history()
+                        .iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                ),
+                "attachments" => (
+                    f.to_string(),
+                    self.attachments()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, a)| format!("{}. {a}", i + 1))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                ),
+                "code_links" => (
+                    f.to_string(),
+                    self.code_links()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, l)| format!("{}. {l}", i + 1))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                ),
+                "comment_hash" => (
+                    f.to_string(),
+                    self.comment_hash().clone().unwrap_or_default(),
+                ),
                 "work_note_path" => (
                     f.to_string(),
                     self.work_note_path()
@@ -222,6 +332,8 @@ impl Display for TaskError {
     }
 }
 
+impl std::error::Error for TaskError {}
+
 impl Display for TaskErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -231,22 +343,154 @@ impl Display for TaskErrorKind {
     }
 }
 
+impl ErrorCode for TaskErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            TaskErrorKind::BadPrefix => "E-TASK-001",
+            TaskErrorKind::NoId => "E-TASK-002",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            TaskErrorKind::BadPrefix | TaskErrorKind::NoId => ErrorCategory::Internal,
+        }
+    }
+}
+
 // -- TaskPriority --
 /// Represents the priority of a task.
-#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
-pub(super) enum TaskPriority {
+///
+/// Priorities are ordered by their weight. The built-in levels have fixed weights
+/// (None = 0, Low = 10, Medium = 20, High = 30, Critical = 40); custom levels are
+/// defined in the config file with a weight of their own.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TaskPriority {
+    None,
     #[default]
     Low,
     Medium,
     High,
+    Critical,
+    Custom(String),
+}
+
+/// Custom priority levels registered from the config file, mapping name to weight.
+static CUSTOM_PRIORITIES: RwLock<BTreeMap<String, u32>> = RwLock::new(BTreeMap::new());
+
+impl TaskPriority {
+    const BUILTIN: [TaskPriority; 5] = [
+        TaskPriority::None,
+        TaskPriority::Low,
+        TaskPriority::Medium,
+        TaskPriority::High,
+        TaskPriority::Critical,
+    ];
+
+    /// Registers the custom priority levels defined in the config file.
+    ///
+    /// Replaces any previously registered levels.
+    pub(super) fn register_custom_levels(levels: &BTreeMap<String, u32>) {
+        if let Ok(mut custom) = CUSTOM_PRIORITIES.write() {
+            *custom = levels.clone();
+        }
+    }
+
+    /// All known priority levels, built-in and custom, sorted by weight.
+    pub(super) fn known_levels() -> Vec<TaskPriority> {
+        let mut levels: Vec<TaskPriority> = Self::BUILTIN.to_vec();
+        if let Ok(custom) = CUSTOM_PRIORITIES.read() {
+            levels.extend(custom.keys().cloned().map(TaskPriority::Custom));
+        }
+        levels.sort();
+        levels
+    }
+
+    /// The numeric weight of the priority, used for ordering.
+    ///
+    /// Custom levels that are no longer defined in the config file weigh 0.
+    pub(super) fn weight(&self) -> u32 {
+        match self {
+            TaskPriority::None => 0,
+            TaskPriority::Low => 10,
+            TaskPriority::Medium => 20,
+            TaskPriority::High => 30,
+            TaskPriority::Critical => 40,
+            TaskPriority::Custom(name) => CUSTOM_PRIORITIES
+                .read()
+                .ok()
+                .and_then(|custom| custom.get(name).copied())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Parses a built-in priority level, accepting names case-insensitively as well
+    /// as the `p0`-`p4` and `!`-`!!!!` shorthands.
+    pub(super) fn parse_builtin(s: &str) -> Option<TaskPriority> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "p4" => Some(TaskPriority::None),
+            "low" | "p3" | "!" => Some(TaskPriority::Low),
+            "medium" | "p2" | "!!" => Some(TaskPriority::Medium),
+            "high" | "p1" | "!!!" => Some(TaskPriority::High),
+            "critical" | "p0" | "!!!!" => Some(TaskPriority::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for TaskPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(priority) = TaskPriority::parse_builtin(s) {
+            return Ok(priority);
+        }
+        let custom = CUSTOM_PRIORITIES
+            .read()
+            .ok()
+            .and_then(|custom| {
+                custom
+                    .keys()
+                    .find(|name| name.eq_ignore_ascii_case(s.trim()))
+                    .cloned()
+            })
+            .map(TaskPriority::Custom);
+        custom.ok_or_else(|| {
+            let known: Vec<String> = TaskPriority::known_levels()
+                .iter()
+                .map(String::from)
+                .collect();
+            format!(
+                "invalid priority '{s}', expected one of: {} (or a shorthand p0-p4, !-!!!!)",
+                known.join(", ")
+            )
+        })
+    }
+}
+
+impl Ord for TaskPriority {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.weight()
+            .cmp(&other.weight())
+            .then_with(|| String::from(self).cmp(&String::from(other)))
+    }
+}
+
+impl PartialOrd for TaskPriority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl From<&TaskPriority> for String {
     fn from(priority: &TaskPriority) -> Self {
         match priority {
+            TaskPriority::None => "None".to_string(),
             TaskPriority::Low => "Low".to_string(),
             TaskPriority::Medium => "Medium".to_string(),
             TaskPriority::High => "High".to_string(),
+            TaskPriority::Critical => "Critical".to_string(),
+            TaskPriority::Custom(name) => name.to_string(),
         }
     }
 }
@@ -256,11 +500,259 @@ impl Display for TaskPriority {
         <&TaskPriority as Into<String>>::into(self).fmt(f)
     }
 }
+
+impl Serialize for TaskPriority {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&String::from(self))
+    }
+}
+
+// priorities are stored by name; any name that is not a built-in level is kept as a
+// custom level so that removing a level from the config does not break existing tasks
+impl<'de> Deserialize<'de> for TaskPriority {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(TaskPriority::BUILTIN
+            .into_iter()
+            .find(|p| String::from(p) == name)
+            .unwrap_or(TaskPriority::Custom(name)))
+    }
+}
 // -- TaskPriority --
 
+// -- TaskState --
+/// Represents where a task is in its workflow.
+#[derive(
+    Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskState {
+    #[default]
+    Todo,
+    InProgress,
+    InReview,
+    Blocked,
+    Done,
+    Cancelled,
+}
+
+impl TaskState {
+    /// Whether the task is finished in this state. Moving a task into a closed state
+    /// marks it as completed, and moving it out again reopens it.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TaskState::Done | TaskState::Cancelled)
+    }
+
+    /// The transitions allowed from each state when none are configured.
+    pub fn default_transitions() -> BTreeMap<TaskState, Vec<TaskState>> {
+        use TaskState::*;
+        BTreeMap::from([
+            (Todo, vec![InProgress, Blocked, Done, Cancelled]),
+            (InProgress, vec![Todo, InReview, Blocked, Done, Cancelled]),
+            (InReview, vec![InProgress, Blocked, Done, Cancelled]),
+            (Blocked, vec![Todo, InProgress, Cancelled]),
+            (Done, vec![InProgress]),
+            (Cancelled, vec![Todo]),
+        ])
+    }
+}
+
+impl From<&TaskState> for String {
+    fn from(state: &TaskState) -> Self {
+        match state {
+            TaskState::Todo => "todo".to_string(),
+            TaskState::InProgress => "in-progress".to_string(),
+            TaskState::InReview => "in-review".to_string(),
+            TaskState::Blocked => "blocked".to_string(),
+            TaskState::Done => "done".to_string(),
+            TaskState::Cancelled => "cancelled".to_string(),
+        }
+    }
+}
+
+impl Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <&TaskState as Into<String>>::into(self).fmt(f)
+    }
+}
+
+/// A single change of state, recorded on the task when it is moved.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct StateTransition {
+    from: TaskState,
+    to: TaskState,
+    at: Datetime,
+}
+
+impl StateTransition {
+    pub(super) fn new(from: TaskState, to: TaskState) -> Self {
+        Self {
+            from,
+            to,
+            at: Datetime::default(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn from(&self) -> &TaskState {
+        &self.from
+    }
+
+    #[allow(dead_code)]
+    pub fn to(&self) -> &TaskState {
+        &self.to
+    }
+
+    #[allow(dead_code)]
+    pub fn at(&self) -> &Datetime {
+        &self.at
+    }
+}
+
+impl Display for StateTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {} at {}",
+            self.from,
+            self.to,
+            format_datetime(&self.at)
+        )
+    }
+}
+// -- TaskState --
+
+// -- Attachment --
+/// A file or URL attached to a task.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Attachment {
+    kind: AttachmentKind,
+    target: String,
+    added_at: Datetime,
+}
+
+impl Attachment {
+    pub(super) fn new(kind: AttachmentKind, target: String) -> Self {
+        Self {
+            kind,
+            target,
+            added_at: Datetime::default(),
+        }
+    }
+
+    pub fn kind(&self) -> &AttachmentKind {
+        &self.kind
+    }
+
+    /// The path of the file, or the URL.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    #[allow(dead_code)]
+    pub fn added_at(&self) -> &Datetime {
+        &self.added_at
+    }
+}
+
+impl Display for Attachment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.kind, self.target)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttachmentKind {
+    /// A file copied into the task's attachments folder.
+    Copy,
+    /// A file referenced where it is.
+    Path,
+    Url,
+}
+
+impl Display for AttachmentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttachmentKind::Copy => write!(f, "copy"),
+            AttachmentKind::Path => write!(f, "path"),
+            AttachmentKind::Url => write!(f, "url"),
+        }
+    }
+}
+// -- Attachment --
+
+// -- CodeLink --
+/// A range of lines in a source file, linked to a task.
+///
+/// The path is relative to the root of the git repository holding the file. The linked
+/// lines are kept so that changes to them can be detected.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CodeLink {
+    root: String,
+    path: String,
+    start: usize,
+    end: usize,
+    lines: Vec<String>,
+}
+
+impl CodeLink {
+    pub(super) fn new(
+        root: String,
+        path: String,
+        start: usize,
+        end: usize,
+        lines: Vec<String>,
+    ) -> Self {
+        Self {
+            root,
+            path,
+            start,
+            end,
+            lines,
+        }
+    }
+
+    /// The root of the git repository holding the file.
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// The path of the file, relative to the root of its git repository.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The first linked line, starting at 1.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The last linked line, inclusive.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The content of the linked lines when they were linked.
+    pub fn lines(&self) -> &Vec<String> {
+        &self.lines
+    }
+}
+
+impl Display for CodeLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}:{}", self.path, self.start)
+        } else {
+            write!(f, "{}:{}-{}", self.path, self.start, self.end)
+        }
+    }
+}
+// -- CodeLink --
+
 // -- TaskBuilder --
 #[derive(Default)]
-pub(super) struct TaskBuilder {
+pub struct TaskBuilder {
     id: Option<String>,
     name: Option<String>,
     priority: Option<TaskPriority>,
@@ -268,6 +760,11 @@ pub(super) struct TaskBuilder {
     work_note_path: Option<String>,
     created_at: Option<Datetime>,
     completed_at: Option<Datetime>,
+    state: Option<TaskState>,
+    state_history: Vec<StateTransition>,
+    attachments: Vec<Attachment>,
+    code_links: Vec<CodeLink>,
+    comment_hash: Option<String>,
 }
 
 impl TaskBuilder {
@@ -275,7 +772,7 @@ impl TaskBuilder {
     ///
     /// This is optional, and defaults to `None`.
     #[allow(dead_code)]
-    pub(super) fn id(mut self, id: impl Into<String>) -> Self {
+    pub fn id(mut self, id: impl Into<String>) -> Self {
         self.id = Some(id.into());
         self
     }
@@ -283,7 +780,7 @@ impl TaskBuilder {
     /// Sets the name of the task to the given value.
     ///
     /// This is required, and defaults to `String::default()`.
-    pub(super) fn name(mut self, name: impl Into<String>) -> Self {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
         self.name = Some(name.into());
         self
     }
@@ -291,7 +788,7 @@ impl TaskBuilder {
     /// Sets the priority of the task to the given value.
     ///
     /// This is required, and defaults to `TaskPriority::Low`.
-    pub(super) fn priority(mut self, priority: TaskPriority) -> Self {
+    pub fn priority(mut self, priority: TaskPriority) -> Self {
         self.priority = Some(priority);
         self
     }
@@ -299,7 +796,7 @@ impl TaskBuilder {
     /// Sets the description of the task to the given value.
     ///
     /// This is optional, and defaults to `None`.
-    pub(super) fn description(mut self, description: impl Into<String>) -> Self {
+    pub fn description(mut self, description: impl Into<String>) -> Self {
         self.description = Some(description.into());
         self
     }
@@ -308,7 +805,7 @@ impl TaskBuilder {
     ///
     /// This is optional, and defaults to `None`.
     #[allow(dead_code)]
-    pub(super) fn work_note_path(mut self, work_note_path: impl Into<String>) -> Self {
+    pub fn work_note_path(mut self, work_note_path: impl Into<String>) -> Self {
         self.work_note_path = Some(work_note_path.into());
         self
     }
@@ -317,7 +814,7 @@ impl TaskBuilder {
     ///
     /// This is optional, and defaults to `Datetime::default()`.
     #[allow(dead_code)]
-    pub(super) fn created_at(mut self, created_at: Datetime) -> Self {
+    pub fn created_at(mut self, created_at: Datetime) -> Self {
         self.created_at = Some(created_at);
         self
     }
@@ -326,13 +823,55 @@ impl TaskBuilder {
     ///
     /// This is optional, and defaults to `None`.
     #[allow(dead_code)]
-    pub(super) fn completed_at(mut self, completed_at: Datetime) -> Self {
+    pub fn completed_at(mut self, completed_at: Datetime) -> Self {
         self.completed_at = Some(completed_at);
         self
     }
 
+    /// Sets the workflow state of the task to the given value.
+    ///
+    /// This is optional, and defaults to `None` (the state is inferred).
+    pub fn state(mut self, state: TaskState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Sets the state transitions of the task to the given value.
+    ///
+    /// This is optional, and defaults to an empty history.
+    #[allow(dead_code)]
+    pub fn state_history(mut self, state_history: Vec<StateTransition>) -> Self {
+        self.state_history = state_history;
+        self
+    }
+
+    /// Sets the attachments of the task to the given value.
+    ///
+    /// This is optional, and defaults to no attachments.
+    #[allow(dead_code)]
+    pub fn attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    /// Sets the code links of the task to the given value.
+    ///
+    /// This is optional, and defaults to no code links.
+    pub fn code_links(mut self, code_links: Vec<CodeLink>) -> Self {
+        self.code_links = code_links;
+        self
+    }
+
+    /// Sets the hash of the source comment the task was created from.
+    ///
+    /// This is optional, and defaults to `None`.
+    pub fn comment_hash(mut self, comment_hash: impl Into<String>) -> Self {
+        self.comment_hash = Some(comment_hash.into());
+        self
+    }
+
     /// Builds a `Task` from the current state of the builder.
-    pub(super) fn build(self) -> Task {
+    pub fn build(self) -> Task {
         Task {
             id: self.id,
             name: self.name.unwrap_or_default(),
@@ -341,22 +880,136 @@ impl TaskBuilder {
             work_note_path: self.work_note_path,
             created_at: self.created_at.unwrap_or_default(),
             completed_at: self.completed_at,
+            state: self.state,
+            state_history: self.state_history,
+            attachments: self.attachments,
+            code_links: self.code_links,
+            comment_hash: self.comment_hash,
         }
     }
 }
 
 // -- TaskBuilder --
 
+// -- ErrorCategory --
+/// What went wrong, broadly. Scripts can tell categories apart by the exit code of the CLI.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorCategory {
+    /// A task id matched several tasks.
+    Ambiguous,
+    /// A failure of tmgr itself or of its database.
+    Internal,
+    /// A file, the editor or another program could not be used.
+    IO,
+    /// A remote server could not be reached, or answered unexpectedly.
+    Network,
+    /// A task, file, note or other item does not exist.
+    NotFound,
+    /// The input was rejected, like an invalid value or a disallowed state change.
+    Validation,
+}
+
+impl Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorCategory::Ambiguous => write!(f, "Ambiguous"),
+            ErrorCategory::Internal => write!(f, "Internal"),
+            ErrorCategory::IO => write!(f, "IO"),
+            ErrorCategory::Network => write!(f, "Network"),
+            ErrorCategory::NotFound => write!(f, "Not found"),
+            ErrorCategory::Validation => write!(f, "Validation"),
+        }
+    }
+}
+
+/// Describes the kind of an error with a stable code, a category and an optional hint.
+///
+/// Codes read `E-<AREA>-<NUMBER>`, like `E-DB-002` for an id matching several tasks. A code
+/// never changes meaning; new kinds get new numbers.
+pub trait ErrorCode {
+    /// The stable code of the kind.
+    fn code(&self) -> &'static str;
+
+    /// What went wrong, broadly.
+    fn category(&self) -> ErrorCategory;
+
+    /// What the user can do about the error, if anything.
+    fn hint(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// The hint of errors of the database itself, whose details are only logged.
+pub(super) const DATABASE_HINT: &str =
+    "Run the command again with -vv to see the error of the database";
+
+/// The hint of errors caused by task records that `tmgr doctor` reports.
+pub(super) const DOCTOR_HINT: &str =
+    "Run 'tmgr doctor' to find the task records that cannot be read";
+// -- ErrorCategory --
+
 // -- TmgrError --
+/// The error of a command, as reported by the CLI.
+///
+/// It keeps the error of the command as its source, along with the stable code, the category
+/// and the hint of the kind of that error.
 #[derive(Debug)]
 pub struct TmgrError {
     kind: TmgrErrorKind,
     message: String,
+    code: &'static str,
+    category: ErrorCategory,
+    hint: Option<&'static str>,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
 }
 
 impl TmgrError {
     pub fn new(kind: TmgrErrorKind, message: String) -> Self {
-        Self { kind, message }
+        Self {
+            kind,
+            message,
+            code: "E-TMGR-001",
+            category: ErrorCategory::Internal,
+            hint: None,
+            source: None,
+        }
+    }
+
+    /// Takes the code, category and hint of the kind of the underlying error.
+    pub(super) fn with_code(mut self, kind: &impl ErrorCode) -> Self {
+        self.code = kind.code();
+        self.category = kind.category();
+        self.hint = kind.hint();
+        self
+    }
+
+    /// Keeps the underlying error, returned by `source`.
+    pub(super) fn with_source(
+        mut self,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// The command that failed.
+    pub fn kind(&self) -> &TmgrErrorKind {
+        &self.kind
+    }
+
+    /// The stable code of the error, like `E-DB-002`.
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// What went wrong, broadly, which decides the exit code of the CLI.
+    pub fn category(&self) -> ErrorCategory {
+        self.category
+    }
+
+    /// What the user can do about the error, if anything.
+    pub fn hint(&self) -> Option<&'static str> {
+        self.hint
     }
 }
 
@@ -366,15 +1019,40 @@ impl Display for TmgrError {
     }
 }
 
+impl std::error::Error for TmgrError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
 #[derive(Debug)]
 pub enum TmgrErrorKind {
     AddCommand,
+    AttachCommand,
+    BoardCommand,
     CompleteCommand,
+    CompletionsCommand,
+    ConfigCommand,
     DeleteCommand,
+    DoctorCommand,
+    GitHookCommand,
+    LinkCommand,
     ListCommand,
+    LspCommand,
+    ManpageCommand,
     MigrateCommand,
+    MoveCommand,
     NoteCommand,
+    OpenCommand,
+    QueryCommand,
+    ScanCommand,
+    ServeCommand,
+    ShellCommand,
+    StartBranchCommand,
     StatusCommand,
+    TuiCommand,
     UpdateCommand,
     UpgradeCommand,
     ViewCommand,
@@ -385,12 +1063,29 @@ impl Display for TmgrErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             TmgrErrorKind::AddCommand => write!(f, "Add command error"),
+            TmgrErrorKind::AttachCommand => write!(f, "Attach command error"),
+            TmgrErrorKind::BoardCommand => write!(f, "Board command error"),
             TmgrErrorKind::CompleteCommand => write!(f, "Complete command error"),
+            TmgrErrorKind::CompletionsCommand => write!(f, "Completions command error"),
+            TmgrErrorKind::ConfigCommand => write!(f, "Config command error"),
             TmgrErrorKind::DeleteCommand => write!(f, "Delete command error"),
+            TmgrErrorKind::DoctorCommand => write!(f, "Doctor command error"),
+            TmgrErrorKind::GitHookCommand => write!(f, "Git hook command error"),
+            TmgrErrorKind::LinkCommand => write!(f, "Link command error"),
             TmgrErrorKind::ListCommand => write!(f, "List command error"),
+            TmgrErrorKind::LspCommand => write!(f, "Lsp command error"),
+            TmgrErrorKind::ManpageCommand => write!(f, "Manpage command error"),
             TmgrErrorKind::MigrateCommand => write!(f, "Migrate command error"),
+            TmgrErrorKind::MoveCommand => write!(f, "Move command error"),
             TmgrErrorKind::NoteCommand => write!(f, "Note command error"),
+            TmgrErrorKind::OpenCommand => write!(f, "Open command error"),
+            TmgrErrorKind::QueryCommand => write!(f, "Query command error"),
+            TmgrErrorKind::ScanCommand => write!(f, "Scan command error"),
+            TmgrErrorKind::ServeCommand => write!(f, "Serve command error"),
+            TmgrErrorKind::ShellCommand => write!(f, "Shell command error"),
+            TmgrErrorKind::StartBranchCommand => write!(f, "Start branch command error"),
             TmgrErrorKind::StatusCommand => write!(f, "Status command error"),
+            TmgrErrorKind::TuiCommand => write!(f, "Tui command error"),
             TmgrErrorKind::UpdateCommand => write!(f, "Update command error"),
             TmgrErrorKind::UpgradeCommand => write!(f, "Upgrade command error"),
             TmgrErrorKind::ViewCommand => write!(f, "View command error"),
@@ -402,10 +1097,10 @@ impl Display for TmgrErrorKind {
 // -- TmgrError --
 
 // -- CommandResult --
+/// The outcome of a command: the message printed by the CLI and the typed value behind it.
 #[derive(Debug)]
-pub(super) struct CommandResult<T> {
+pub struct CommandResult<T> {
     message: String,
-    #[allow(dead_code)]
     result: T,
 }
 
@@ -414,13 +1109,19 @@ impl<T> CommandResult<T> {
         Self { message, result }
     }
 
-    pub(super) fn message(&self) -> &str {
+    /// The message the CLI prints for the command.
+    pub fn message(&self) -> &str {
         &self.message
     }
 
-    #[allow(dead_code)]
-    pub(super) fn result(&self) -> &T {
+    /// The value the command produced, like the task it changed.
+    pub fn result(&self) -> &T {
         &self.result
     }
+
+    /// Consumes the result, returning the value the command produced.
+    pub fn into_result(self) -> T {
+        self.result
+    }
 }
 // -- CommandResult --
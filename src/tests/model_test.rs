@@ -0,0 +1,37 @@
+use super::super::model::TaskPriority;
+use std::{collections::BTreeMap, str::FromStr};
+
+#[test]
+fn builtin_priorities_should_be_ordered_by_weight() {
+    assert!(TaskPriority::None < TaskPriority::Low);
+    assert!(TaskPriority::Low < TaskPriority::Medium);
+    assert!(TaskPriority::Medium < TaskPriority::High);
+    assert!(TaskPriority::High < TaskPriority::Critical);
+}
+
+#[test]
+fn given_shorthands_when_parsing_priority_then_builtin_levels_should_be_returned() {
+    assert_eq!(
+        TaskPriority::from_str("p0").unwrap(),
+        TaskPriority::Critical
+    );
+    assert_eq!(TaskPriority::from_str("p1").unwrap(), TaskPriority::High);
+    assert_eq!(TaskPriority::from_str("!!").unwrap(), TaskPriority::Medium);
+    assert_eq!(TaskPriority::from_str("!").unwrap(), TaskPriority::Low);
+    assert_eq!(TaskPriority::from_str("NONE").unwrap(), TaskPriority::None);
+}
+
+#[test]
+fn given_unknown_name_when_parsing_priority_then_error_should_be_returned() {
+    assert!(TaskPriority::from_str("not-a-level").is_err());
+}
+
+#[test]
+fn given_registered_custom_level_when_parsing_priority_then_custom_level_should_be_ordered_by_weight()
+ {
+    TaskPriority::register_custom_levels(&BTreeMap::from([("model-test-level".to_string(), 25)]));
+    let custom = TaskPriority::from_str("Model-Test-Level").unwrap();
+    assert_eq!(custom, TaskPriority::Custom("model-test-level".to_string()));
+    assert!(custom > TaskPriority::Medium);
+    assert!(custom < TaskPriority::High);
+}